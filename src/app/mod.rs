@@ -13,7 +13,7 @@ mod imp {
     use crate::{
         compose::textured::TexturedDotsDistribution,
         config,
-        pens::PenStyle,
+        pens::{stylusconfig::StylusButtonAction, PenStyle},
         sheet::format::MeasureUnit,
         sheet::{background::PatternStyle, format::PredefinedFormat},
         ui::{
@@ -122,6 +122,7 @@ mod imp {
             PatternStyle::static_type();
             UnitEntry::static_type();
             TexturedDotsDistribution::static_type();
+            StylusButtonAction::static_type();
 
             // Load the resources
             app.set_resource_base_path(Some(config::APP_IDPATH));
@@ -0,0 +1,326 @@
+#![warn(missing_debug_implementations)]
+
+/*
+Converts and exports Rnote and Xournal++ documents on the command line.
+GTK is never initialized, so it also runs without a display, e.g. in scripts or on a server.
+*/
+
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use anyhow::Context;
+use rnote::config;
+use rnote::render::Renderer;
+use rnote::sheet::Sheet;
+
+const USAGE: &str = "Converts and exports Rnote and Xournal++ documents without opening the app.
+
+USAGE:
+    rnote-cli [OPTIONS] <INPUT> <OUTPUT>
+
+The formats are determined by the file extensions.
+    input:  .rnote, .xopp
+    output: .rnote, .xopp, .pdf, .svg, .png
+
+When more than one page is exported as png, every page is written into a separate file,
+with the page number appended to the output file name (e.g. notes-1.png, notes-2.png, ..).
+
+OPTIONS:
+    -p, --pages <PAGES>    The pages to export, starting at 1. Either a single page (e.g. 3)
+                           or a range (e.g. 2-5, 4-). Defaults to all pages
+    -d, --dpi <DPI>        The resolution of png exports. Defaults to the dpi of the document
+    -h, --help             Prints this help
+    -V, --version          Prints the version";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InputFormat {
+    Rnote,
+    Xopp,
+}
+
+impl InputFormat {
+    fn from_path(path: &Path) -> Result<Self, anyhow::Error> {
+        match extension(path).as_deref() {
+            Some("rnote") => Ok(Self::Rnote),
+            Some("xopp") => Ok(Self::Xopp),
+            _ => Err(anyhow::anyhow!(
+                "unsupported input file `{}`, expected a .rnote or .xopp file",
+                path.display()
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Rnote,
+    Xopp,
+    Pdf,
+    Svg,
+    Png,
+}
+
+impl OutputFormat {
+    fn from_path(path: &Path) -> Result<Self, anyhow::Error> {
+        match extension(path).as_deref() {
+            Some("rnote") => Ok(Self::Rnote),
+            Some("xopp") => Ok(Self::Xopp),
+            Some("pdf") => Ok(Self::Pdf),
+            Some("svg") => Ok(Self::Svg),
+            Some("png") => Ok(Self::Png),
+            _ => Err(anyhow::anyhow!(
+                "unsupported output file `{}`, expected a .rnote, .xopp, .pdf, .svg or .png file",
+                path.display()
+            )),
+        }
+    }
+}
+
+/// The pages as given on the command line, starting at 1. The end is inclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PageRange {
+    first: u32,
+    last: Option<u32>,
+}
+
+impl PageRange {
+    fn parse(s: &str) -> Result<Self, anyhow::Error> {
+        let parse_page = |page: &str| -> Result<u32, anyhow::Error> {
+            let page = page
+                .trim()
+                .parse::<u32>()
+                .with_context(|| format!("invalid page `{}` in page range `{}`", page, s))?;
+
+            if page == 0 {
+                return Err(anyhow::anyhow!("pages start at 1, got page range `{}`", s));
+            }
+            Ok(page)
+        };
+
+        let range = if let Some((first, last)) = s.split_once('-') {
+            Self {
+                first: parse_page(first)?,
+                last: if last.trim().is_empty() {
+                    None
+                } else {
+                    Some(parse_page(last)?)
+                },
+            }
+        } else {
+            let page = parse_page(s)?;
+            Self {
+                first: page,
+                last: Some(page),
+            }
+        };
+
+        if let Some(last) = range.last {
+            if last < range.first {
+                return Err(anyhow::anyhow!(
+                    "the last page comes before the first in page range `{}`",
+                    s
+                ));
+            }
+        }
+
+        Ok(range)
+    }
+
+    /// The zero based page indices, checked against the number of pages of the sheet
+    fn to_page_indices(self, n_pages: u32) -> Result<Range<u32>, anyhow::Error> {
+        if self.first > n_pages {
+            return Err(anyhow::anyhow!(
+                "page {} does not exist, the document has {} pages",
+                self.first,
+                n_pages
+            ));
+        }
+        let last = self.last.unwrap_or(n_pages);
+        if last > n_pages {
+            return Err(anyhow::anyhow!(
+                "page {} does not exist, the document has {} pages",
+                last,
+                n_pages
+            ));
+        }
+
+        Ok(self.first - 1..last)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Args {
+    input: PathBuf,
+    output: PathBuf,
+    pages: Option<PageRange>,
+    dpi: Option<f64>,
+}
+
+#[derive(Debug, Clone)]
+enum Command {
+    Convert(Args),
+    Help,
+    Version,
+}
+
+impl Command {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, anyhow::Error> {
+        let mut paths = vec![];
+        let mut pages = None;
+        let mut dpi = None;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(Self::Help),
+                "-V" | "--version" => return Ok(Self::Version),
+                "-p" | "--pages" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| anyhow::anyhow!("missing value for `{}`", arg))?;
+                    pages = Some(PageRange::parse(&value)?);
+                }
+                "-d" | "--dpi" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| anyhow::anyhow!("missing value for `{}`", arg))?;
+                    let value = value
+                        .parse::<f64>()
+                        .with_context(|| format!("invalid dpi `{}`", value))?;
+                    if !(value.is_finite() && value > 0.0) {
+                        return Err(anyhow::anyhow!("the dpi must be larger than 0"));
+                    }
+                    dpi = Some(value);
+                }
+                _ if arg.starts_with('-') && arg.len() > 1 => {
+                    return Err(anyhow::anyhow!("unknown option `{}`", arg));
+                }
+                _ => paths.push(PathBuf::from(arg)),
+            }
+        }
+
+        if paths.len() != 2 {
+            return Err(anyhow::anyhow!(
+                "expected an input and an output file, got {} file arguments",
+                paths.len()
+            ));
+        }
+        let output = paths.pop().unwrap();
+        let input = paths.pop().unwrap();
+
+        Ok(Self::Convert(Args {
+            input,
+            output,
+            pages,
+            dpi,
+        }))
+    }
+}
+
+fn main() {
+    pretty_env_logger::init();
+
+    let result = Command::parse(std::env::args().skip(1)).and_then(|command| match command {
+        Command::Convert(args) => convert(&args),
+        Command::Help => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        Command::Version => {
+            println!("rnote-cli {}", config::APP_VERSION);
+            Ok(())
+        }
+    });
+
+    if let Err(e) = result {
+        eprintln!("Error: {:?}\n\nSee `rnote-cli --help` for usage.", e);
+        std::process::exit(1);
+    }
+}
+
+fn convert(args: &Args) -> Result<(), anyhow::Error> {
+    let input_format = InputFormat::from_path(&args.input)?;
+    let output_format = OutputFormat::from_path(&args.output)?;
+
+    if args.dpi.is_some() && output_format != OutputFormat::Png {
+        return Err(anyhow::anyhow!("`--dpi` only applies to png exports"));
+    }
+
+    let bytes = std::fs::read(&args.input)
+        .with_context(|| format!("failed to read `{}`", args.input.display()))?;
+
+    let mut sheet = Sheet::default();
+    match input_format {
+        InputFormat::Rnote => sheet.open_sheet_from_rnote_bytes(&bytes),
+        InputFormat::Xopp => sheet.open_from_xopp_bytes(&bytes),
+    }
+    .with_context(|| format!("failed to load `{}`", args.input.display()))?;
+
+    let first_page = if let Some(pages) = args.pages {
+        let page_indices = pages.to_page_indices(sheet.calc_n_pages())?;
+        let first_page = page_indices.start + 1;
+
+        sheet = sheet.clone_pages(page_indices);
+        first_page
+    } else {
+        1
+    };
+
+    let file_name = args
+        .output
+        .file_name()
+        .map(|file_name| file_name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    match output_format {
+        OutputFormat::Rnote => write(&args.output, &sheet.save_sheet_as_rnote_bytes(&file_name)?),
+        OutputFormat::Xopp => {
+            let renderer = Arc::new(RwLock::new(Renderer::default()));
+            write(
+                &args.output,
+                &sheet.export_sheet_as_xopp_bytes(&file_name, renderer)?,
+            )
+        }
+        OutputFormat::Pdf => write(&args.output, &sheet.export_sheet_in_pdf_bytes(&file_name)?),
+        OutputFormat::Svg => write(&args.output, sheet.export_sheet_as_svg_string()?.as_bytes()),
+        OutputFormat::Png => {
+            let zoom = args.dpi.map(|dpi| dpi / sheet.format.dpi).unwrap_or(1.0);
+            let pages = sheet.export_pages_as_png_bytes(zoom)?;
+
+            if pages.len() == 1 {
+                write(&args.output, &pages[0])
+            } else {
+                pages
+                    .iter()
+                    .zip(first_page..)
+                    .try_for_each(|(png_bytes, page)| {
+                        write(&numbered_path(&args.output, page), png_bytes)
+                    })
+            }
+        }
+    }
+}
+
+fn write(path: &Path, bytes: &[u8]) -> Result<(), anyhow::Error> {
+    std::fs::write(path, bytes).with_context(|| format!("failed to write `{}`", path.display()))
+}
+
+fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+}
+
+/// Appends the page number to the file stem, e.g. notes.png becomes notes-2.png
+fn numbered_path(path: &Path, page: u32) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    match path.extension() {
+        Some(extension) => {
+            path.with_file_name(format!("{}-{}.{}", stem, page, extension.to_string_lossy()))
+        }
+        None => path.with_file_name(format!("{}-{}", stem, page)),
+    }
+}
@@ -0,0 +1,84 @@
+#![warn(missing_debug_implementations)]
+
+/*
+Generates thumbnails for .rnote files for file managers, see the installed .thumbnailer entry.
+Called as `rnote-thumbnailer -s <SIZE> <INPUT> <OUTPUT>`, it renders the first page into a png which fits into the size.
+*/
+
+use std::path::PathBuf;
+
+use anyhow::Context;
+use rnote::render::{self, tinyskia::TinySkiaRenderer};
+use rnote::sheet::Sheet;
+
+const USAGE: &str = "USAGE:
+    rnote-thumbnailer [-s <SIZE>] <INPUT> <OUTPUT>
+
+Renders the first page of the .rnote file INPUT into the png file OUTPUT.
+
+OPTIONS:
+    -s, --size <SIZE>    The size in pixels of the square the thumbnail fits into. Defaults to 256
+    -h, --help           Prints this help";
+
+const SIZE_DEFAULT: u32 = 256;
+
+fn main() {
+    pretty_env_logger::init();
+
+    if let Err(e) = run(std::env::args().skip(1)) {
+        eprintln!("Error: {:?}\n\n{}", e, USAGE);
+        std::process::exit(1);
+    }
+}
+
+fn run(mut args: impl Iterator<Item = String>) -> Result<(), anyhow::Error> {
+    let mut paths = vec![];
+    let mut size = SIZE_DEFAULT;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return Ok(());
+            }
+            "-s" | "--size" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| anyhow::anyhow!("missing value for `{}`", arg))?;
+                size = value
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|&size| size > 0)
+                    .ok_or_else(|| anyhow::anyhow!("invalid size `{}`", value))?;
+            }
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+
+    if paths.len() != 2 {
+        return Err(anyhow::anyhow!(
+            "expected an input and an output file, got {} file arguments",
+            paths.len()
+        ));
+    }
+    let output = paths.pop().unwrap();
+    let input = paths.pop().unwrap();
+
+    let bytes =
+        std::fs::read(&input).with_context(|| format!("failed to read `{}`", input.display()))?;
+
+    let sheet = Sheet::from_rnote_bytes(&bytes)
+        .with_context(|| format!("failed to load `{}`", input.display()))?;
+
+    let thumbnail = sheet
+        .gen_thumbnail(size, &TinySkiaRenderer::with_system_fonts())?
+        .ok_or_else(|| anyhow::anyhow!("`{}` has nothing to render", input.display()))?;
+
+    std::fs::write(
+        &output,
+        render::image_into_bytes(thumbnail, image::ImageOutputFormat::Png)?,
+    )
+    .with_context(|| format!("failed to write `{}`", output.display()))?;
+
+    Ok(())
+}
@@ -0,0 +1,144 @@
+use super::{color::Color, curves};
+
+use geo::prelude::*;
+use serde::{Deserialize, Serialize};
+use svg::node::element::path;
+
+/// The Options of how a calligraphy stroke should look.
+/// The stroke is drawn with a flat nib of the given width, held at the nib angle.
+/// Moving perpendicular to the nib produces the full width, moving along it only the nib thickness.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default, rename = "calligraphy_options")]
+pub struct CalligraphyOptions {
+    /// The width of the nib
+    #[serde(rename = "width")]
+    pub width: f64,
+    /// The color of the stroke
+    #[serde(rename = "stroke_color")]
+    pub stroke_color: Option<Color>,
+    /// The angle of the nib (rad), relative to the x axis
+    #[serde(rename = "nib_angle")]
+    pub nib_angle: f64,
+    /// The thickness of the nib, as ratio of the width (between 0.0 and 1.0)
+    #[serde(rename = "nib_thickness")]
+    pub nib_thickness: f64,
+    /// How much the stylus tilt widens the nib, if the tablet reports it. 0.0 disables it
+    #[serde(rename = "tilt_sensitivity")]
+    pub tilt_sensitivity: f64,
+}
+
+impl Default for CalligraphyOptions {
+    fn default() -> Self {
+        Self {
+            width: Self::WIDTH_DEFAULT,
+            stroke_color: Some(Self::COLOR_DEFAULT),
+            nib_angle: Self::NIB_ANGLE_DEFAULT,
+            nib_thickness: Self::NIB_THICKNESS_DEFAULT,
+            tilt_sensitivity: Self::TILT_SENSITIVITY_DEFAULT,
+        }
+    }
+}
+
+impl CalligraphyOptions {
+    /// The default width
+    pub const WIDTH_DEFAULT: f64 = 6.0;
+    /// The default color
+    pub const COLOR_DEFAULT: Color = Color {
+        r: 0.0,
+        g: 0.0,
+        b: 0.0,
+        a: 1.0,
+    };
+    /// The default nib angle (rad), 45 degrees
+    pub const NIB_ANGLE_DEFAULT: f64 = std::f64::consts::FRAC_PI_4;
+    /// The default nib thickness ratio
+    pub const NIB_THICKNESS_DEFAULT: f64 = 0.15;
+    /// The default tilt sensitivity
+    pub const TILT_SENSITIVITY_DEFAULT: f64 = 0.5;
+
+    /// The nib vector (from the center to one end of the nib) for the given pressure and optional stylus tilt
+    pub fn nib_vector(&self, pressure: f64, tilt: Option<na::Vector2<f64>>) -> na::Vector2<f64> {
+        let tilt_factor = tilt.map_or(1.0, |tilt| {
+            1.0 + self.tilt_sensitivity * tilt.magnitude().clamp(0.0, 1.0)
+        });
+
+        na::Rotation2::new(self.nib_angle)
+            * na::vector![self.width * pressure * tilt_factor * 0.5, 0.0]
+    }
+
+    /// The effective width of a stroke moving in the given direction, with the given pressure and optional tilt
+    pub fn width_for_direction(
+        &self,
+        direction: na::Vector2<f64>,
+        pressure: f64,
+        tilt: Option<na::Vector2<f64>>,
+    ) -> f64 {
+        let nib = self.nib_vector(pressure, tilt);
+        let thickness = self.width * pressure * self.nib_thickness.clamp(0.0, 1.0);
+
+        if direction.magnitude() > 0.0 {
+            // The projection of the nib onto the normal of the direction
+            let norm = super::geometry::vector2_unit_norm(direction);
+            (2.0 * nib.dot(&norm).abs()).max(thickness)
+        } else {
+            (2.0 * nib.magnitude()).max(thickness)
+        }
+    }
+
+    /// The corners of the nib at the given position
+    fn nib_corners(
+        &self,
+        pos: na::Vector2<f64>,
+        pressure: f64,
+        tilt: Option<na::Vector2<f64>>,
+    ) -> [na::Vector2<f64>; 4] {
+        let nib = self.nib_vector(pressure, tilt);
+        let thickness = self.width * pressure * self.nib_thickness.clamp(0.0, 1.0);
+        let nib_norm = super::geometry::vector2_unit_norm(nib) * thickness * 0.5;
+
+        [
+            pos + nib + nib_norm,
+            pos + nib - nib_norm,
+            pos - nib - nib_norm,
+            pos - nib + nib_norm,
+        ]
+    }
+}
+
+/// Composes the outline of the nib swept along the line, from the start to the end with interpolated pressures and tilts.
+/// The outline is the convex hull of the nib corners at the start and the end, so overlapping segments merge when filled with the nonzero rule.
+pub fn compose_line(
+    line: curves::Line,
+    (start_pressure, end_pressure): (f64, f64),
+    (start_tilt, end_tilt): (Option<na::Vector2<f64>>, Option<na::Vector2<f64>>),
+    options: &CalligraphyOptions,
+) -> Vec<path::Command> {
+    let mut commands = Vec::new();
+
+    let corners = options
+        .nib_corners(line.start, start_pressure, start_tilt)
+        .into_iter()
+        .chain(options.nib_corners(line.end, end_pressure, end_tilt))
+        .map(|corner| geo::Point::new(corner[0], corner[1]))
+        .collect::<Vec<geo::Point<f64>>>();
+
+    let hull = geo::MultiPoint::from(corners).convex_hull();
+    let mut hull_coords = hull.exterior().points_iter();
+
+    if let Some(first) = hull_coords.next() {
+        commands.push(path::Command::Move(
+            path::Position::Absolute,
+            path::Parameters::from((first.x(), first.y())),
+        ));
+
+        for coord in hull_coords {
+            commands.push(path::Command::Line(
+                path::Position::Absolute,
+                path::Parameters::from((coord.x(), coord.y())),
+            ));
+        }
+        commands.push(path::Command::Close);
+    }
+
+    commands
+}
@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 
 use super::geometry;
 use super::shapes::Rectangle;
-use super::transformable::{Transform, Transformable};
+use super::transformable::{self, Transform, Transformable};
 
 #[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
 #[serde(default, rename = "line")]
@@ -35,6 +35,11 @@ impl Transformable for Line {
         self.start = mid - half_vec.component_mul(&scale);
         self.end = mid + half_vec.component_mul(&scale);
     }
+
+    fn transform_wrt_point(&mut self, matrix: na::Matrix2<f64>, center: na::Point2<f64>) {
+        self.start = transformable::transform_pos_wrt_point(matrix, self.start, center);
+        self.end = transformable::transform_pos_wrt_point(matrix, self.end, center);
+    }
 }
 
 impl Line {
@@ -210,6 +210,13 @@ pub fn aabb_translate(aabb: AABB, offset: na::Vector2<f64>) -> AABB {
     aabb.transform_by(&na::convert(na::Translation2::from(offset)))
 }
 
+/// The isometry which rotates around the given center
+pub fn rotation_around_center(angle: f64, center: na::Point2<f64>) -> na::Isometry2<f64> {
+    na::Isometry2::translation(center[0], center[1])
+        * na::Isometry2::rotation(angle)
+        * na::Isometry2::translation(-center[0], -center[1])
+}
+
 /// Shrinks the aabb to the nearest integer of its vertices
 pub fn aabb_floor(aabb: AABB) -> AABB {
     AABB::new(
@@ -310,3 +317,77 @@ pub fn scale_inner_bounds_to_new_outer_bounds(
         ],
     )
 }
+
+/// The distance of the point to the line segment between start and end
+pub fn point_segment_distance(
+    pos: na::Vector2<f64>,
+    start: na::Vector2<f64>,
+    end: na::Vector2<f64>,
+) -> f64 {
+    let delta = end - start;
+    let length_squared = delta.norm_squared();
+    if length_squared == 0.0 {
+        return (pos - start).magnitude();
+    }
+
+    let t = ((pos - start).dot(&delta) / length_squared).clamp(0.0, 1.0);
+    (pos - (start + delta * t)).magnitude()
+}
+
+/// The distance of the point to the aabb, 0.0 if it is inside
+pub fn point_aabb_distance(pos: na::Vector2<f64>, aabb: AABB) -> f64 {
+    let dx = (aabb.mins[0] - pos[0]).max(pos[0] - aabb.maxs[0]).max(0.0);
+    let dy = (aabb.mins[1] - pos[1]).max(pos[1] - aabb.maxs[1]).max(0.0);
+
+    (dx * dx + dy * dy).sqrt()
+}
+
+/// Whether the line segment between start and end intersects the aabb, by clipping it to the aabb on both axes
+pub fn segment_intersects_aabb(start: na::Vector2<f64>, end: na::Vector2<f64>, aabb: AABB) -> bool {
+    let delta = end - start;
+    let mut t_min: f64 = 0.0;
+    let mut t_max: f64 = 1.0;
+
+    for axis in 0..2 {
+        if delta[axis].abs() < f64::EPSILON {
+            if start[axis] < aabb.mins[axis] || start[axis] > aabb.maxs[axis] {
+                return false;
+            }
+        } else {
+            let t_first = (aabb.mins[axis] - start[axis]) / delta[axis];
+            let t_second = (aabb.maxs[axis] - start[axis]) / delta[axis];
+
+            t_min = t_min.max(t_first.min(t_second));
+            t_max = t_max.min(t_first.max(t_second));
+            if t_min > t_max {
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
+/// The distance of the line segment between start and end to the aabb, 0.0 if they intersect
+pub fn segment_aabb_distance(start: na::Vector2<f64>, end: na::Vector2<f64>, aabb: AABB) -> f64 {
+    if segment_intersects_aabb(start, end, aabb) {
+        return 0.0;
+    }
+
+    // If they don't intersect, the closest points always include an endpoint of the segment or a corner of the aabb
+    let corners = [
+        na::vector![aabb.mins[0], aabb.mins[1]],
+        na::vector![aabb.maxs[0], aabb.mins[1]],
+        na::vector![aabb.maxs[0], aabb.maxs[1]],
+        na::vector![aabb.mins[0], aabb.maxs[1]],
+    ];
+
+    corners
+        .iter()
+        .map(|&corner| point_segment_distance(corner, start, end))
+        .chain([
+            point_aabb_distance(start, aabb),
+            point_aabb_distance(end, aabb),
+        ])
+        .fold(f64::MAX, f64::min)
+}
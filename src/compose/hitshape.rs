@@ -0,0 +1,134 @@
+use geo::prelude::*;
+use p2d::bounding_volume::{BoundingVolume, AABB};
+
+use super::geometry;
+use super::regionfill::BoundaryPath;
+
+/// The geometry of a stroke as it is composed, used for exact hit-testing.
+/// Paths are hit within half their width around their center line, areas are hit anywhere inside.
+#[derive(Debug, Clone, Default)]
+pub struct HitShape {
+    pub paths: Vec<BoundaryPath>,
+    pub areas: Vec<geo::Polygon<f64>>,
+}
+
+impl HitShape {
+    pub fn new(paths: Vec<BoundaryPath>, areas: Vec<geo::Polygon<f64>>) -> Self {
+        Self { paths, areas }
+    }
+
+    /// A hit shape which is hit anywhere inside the bounds
+    pub fn from_bounds(bounds: AABB) -> Self {
+        Self::new(vec![], vec![geometry::p2d_aabb_to_geo_polygon(bounds)])
+    }
+
+    /// Whether the shape intersects the aabb
+    pub fn intersects_aabb(&self, aabb: AABB) -> bool {
+        self.paths.iter().any(|path| {
+            let half_width = path.width * 0.5;
+
+            path_segments(path)
+                .any(|(start, end)| geometry::segment_aabb_distance(start, end, aabb) <= half_width)
+        }) || {
+            let aabb_polygon = geometry::p2d_aabb_to_geo_polygon(aabb);
+            self.areas.iter().any(|area| area.intersects(&aabb_polygon))
+        }
+    }
+
+    /// Whether the position is on the shape. The tolerance widens the shape
+    pub fn contains_point(&self, pos: na::Vector2<f64>, tolerance: f64) -> bool {
+        self.paths.iter().any(|path| {
+            let hit_distance = path.width * 0.5 + tolerance;
+
+            path_segments(path).any(|(start, end)| {
+                geometry::point_segment_distance(pos, start, end) <= hit_distance
+            })
+        }) || self.areas.iter().any(|area| {
+            area.contains(&geo::Point::new(pos[0], pos[1]))
+                || polygon_rings(area).any(|ring| {
+                    ring.lines().any(|line| {
+                        geometry::point_segment_distance(
+                            pos,
+                            na::vector![line.start.x, line.start.y],
+                            na::vector![line.end.x, line.end.y],
+                        ) <= tolerance
+                    })
+                })
+        })
+    }
+
+    /// Whether the shape touches the polygon. For paths only their center line is tested
+    pub fn intersects_polygon(&self, polygon: &geo::Polygon<f64>) -> bool {
+        self.paths.iter().any(|path| {
+            path_segments(path).any(|(start, end)| {
+                polygon.intersects(&geo::Line::new(
+                    geo::Coordinate {
+                        x: start[0],
+                        y: start[1],
+                    },
+                    geo::Coordinate {
+                        x: end[0],
+                        y: end[1],
+                    },
+                ))
+            })
+        }) || self.areas.iter().any(|area| polygon.intersects(area))
+    }
+
+    /// Whether the shape is entirely inside the polygon. For paths only their points are tested
+    pub fn contained_in_polygon(&self, polygon: &geo::Polygon<f64>) -> bool {
+        if self.paths.is_empty() && self.areas.is_empty() {
+            return false;
+        }
+
+        self.paths.iter().all(|path| {
+            path.points
+                .iter()
+                .all(|point| polygon.contains(&geo::Point::new(point[0], point[1])))
+        }) && self.areas.iter().all(|area| polygon.contains(area))
+    }
+
+    /// The bounds of every path segment and area, used for visual debugging
+    pub fn hitboxes(&self) -> Vec<AABB> {
+        self.paths
+            .iter()
+            .flat_map(|path| {
+                let half_width = path.width * 0.5;
+
+                path_segments(path)
+                    .map(move |(start, end)| {
+                        geometry::aabb_new_positive(na::Point2::from(start), na::Point2::from(end))
+                            .loosened(half_width)
+                    })
+                    .collect::<Vec<AABB>>()
+            })
+            .chain(self.areas.iter().map(|area| {
+                let mut bounds = AABB::new_invalid();
+                for point in area.exterior().points_iter() {
+                    bounds.take_point(na::point![point.x(), point.y()]);
+                }
+                bounds
+            }))
+            .collect()
+    }
+}
+
+/// The segments of the path. A path with a single point is a segment of zero length
+fn path_segments(
+    path: &BoundaryPath,
+) -> impl Iterator<Item = (na::Vector2<f64>, na::Vector2<f64>)> + '_ {
+    let single = if path.points.len() == 1 {
+        Some((path.points[0], path.points[0]))
+    } else {
+        None
+    };
+
+    path.points
+        .windows(2)
+        .map(|points| (points[0], points[1]))
+        .chain(single)
+}
+
+fn polygon_rings(polygon: &geo::Polygon<f64>) -> impl Iterator<Item = &geo::LineString<f64>> {
+    std::iter::once(polygon.exterior()).chain(polygon.interiors().iter())
+}
@@ -2,12 +2,17 @@ use p2d::bounding_volume::AABB;
 use rand::SeedableRng;
 use svg::node::{self, element};
 
+pub mod calligraphy;
 pub mod color;
 pub mod curves;
 pub mod geometry;
+pub mod hitshape;
+pub mod pencil;
+pub mod regionfill;
 pub mod rough;
 pub mod shapes;
 pub mod smooth;
+pub mod snapping;
 pub mod textured;
 pub mod transformable;
 
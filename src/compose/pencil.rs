@@ -0,0 +1,172 @@
+use super::{color::Color, curves, smooth};
+use crate::compose;
+
+use rand_distr::{Distribution, Uniform};
+use serde::{Deserialize, Serialize};
+use svg::node::element::{self, path};
+
+/// The Options of how a pencil stroke should look.
+/// The stroke is filled with a paper grain pattern which is anchored in sheet space, so it tiles seamlessly across strokes and stays stable when strokes are transformed.
+/// Every grain dot has a threshold, higher pressure reveals more dots of the grain.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default, rename = "pencil_options")]
+pub struct PencilOptions {
+    /// An optional seed to generate reproducable strokes
+    #[serde(rename = "seed")]
+    pub seed: Option<u64>,
+    /// The width
+    #[serde(rename = "width")]
+    pub width: f64,
+    /// The color of the stroke
+    #[serde(rename = "stroke_color")]
+    pub stroke_color: Option<Color>,
+    /// The seed of the paper grain. Strokes with the same grain seed share the same grain
+    #[serde(rename = "grain_seed")]
+    pub grain_seed: u64,
+    /// Amount of grain dots per 10x10 area
+    #[serde(rename = "grain_density")]
+    pub grain_density: f64,
+    /// The radius of the grain dots
+    #[serde(rename = "grain_radius")]
+    pub grain_radius: f64,
+}
+
+impl Default for PencilOptions {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            width: Self::WIDTH_DEFAULT,
+            stroke_color: Some(Self::COLOR_DEFAULT),
+            grain_seed: Self::GRAIN_SEED_DEFAULT,
+            grain_density: Self::GRAIN_DENSITY_DEFAULT,
+            grain_radius: Self::GRAIN_RADIUS_DEFAULT,
+        }
+    }
+}
+
+impl PencilOptions {
+    /// The default width
+    pub const WIDTH_DEFAULT: f64 = 2.0;
+    /// The default color
+    pub const COLOR_DEFAULT: Color = Color {
+        r: 0.2,
+        g: 0.2,
+        b: 0.2,
+        a: 1.0,
+    };
+    /// The default grain seed
+    pub const GRAIN_SEED_DEFAULT: u64 = 0x5eed_9a9e;
+    /// The default grain density
+    pub const GRAIN_DENSITY_DEFAULT: f64 = 120.0;
+    /// The default grain dot radius
+    pub const GRAIN_RADIUS_DEFAULT: f64 = 0.5;
+    /// The size of a grain tile in sheet coordinates
+    pub const GRAIN_TILE_SIZE: f64 = 12.0;
+    /// The amount of grain levels the pressure is quantized to
+    pub const GRAIN_LEVELS: u32 = 8;
+    /// The fraction of grain dots which are visible independent of the pressure
+    pub const GRAIN_COVERAGE_MIN: f64 = 0.2;
+
+    /// The grain level for the given pressure, between 1 and GRAIN_LEVELS
+    pub fn grain_level(&self, pressure: f64) -> u32 {
+        ((pressure.clamp(0.0, 1.0) * f64::from(Self::GRAIN_LEVELS)).ceil() as u32)
+            .clamp(1, Self::GRAIN_LEVELS)
+    }
+
+    /// The id of the grain pattern. It is unique for the color, grain and level, so identical patterns in a document are interchangeable
+    pub fn grain_pattern_id(&self, level: u32) -> String {
+        format!(
+            "rnote-pencil-grain-{:08x}-{:x}-{:x}-{:x}-{}",
+            self.stroke_color.map_or(0, |color| color.to_u32()),
+            self.grain_seed,
+            self.grain_density.to_bits(),
+            self.grain_radius.to_bits(),
+            level
+        )
+    }
+}
+
+/// Composes the grain pattern for the given level. The pattern is anchored at the offset, which is the origin of the sheet
+pub fn compose_grain_pattern(
+    level: u32,
+    offset: na::Vector2<f64>,
+    options: &PencilOptions,
+) -> element::Pattern {
+    // The grain must only depend on the grain options, not on the stroke seed
+    let mut rng = compose::new_rng_default_pcg64(Some(options.grain_seed));
+
+    let tile_size = PencilOptions::GRAIN_TILE_SIZE;
+    let n_dots = (tile_size * tile_size * 0.01 * options.grain_density).round() as i32;
+    let coverage = PencilOptions::GRAIN_COVERAGE_MIN
+        + (1.0 - PencilOptions::GRAIN_COVERAGE_MIN)
+            * (f64::from(level) / f64::from(PencilOptions::GRAIN_LEVELS));
+
+    let distr_pos = Uniform::from(0.0..tile_size);
+    let distr_radius = Uniform::from(options.grain_radius * 0.6..options.grain_radius * 1.4);
+    let distr_threshold = Uniform::from(0.0..1.0);
+
+    let fill = options
+        .stroke_color
+        .map_or(String::from(""), |color| color.to_css_color());
+
+    let mut pattern = element::Pattern::new()
+        .set("id", options.grain_pattern_id(level))
+        .set("patternUnits", "userSpaceOnUse")
+        .set("x", offset[0])
+        .set("y", offset[1])
+        .set("width", tile_size)
+        .set("height", tile_size);
+
+    for _ in 0..n_dots {
+        // Always sample every value, so the dots are identical across all levels
+        let pos = na::vector![distr_pos.sample(&mut rng), distr_pos.sample(&mut rng)];
+        let radius = distr_radius.sample(&mut rng);
+        let threshold: f64 = distr_threshold.sample(&mut rng);
+
+        if threshold >= coverage {
+            continue;
+        }
+
+        // Dots on the edges are wrapped around, so the tiles are seamless
+        for x_wrap in [-tile_size, 0.0, tile_size] {
+            for y_wrap in [-tile_size, 0.0, tile_size] {
+                let wrapped_pos = pos + na::vector![x_wrap, y_wrap];
+
+                if wrapped_pos[0] + radius < 0.0
+                    || wrapped_pos[0] - radius > tile_size
+                    || wrapped_pos[1] + radius < 0.0
+                    || wrapped_pos[1] - radius > tile_size
+                {
+                    continue;
+                }
+
+                pattern = pattern.add(
+                    element::Circle::new()
+                        .set("cx", wrapped_pos[0])
+                        .set("cy", wrapped_pos[1])
+                        .set("r", radius)
+                        .set("fill", fill.as_str()),
+                );
+            }
+        }
+    }
+
+    pattern
+}
+
+/// Composes the outline of a line with variable width. It is intended to be filled with the grain pattern
+pub fn compose_line(
+    line: curves::Line,
+    width_start: f64,
+    width_end: f64,
+    options: &PencilOptions,
+) -> Vec<path::Command> {
+    let smooth_options = smooth::SmoothOptions {
+        seed: options.seed,
+        width: options.width,
+        stroke_color: options.stroke_color,
+        fill_color: None,
+    };
+
+    smooth::compose_line_variable_width(line, width_start, width_end, true, &smooth_options)
+}
@@ -0,0 +1,439 @@
+use std::collections::{HashMap, VecDeque};
+
+use geo::algorithm::simplify::Simplify;
+use p2d::bounding_volume::AABB;
+
+/// The max. amount of cells of the grid into which the boundaries get rasterized
+const GRID_CELLS_MAX: f64 = 1_000_000.0;
+
+/// A polyline with a width, which bounds the regions that can be filled
+#[derive(Debug, Clone)]
+pub struct BoundaryPath {
+    pub points: Vec<na::Vector2<f64>>,
+    pub width: f64,
+}
+
+impl BoundaryPath {
+    pub fn new(points: Vec<na::Vector2<f64>>, width: f64) -> Self {
+        Self { points, width }
+    }
+}
+
+/// Finds the closed region around pos which is bounded by the paths.
+/// The paths are rasterized into a grid spanning search_bounds, with gaps smaller than gap_tolerance closed.
+/// The region is flood filled from pos, then grown by overlap so that it reaches below the boundaries.
+/// Returns None if pos is on a boundary or the region is not closed inside search_bounds.
+pub fn enclosed_region(
+    paths: &[BoundaryPath],
+    pos: na::Vector2<f64>,
+    search_bounds: AABB,
+    cell_size: f64,
+    gap_tolerance: f64,
+    overlap: f64,
+) -> Option<geo::Polygon<f64>> {
+    let mut grid = Grid::new(search_bounds, cell_size)?;
+
+    for path in paths.iter() {
+        grid.rasterize_path(path, gap_tolerance);
+    }
+
+    let (start_x, start_y) = grid.cell_at(pos)?;
+    let mut region = grid.flood_fill(start_x, start_y)?;
+
+    let dilations = (overlap / grid.cell_size).ceil().max(0.0) as usize;
+    for _ in 0..dilations {
+        region = grid.dilate(&region);
+    }
+
+    let mut rings = grid.trace_rings(&region);
+    if rings.is_empty() {
+        return None;
+    }
+
+    // The ring with the largest area is the exterior, all others are holes
+    rings.sort_by(|first, second| {
+        ring_area(second)
+            .abs()
+            .partial_cmp(&ring_area(first).abs())
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    let mut rings = rings.into_iter().map(|ring| {
+        geo::LineString::from(
+            ring.into_iter()
+                .map(|(x, y)| {
+                    let coord = grid.origin + na::vector![x as f64, y as f64] * grid.cell_size;
+                    (coord[0], coord[1])
+                })
+                .collect::<Vec<(f64, f64)>>(),
+        )
+    });
+
+    let exterior = rings.next()?;
+    let interiors = rings.collect::<Vec<geo::LineString<f64>>>();
+
+    Some(geo::Polygon::new(exterior, interiors).simplify(&(grid.cell_size * 0.75)))
+}
+
+/// the shoelace area of a ring of grid vertices
+fn ring_area(ring: &[(i64, i64)]) -> f64 {
+    let mut area = 0.0;
+    for (i, first) in ring.iter().enumerate() {
+        let second = ring[(i + 1) % ring.len()];
+        area += (first.0 * second.1 - second.0 * first.1) as f64;
+    }
+    area * 0.5
+}
+
+fn distance_to_segment(
+    point: na::Vector2<f64>,
+    start: na::Vector2<f64>,
+    end: na::Vector2<f64>,
+) -> f64 {
+    let segment = end - start;
+    let length_squared = segment.magnitude_squared();
+    if length_squared == 0.0 {
+        return (point - start).magnitude();
+    }
+
+    let t = ((point - start).dot(&segment) / length_squared).clamp(0.0, 1.0);
+    (point - (start + segment * t)).magnitude()
+}
+
+struct Grid {
+    origin: na::Vector2<f64>,
+    cell_size: f64,
+    width: usize,
+    height: usize,
+    /// the cells covered by a boundary
+    blocked: Vec<bool>,
+}
+
+impl Grid {
+    fn new(bounds: AABB, cell_size: f64) -> Option<Self> {
+        let extents = bounds.extents();
+        if extents[0] <= 0.0 || extents[1] <= 0.0 {
+            return None;
+        }
+
+        let cell_size = cell_size.max((extents[0] * extents[1] / GRID_CELLS_MAX).sqrt());
+        let width = (extents[0] / cell_size).ceil() as usize;
+        let height = (extents[1] / cell_size).ceil() as usize;
+
+        Some(Self {
+            origin: bounds.mins.coords,
+            cell_size,
+            width,
+            height,
+            blocked: vec![false; width * height],
+        })
+    }
+
+    fn index(&self, x: usize, y: usize) -> usize {
+        y * self.width + x
+    }
+
+    fn cell_at(&self, pos: na::Vector2<f64>) -> Option<(usize, usize)> {
+        let cell = (pos - self.origin) / self.cell_size;
+        if cell[0] < 0.0 || cell[1] < 0.0 {
+            return None;
+        }
+        let (x, y) = (cell[0].floor() as usize, cell[1].floor() as usize);
+
+        if x < self.width && y < self.height {
+            Some((x, y))
+        } else {
+            None
+        }
+    }
+
+    fn cell_center(&self, x: usize, y: usize) -> na::Vector2<f64> {
+        self.origin + na::vector![x as f64 + 0.5, y as f64 + 0.5] * self.cell_size
+    }
+
+    /// Blocks all cells whose center is within the path width, widened by the gap tolerance.
+    /// Half a cell is added so that thin paths can't slip between the cell centers.
+    fn rasterize_path(&mut self, path: &BoundaryPath, gap_tolerance: f64) {
+        let radius = 0.5 * (path.width + gap_tolerance + self.cell_size);
+
+        let segments = if path.points.len() == 1 {
+            vec![(path.points[0], path.points[0])]
+        } else {
+            path.points
+                .windows(2)
+                .map(|points| (points[0], points[1]))
+                .collect::<Vec<(na::Vector2<f64>, na::Vector2<f64>)>>()
+        };
+
+        for (start, end) in segments {
+            let mins = (start.inf(&end) - na::Vector2::from_element(radius) - self.origin)
+                / self.cell_size;
+            let maxs = (start.sup(&end) + na::Vector2::from_element(radius) - self.origin)
+                / self.cell_size;
+            if maxs[0] < 0.0 || maxs[1] < 0.0 {
+                continue;
+            }
+
+            let x_range =
+                (mins[0].max(0.0).floor() as usize)..((maxs[0].ceil() as usize).min(self.width));
+            let y_range =
+                (mins[1].max(0.0).floor() as usize)..((maxs[1].ceil() as usize).min(self.height));
+
+            for y in y_range {
+                for x in x_range.clone() {
+                    if distance_to_segment(self.cell_center(x, y), start, end) <= radius {
+                        let index = self.index(x, y);
+                        self.blocked[index] = true;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Fills the free cells which are 4-connected to the start cell. Returns None if the start cell is blocked or the fill leaks out of the grid
+    fn flood_fill(&self, start_x: usize, start_y: usize) -> Option<Vec<bool>> {
+        if self.blocked[self.index(start_x, start_y)] {
+            return None;
+        }
+
+        let mut filled = vec![false; self.width * self.height];
+        let mut queue = VecDeque::from(vec![(start_x, start_y)]);
+        filled[self.index(start_x, start_y)] = true;
+
+        while let Some((x, y)) = queue.pop_front() {
+            if x == 0 || y == 0 || x + 1 == self.width || y + 1 == self.height {
+                return None;
+            }
+
+            for (nx, ny) in [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)] {
+                let index = self.index(nx, ny);
+                if !self.blocked[index] && !filled[index] {
+                    filled[index] = true;
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+
+        Some(filled)
+    }
+
+    /// Grows the region by one cell in each direction
+    fn dilate(&self, region: &[bool]) -> Vec<bool> {
+        let mut dilated = region.to_vec();
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if !region[self.index(x, y)] {
+                    continue;
+                }
+                if x > 0 {
+                    dilated[self.index(x - 1, y)] = true;
+                }
+                if x + 1 < self.width {
+                    dilated[self.index(x + 1, y)] = true;
+                }
+                if y > 0 {
+                    dilated[self.index(x, y - 1)] = true;
+                }
+                if y + 1 < self.height {
+                    dilated[self.index(x, y + 1)] = true;
+                }
+            }
+        }
+
+        dilated
+    }
+
+    /// Traces the outlines of the region along the cell edges into closed rings of grid vertices
+    fn trace_rings(&self, region: &[bool]) -> Vec<Vec<(i64, i64)>> {
+        let is_filled = |x: i64, y: i64| -> bool {
+            x >= 0
+                && y >= 0
+                && (x as usize) < self.width
+                && (y as usize) < self.height
+                && region[self.index(x as usize, y as usize)]
+        };
+
+        // Directed edges between filled and empty cells, oriented consistently around the region
+        let mut edges = HashMap::<(i64, i64), Vec<(i64, i64)>>::new();
+        for y in 0..self.height as i64 {
+            for x in 0..self.width as i64 {
+                if !is_filled(x, y) {
+                    continue;
+                }
+                if !is_filled(x, y - 1) {
+                    edges.entry((x + 1, y)).or_default().push((x, y));
+                }
+                if !is_filled(x - 1, y) {
+                    edges.entry((x, y)).or_default().push((x, y + 1));
+                }
+                if !is_filled(x, y + 1) {
+                    edges.entry((x, y + 1)).or_default().push((x + 1, y + 1));
+                }
+                if !is_filled(x + 1, y) {
+                    edges.entry((x + 1, y + 1)).or_default().push((x + 1, y));
+                }
+            }
+        }
+
+        let mut rings = Vec::new();
+        while let Some(&start) = edges.keys().next() {
+            let mut ring = vec![start];
+            let mut current = start;
+            let mut direction: Option<(i64, i64)> = None;
+
+            loop {
+                let nexts = match edges.get_mut(&current) {
+                    Some(nexts) => nexts,
+                    None => break,
+                };
+
+                // At vertices where two diagonal cells touch, turn left to keep them apart
+                let choice = direction
+                    .and_then(|direction| {
+                        let left = (direction.1, -direction.0);
+                        nexts
+                            .iter()
+                            .position(|next| (next.0 - current.0, next.1 - current.1) == left)
+                    })
+                    .unwrap_or(0);
+                let next = nexts.swap_remove(choice);
+                if nexts.is_empty() {
+                    edges.remove(&current);
+                }
+
+                direction = Some((next.0 - current.0, next.1 - current.1));
+                current = next;
+                if current == start {
+                    break;
+                }
+                ring.push(current);
+            }
+
+            if ring.len() >= 4 {
+                rings.push(ring);
+            }
+        }
+
+        rings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo::algorithm::area::Area;
+
+    /// A closed square path from mins to maxs
+    fn square_path(mins: f64, maxs: f64, width: f64) -> BoundaryPath {
+        BoundaryPath::new(
+            vec![
+                na::vector![mins, mins],
+                na::vector![maxs, mins],
+                na::vector![maxs, maxs],
+                na::vector![mins, maxs],
+                na::vector![mins, mins],
+            ],
+            width,
+        )
+    }
+
+    fn search_bounds(extent: f64) -> AABB {
+        AABB::new(na::point![0.0, 0.0], na::point![extent, extent])
+    }
+
+    #[test]
+    fn closed_square_is_filled() {
+        let region = enclosed_region(
+            &[square_path(100.0, 200.0, 2.0)],
+            na::vector![150.0, 150.0],
+            search_bounds(300.0),
+            1.0,
+            0.0,
+            0.0,
+        )
+        .unwrap();
+
+        assert!(region.interiors().is_empty());
+        // The inside of the square, minus the boundary width
+        let area = region.unsigned_area();
+        assert!(area > 9000.0 && area < 10_000.0, "area: {}", area);
+    }
+
+    #[test]
+    fn gap_leaks_unless_within_tolerance() {
+        // The top side has a gap of 10 between x = 145 and x = 155
+        let paths = [BoundaryPath::new(
+            vec![
+                na::vector![145.0, 100.0],
+                na::vector![100.0, 100.0],
+                na::vector![100.0, 200.0],
+                na::vector![200.0, 200.0],
+                na::vector![200.0, 100.0],
+                na::vector![155.0, 100.0],
+            ],
+            2.0,
+        )];
+
+        assert!(enclosed_region(
+            &paths,
+            na::vector![150.0, 150.0],
+            search_bounds(300.0),
+            1.0,
+            0.0,
+            0.0,
+        )
+        .is_none());
+        assert!(enclosed_region(
+            &paths,
+            na::vector![150.0, 150.0],
+            search_bounds(300.0),
+            1.0,
+            12.0,
+            0.0,
+        )
+        .is_some());
+    }
+
+    #[test]
+    fn inner_boundary_is_a_hole() {
+        let region = enclosed_region(
+            &[
+                square_path(100.0, 200.0, 2.0),
+                square_path(140.0, 160.0, 2.0),
+            ],
+            na::vector![120.0, 120.0],
+            search_bounds(300.0),
+            1.0,
+            0.0,
+            0.0,
+        )
+        .unwrap();
+
+        assert_eq!(region.interiors().len(), 1);
+        let area = region.unsigned_area();
+        assert!(area > 8500.0 && area < 9600.0, "area: {}", area);
+    }
+
+    #[test]
+    fn large_bounds_fall_back_to_larger_cells() {
+        let bounds = search_bounds(100_000.0);
+        let grid = Grid::new(bounds, 1.0).unwrap();
+
+        assert!(grid.cell_size >= 100.0);
+        assert!((grid.width * grid.height) as f64 <= GRID_CELLS_MAX * 1.01);
+
+        let region = enclosed_region(
+            &[square_path(20_000.0, 80_000.0, 10.0)],
+            na::vector![50_000.0, 50_000.0],
+            bounds,
+            1.0,
+            0.0,
+            0.0,
+        )
+        .unwrap();
+
+        let area = region.unsigned_area();
+        let expected = 60_000.0 * 60_000.0;
+        assert!((area - expected).abs() < expected * 0.05, "area: {}", area);
+    }
+}
@@ -164,3 +164,34 @@ pub fn ellipse(ellipse: shapes::Ellipse, options: &RoughOptions) -> element::Gro
 
     element::Group::new().add(fill_polygon).add(ellipse)
 }
+
+/// Generating a polygon. The fill does not respect the interior rings
+pub fn polygon(polygon: shapes::Polygon, options: &RoughOptions) -> element::Group {
+    let mut rng = compose::new_rng_default_pcg64(options.seed);
+
+    let mut commands = Vec::new();
+    // Applying the transform at the end
+    for ring in std::iter::once(&polygon.exterior).chain(polygon.interiors.iter()) {
+        for (start, end) in ring.iter().zip(ring.iter().cycle().skip(1)) {
+            if !options.disable_multistroke {
+                commands.append(&mut roughshapes::doubleline(
+                    *start, *end, options, &mut rng,
+                ));
+            } else {
+                commands.append(&mut roughshapes::line(
+                    *start, *end, true, false, options, &mut rng,
+                ));
+            }
+        }
+    }
+
+    let outline = options.apply_to_rect(element::Path::new().set("d", path::Data::from(commands)));
+    let fill_polygon = fill_polygon(polygon.exterior.clone(), options);
+
+    let transform_string = polygon.transform.transform_as_svg_transform_attr();
+
+    element::Group::new()
+        .set("transform", transform_string)
+        .add(fill_polygon)
+        .add(outline)
+}
@@ -46,6 +46,10 @@ impl Transformable for Rectangle {
     fn scale(&mut self, scale: na::Vector2<f64>) {
         self.transform.append_scale_mut(scale);
     }
+
+    fn transform_wrt_point(&mut self, matrix: na::Matrix2<f64>, center: na::Point2<f64>) {
+        self.transform.append_linear_wrt_point_mut(matrix, center);
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -80,6 +84,10 @@ impl Transformable for Ellipse {
     fn scale(&mut self, scale: na::Vector2<f64>) {
         self.transform.append_scale_mut(scale);
     }
+
+    fn transform_wrt_point(&mut self, matrix: na::Matrix2<f64>, center: na::Point2<f64>) {
+        self.transform.append_linear_wrt_point_mut(matrix, center);
+    }
 }
 
 impl Ellipse {
@@ -94,3 +102,94 @@ impl Ellipse {
         AABB::from_half_extents(center, half_extents)
     }
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename = "polygon")]
+pub struct Polygon {
+    /// The exterior ring, relative to the transform
+    #[serde(rename = "exterior")]
+    pub exterior: Vec<na::Vector2<f64>>,
+    /// The interior rings (holes), relative to the transform
+    #[serde(rename = "interiors")]
+    pub interiors: Vec<Vec<na::Vector2<f64>>>,
+    /// The transform
+    #[serde(rename = "transform")]
+    pub transform: Transform,
+}
+
+impl Default for Polygon {
+    fn default() -> Self {
+        Self {
+            exterior: vec![],
+            interiors: vec![],
+            transform: Transform::default(),
+        }
+    }
+}
+
+impl Transformable for Polygon {
+    fn translate(&mut self, offset: nalgebra::Vector2<f64>) {
+        self.transform.append_translation_mut(offset);
+    }
+
+    fn rotate(&mut self, angle: f64, center: nalgebra::Point2<f64>) {
+        self.transform.append_rotation_wrt_point_mut(angle, center)
+    }
+
+    fn scale(&mut self, scale: na::Vector2<f64>) {
+        self.transform.append_scale_mut(scale);
+    }
+
+    fn transform_wrt_point(&mut self, matrix: na::Matrix2<f64>, center: na::Point2<f64>) {
+        self.transform.append_linear_wrt_point_mut(matrix, center);
+    }
+}
+
+impl Polygon {
+    /// A new polygon from a geo polygon. The points are stored relative to the center of its bounds
+    pub fn from_geo_polygon(polygon: &geo::Polygon<f64>) -> Self {
+        // geo rings are closed by repeating the first point, which is not needed here
+        let ring_to_points = |ring: &geo::LineString<f64>| {
+            let mut points = ring
+                .points_iter()
+                .map(|point| na::vector![point.x(), point.y()])
+                .collect::<Vec<na::Vector2<f64>>>();
+            if points.len() > 1 && points.first() == points.last() {
+                points.pop();
+            }
+            points
+        };
+
+        let mut bounds = AABB::new_invalid();
+        for point in ring_to_points(polygon.exterior()) {
+            bounds.take_point(na::Point2::from(point));
+        }
+        let center = bounds.center().coords;
+
+        Self {
+            exterior: ring_to_points(polygon.exterior())
+                .into_iter()
+                .map(|point| point - center)
+                .collect(),
+            interiors: polygon
+                .interiors()
+                .iter()
+                .map(|interior| {
+                    ring_to_points(interior)
+                        .into_iter()
+                        .map(|point| point - center)
+                        .collect()
+                })
+                .collect(),
+            transform: Transform::new_w_isometry(na::Isometry2::new(center, 0.0)),
+        }
+    }
+
+    pub fn global_aabb(&self) -> AABB {
+        let mut bounds = AABB::new_invalid();
+        for point in self.exterior.iter() {
+            bounds.take_point(self.transform.transform_point(na::Point2::from(*point)));
+        }
+        bounds
+    }
+}
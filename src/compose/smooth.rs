@@ -590,3 +590,40 @@ pub fn compose_ellipse(ellipse: shapes::Ellipse, options: &SmoothOptions) -> ele
         .set("fill", fill)
         .into()
 }
+
+pub fn compose_polygon(polygon: shapes::Polygon, options: &SmoothOptions) -> element::Element {
+    let color = if let Some(color) = options.stroke_color {
+        color.to_css_color()
+    } else {
+        String::from("none")
+    };
+    let fill = if let Some(fill) = options.fill_color {
+        fill.to_css_color()
+    } else {
+        String::from("none")
+    };
+
+    let mut data = path::Data::new();
+    for ring in std::iter::once(&polygon.exterior).chain(polygon.interiors.iter()) {
+        let mut ring_iter = ring.iter();
+
+        if let Some(first) = ring_iter.next() {
+            data = data.move_to((first[0], first[1]));
+            for point in ring_iter {
+                data = data.line_to((point[0], point[1]));
+            }
+            data = data.close();
+        }
+    }
+
+    let transform_string = polygon.transform.transform_as_svg_transform_attr();
+
+    svg::node::element::Path::new()
+        .set("transform", transform_string)
+        .set("d", data)
+        .set("fill-rule", "evenodd")
+        .set("stroke", color)
+        .set("stroke-width", options.width)
+        .set("fill", fill)
+        .into()
+}
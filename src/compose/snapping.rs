@@ -0,0 +1,143 @@
+use p2d::bounding_volume::AABB;
+
+/// A line which bounds can snap to. Its position is on the snapping axis, the range spans the other axis
+#[derive(Debug, Clone, Copy)]
+pub struct SnapLine {
+    pub pos: f64,
+    pub range: (f64, f64),
+}
+
+impl SnapLine {
+    pub fn new(pos: f64, range: (f64, f64)) -> Self {
+        Self { pos, range }
+    }
+}
+
+/// A guide which is displayed while bounds are snapped to a line, in sheet coordinates
+#[derive(Debug, Clone, Copy)]
+pub struct SnapGuide {
+    pub start: na::Vector2<f64>,
+    pub end: na::Vector2<f64>,
+}
+
+/// The lines which the edges and the centers of bounds can snap to
+#[derive(Debug, Clone, Default)]
+pub struct SnapLines {
+    /// lines along the y axis, snapping on the x axis
+    pub vertical: Vec<SnapLine>,
+    /// lines along the x axis, snapping on the y axis
+    pub horizontal: Vec<SnapLine>,
+}
+
+impl SnapLines {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds lines at the edges and the center of the bounds
+    pub fn add_bounds(&mut self, bounds: AABB) {
+        for x in [bounds.mins[0], bounds.center()[0], bounds.maxs[0]] {
+            self.vertical
+                .push(SnapLine::new(x, (bounds.mins[1], bounds.maxs[1])));
+        }
+        for y in [bounds.mins[1], bounds.center()[1], bounds.maxs[1]] {
+            self.horizontal
+                .push(SnapLine::new(y, (bounds.mins[0], bounds.maxs[0])));
+        }
+    }
+
+    /// Adds the lines of a grid starting at the origin, which are inside the bounds.
+    /// No lines are added on an axis if its spacing is None
+    pub fn add_grid(
+        &mut self,
+        bounds: AABB,
+        column_spacing: Option<f64>,
+        row_spacing: Option<f64>,
+    ) {
+        if let Some(column_spacing) = column_spacing.filter(|&spacing| spacing > 1.0) {
+            let mut x = (bounds.mins[0] / column_spacing).ceil() * column_spacing;
+            while x <= bounds.maxs[0] {
+                self.vertical
+                    .push(SnapLine::new(x, (bounds.mins[1], bounds.maxs[1])));
+                x += column_spacing;
+            }
+        }
+        if let Some(row_spacing) = row_spacing.filter(|&spacing| spacing > 1.0) {
+            let mut y = (bounds.mins[1] / row_spacing).ceil() * row_spacing;
+            while y <= bounds.maxs[1] {
+                self.horizontal
+                    .push(SnapLine::new(y, (bounds.mins[0], bounds.maxs[0])));
+                y += row_spacing;
+            }
+        }
+    }
+
+    /// Snaps the edges or the center of the bounds to the closest lines within the distance, separately for each axis.
+    /// Returns the offset to the snapped bounds, and the guides of all lines which the snapped bounds are aligned to
+    pub fn snap(&self, bounds: AABB, distance: f64) -> (na::Vector2<f64>, Vec<SnapGuide>) {
+        let snap_x = snap_axis(&self.vertical, bounds, 0, distance);
+        let snap_y = snap_axis(&self.horizontal, bounds, 1, distance);
+        let offset = na::vector![snap_x.unwrap_or(0.0), snap_y.unwrap_or(0.0)];
+        let snapped = AABB::new(bounds.mins + offset, bounds.maxs + offset);
+
+        let mut guides = Vec::new();
+        if snap_x.is_some() {
+            guides.append(&mut aligned_guides(&self.vertical, snapped, 0));
+        }
+        if snap_y.is_some() {
+            guides.append(&mut aligned_guides(&self.horizontal, snapped, 1));
+        }
+
+        (offset, guides)
+    }
+}
+
+fn bounds_features(bounds: AABB, axis: usize) -> [f64; 3] {
+    [bounds.mins[axis], bounds.center()[axis], bounds.maxs[axis]]
+}
+
+/// The smallest offset on the axis which aligns a feature of the bounds with a line. None if none is within the distance
+fn snap_axis(lines: &[SnapLine], bounds: AABB, axis: usize, distance: f64) -> Option<f64> {
+    let features = bounds_features(bounds, axis);
+
+    lines
+        .iter()
+        .flat_map(|line| features.iter().map(move |feature| line.pos - feature))
+        .filter(|offset| offset.abs() <= distance)
+        .min_by(|first, second| {
+            first
+                .abs()
+                .partial_cmp(&second.abs())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+}
+
+/// The guides for all lines which are aligned with a feature of the bounds, spanning both the line and the bounds
+fn aligned_guides(lines: &[SnapLine], bounds: AABB, axis: usize) -> Vec<SnapGuide> {
+    const ALIGNED_TOLERANCE: f64 = 1e-6;
+    let features = bounds_features(bounds, axis);
+    let other_axis = 1 - axis;
+
+    lines
+        .iter()
+        .filter(|line| {
+            features
+                .iter()
+                .any(|feature| (line.pos - feature).abs() < ALIGNED_TOLERANCE)
+        })
+        .map(|line| {
+            let start = line.range.0.min(bounds.mins[other_axis]);
+            let end = line.range.1.max(bounds.maxs[other_axis]);
+
+            let mut guide = SnapGuide {
+                start: na::Vector2::zeros(),
+                end: na::Vector2::zeros(),
+            };
+            guide.start[axis] = line.pos;
+            guide.start[other_axis] = start;
+            guide.end[axis] = line.pos;
+            guide.end[other_axis] = end;
+            guide
+        })
+        .collect()
+}
@@ -8,6 +8,39 @@ pub trait Transformable {
     fn rotate(&mut self, angle: f64, center: na::Point2<f64>);
     /// scales the stroke by the desired scale
     fn scale(&mut self, scale: na::Vector2<f64>);
+    /// applies the linear transformation matrix (without translation) relative to the center
+    fn transform_wrt_point(&mut self, matrix: na::Matrix2<f64>, center: na::Point2<f64>);
+
+    /// mirrors the stroke horizontally (left and right are swapped) at the vertical axis through the center
+    fn flip_horizontal(&mut self, center: na::Point2<f64>) {
+        self.transform_wrt_point(na::Matrix2::new(-1.0, 0.0, 0.0, 1.0), center);
+    }
+    /// mirrors the stroke vertically (top and bottom are swapped) at the horizontal axis through the center
+    fn flip_vertical(&mut self, center: na::Point2<f64>) {
+        self.transform_wrt_point(na::Matrix2::new(1.0, 0.0, 0.0, -1.0), center);
+    }
+    /// skews the stroke relative to the center. angles[0] (rad) slants it horizontally, angles[1] vertically
+    fn skew(&mut self, angles: na::Vector2<f64>, center: na::Point2<f64>) {
+        self.transform_wrt_point(skew_matrix(angles), center);
+    }
+}
+
+/// The matrix skewing horizontally by angles[0] (rad) and then vertically by angles[1].
+/// Applying the two shears one after the other keeps the determinant at 1, so the matrix never becomes singular
+pub fn skew_matrix(angles: na::Vector2<f64>) -> na::Matrix2<f64> {
+    let horizontal = na::Matrix2::new(1.0, angles[0].tan(), 0.0, 1.0);
+    let vertical = na::Matrix2::new(1.0, 0.0, angles[1].tan(), 1.0);
+
+    vertical * horizontal
+}
+
+/// Applies the linear transformation matrix to the position relative to the center
+pub fn transform_pos_wrt_point(
+    matrix: na::Matrix2<f64>,
+    pos: na::Vector2<f64>,
+    center: na::Point2<f64>,
+) -> na::Vector2<f64> {
+    matrix * (pos - center.coords) + center.coords
 }
 
 /// To be used as state in a stroke to help implement the StrokeBehaviour trait
@@ -64,6 +97,17 @@ impl Transform {
         self.transform = na::Translation2::from(translation) * self.transform;
     }
 
+    pub fn append_linear_wrt_point_mut(
+        &mut self,
+        matrix: na::Matrix2<f64>,
+        center: na::Point2<f64>,
+    ) {
+        self.transform = na::Translation2::from(-center.coords) * self.transform;
+        self.transform =
+            na::Affine2::from_matrix_unchecked(matrix.to_homogeneous()) * self.transform;
+        self.transform = na::Translation2::from(center.coords) * self.transform;
+    }
+
     pub fn transform_as_svg_transform_attr(&self) -> String {
         let matrix = self.transform;
 
@@ -78,3 +122,18 @@ impl Transform {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skew_matrix_is_never_singular() {
+        let angle = 60.0_f64.to_radians();
+        // with a single matrix [1, tan ax; tan ay, 1] these angles would have a negative determinant
+        let matrix = skew_matrix(na::vector![angle, angle]);
+
+        assert!((matrix.determinant() - 1.0).abs() < 1e-9);
+        assert!(matrix.try_inverse().is_some());
+    }
+}
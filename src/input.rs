@@ -3,22 +3,25 @@ use p2d::bounding_volume::AABB;
 use std::collections::VecDeque;
 
 use crate::audioplayer::RnoteAudioPlayer;
+use crate::pens::PenStyle;
 use crate::strokes::strokestyle::InputData;
 use crate::ui::appwindow::RnoteAppWindow;
+use crate::ui::canvas::Canvas;
+use crate::ui::colorpicker::ColorPicker;
 
 pub const INPUT_OVERSHOOT: f64 = 30.0;
 
-/// Map pen input to the position on a sheet
+/// The distance (in surface coordinates) around a stroke where its exact stroke color is still picked
+pub const PICK_COLOR_TOLERANCE: f64 = 2.0;
+
+/// Map pen input to the position on a sheet. The offset is added to the input positions in the canvas coordinate space (e.g. the start point of drag gestures)
 pub fn map_inputdata(
-    zoom: f64,
+    canvas: &Canvas,
     data_entries: &mut VecDeque<InputData>,
-    mapped_offset: na::Vector2<f64>,
+    offset: na::Vector2<f64>,
 ) {
     data_entries.iter_mut().for_each(|inputdata| {
-        *inputdata = InputData::new(
-            inputdata.pos().scale(1.0 / zoom) + mapped_offset,
-            inputdata.pressure(),
-        )
+        inputdata.set_pos(canvas.transform_canvas_coords_to_sheet_coords(inputdata.pos() + offset));
     });
 }
 
@@ -56,8 +59,14 @@ pub fn retreive_stylus_inputdata(
                 let x = axes[1];
                 let y = axes[2];
                 let pressure = axes[5];
+                let tilt = na::vector![axes[6], axes[7]];
                 //log::debug!("{:?}", axes);
-                data_entries.push_back(InputData::new(na::vector![x, y], pressure));
+                let mut inputdata = InputData::new(na::vector![x, y], pressure);
+                // Devices without tilt report zero
+                if tilt != na::Vector2::zeros() {
+                    inputdata.set_tilt(Some(tilt));
+                }
+                data_entries.push_back(inputdata);
             }
         }
     }
@@ -69,13 +78,28 @@ pub fn retreive_stylus_inputdata(
         InputData::PRESSURE_DEFAULT
     };
 
-    data_entries.push_back(InputData::new(na::vector![x, y], pressure));
+    let tilt = match (
+        gesture_stylus.axis(gdk::AxisUse::Xtilt),
+        gesture_stylus.axis(gdk::AxisUse::Ytilt),
+    ) {
+        (Some(xtilt), Some(ytilt)) => Some(na::vector![xtilt, ytilt]),
+        _ => None,
+    };
+
+    let mut inputdata = InputData::new(na::vector![x, y], pressure);
+    inputdata.set_tilt(tilt);
+    data_entries.push_back(inputdata);
 
     data_entries
 }
 
 /// Process pen input start
 pub fn process_peninput_start(appwindow: &RnoteAppWindow, data_entries: VecDeque<InputData>) {
+    // The eyedropper picks the color when the input ends
+    if appwindow.canvas().eyedropper().is_some() {
+        return;
+    }
+
     appwindow.canvas().set_pen_shown(true);
     appwindow.audioplayer().borrow().play_pen_sound_begin(
         RnoteAudioPlayer::PLAY_TIMEOUT_TIME,
@@ -93,6 +117,10 @@ pub fn process_peninput_start(appwindow: &RnoteAppWindow, data_entries: VecDeque
 
 /// Process pen input motion
 pub fn process_peninput_motion(appwindow: &RnoteAppWindow, data_entries: VecDeque<InputData>) {
+    if appwindow.canvas().eyedropper().is_some() {
+        return;
+    }
+
     appwindow.audioplayer().borrow().play_pen_sound_motion(
         RnoteAudioPlayer::PLAY_TIMEOUT_TIME,
         appwindow.canvas().pens().borrow().current_pen,
@@ -106,21 +134,33 @@ pub fn process_peninput_motion(appwindow: &RnoteAppWindow, data_entries: VecDequ
 
 /// Process pen input end
 pub fn process_peninput_end(appwindow: &RnoteAppWindow, data_entries: VecDeque<InputData>) {
-    // We deselect the selection here. (before current_pen.end()!)
-    let all_strokes = appwindow
-        .canvas()
-        .sheet()
-        .borrow()
-        .strokes_state
-        .keys_sorted_chrono();
-    appwindow
-        .canvas()
-        .sheet()
-        .borrow_mut()
-        .strokes_state
-        .set_selected_keys(&all_strokes, false);
+    if let Some(eyedropper) = appwindow.canvas().eyedropper() {
+        if let Some(inputdata) = data_entries.back() {
+            pick_color(appwindow, &eyedropper, inputdata.pos());
+        }
+        eyedropper.eyedropper_button().set_active(false);
+        return;
+    }
 
     let current_pen = appwindow.canvas().pens().borrow().current_pen;
+
+    // We deselect the selection here. (before current_pen.end()!)
+    // The selector updates the selection itself, as it can extend it.
+    if current_pen != PenStyle::SelectorStyle {
+        let all_strokes = appwindow
+            .canvas()
+            .sheet()
+            .borrow()
+            .strokes_state
+            .keys_sorted_chrono();
+        appwindow
+            .canvas()
+            .sheet()
+            .borrow_mut()
+            .strokes_state
+            .set_selected_keys(&all_strokes, false);
+    }
+
     current_pen.end(data_entries, appwindow);
 
     appwindow
@@ -135,3 +175,78 @@ pub fn process_peninput_end(appwindow: &RnoteAppWindow, data_entries: VecDeque<I
     appwindow.canvas().queue_resize();
     appwindow.canvas().queue_draw();
 }
+
+/// The modifier keys which are currently held down on the keyboard
+pub fn keyboard_modifier_state() -> gdk::ModifierType {
+    gdk::Display::default()
+        .and_then(|display| display.default_seat())
+        .and_then(|seat| seat.keyboard())
+        .map(|keyboard| keyboard.modifier_state())
+        .unwrap_or_else(gdk::ModifierType::empty)
+}
+
+/// Picks the color at the position (in sheet coordinates) into the colorpicker.
+/// When Ctrl is held down, the exact stroke color of the stroke under the position is picked instead of the rendered pixel
+fn pick_color(appwindow: &RnoteAppWindow, colorpicker: &ColorPicker, pos: na::Vector2<f64>) {
+    let exact_stroke_color = keyboard_modifier_state().contains(gdk::ModifierType::CONTROL_MASK);
+
+    let color = if exact_stroke_color {
+        appwindow
+            .canvas()
+            .sheet()
+            .borrow()
+            .strokes_state
+            .stroke_color_at_pos(pos, PICK_COLOR_TOLERANCE / appwindow.canvas().zoom())
+    } else {
+        appwindow.canvas().sample_color_at(pos)
+    };
+
+    if let Some(color) = color {
+        colorpicker.set_current_color(Some(color));
+    }
+}
+
+/// Cancel the pen input in progress. The stroke which is currently drawn is removed
+pub fn process_peninput_cancel(appwindow: &RnoteAppWindow) {
+    let current_pen = appwindow.canvas().pens().borrow().current_pen;
+
+    let current_stroke = match current_pen {
+        PenStyle::MarkerStyle => appwindow
+            .canvas()
+            .pens()
+            .borrow_mut()
+            .marker
+            .current_stroke
+            .take(),
+        PenStyle::BrushStyle => appwindow
+            .canvas()
+            .pens()
+            .borrow_mut()
+            .brush
+            .current_stroke
+            .take(),
+        PenStyle::ShaperStyle => appwindow
+            .canvas()
+            .pens()
+            .borrow_mut()
+            .shaper
+            .current_stroke
+            .take(),
+        PenStyle::EraserStyle | PenStyle::SelectorStyle | PenStyle::ToolsStyle => None,
+    };
+
+    if let Some(current_stroke) = current_stroke {
+        appwindow
+            .canvas()
+            .sheet()
+            .borrow_mut()
+            .strokes_state
+            .remove_stroke(current_stroke);
+    }
+
+    // Let the pen reset its state. Without a current stroke there is nothing left to finish
+    current_pen.end(VecDeque::new(), appwindow);
+
+    appwindow.canvas().set_pen_shown(false);
+    appwindow.canvas().queue_draw();
+}
@@ -1,28 +1,9 @@
 #![warn(missing_debug_implementations)]
-#![allow(dead_code)]
-
-pub mod app;
-pub mod audioplayer;
-pub mod compose;
-pub mod config;
-pub mod drawbehaviour;
-pub mod globals;
-pub mod input;
-pub mod pens;
-pub mod render;
-pub mod sheet;
-pub mod strokes;
-pub mod strokesstate;
-pub mod ui;
-pub mod utils;
 
 use gettextrs::LocaleCategory;
 use gtk4::prelude::*;
-extern crate gstreamer as gst;
-extern crate nalgebra as na;
-extern crate parry2d_f64 as p2d;
-
-use self::config::{GETTEXT_PACKAGE, LOCALEDIR};
+use rnote::app;
+use rnote::config::{GETTEXT_PACKAGE, LOCALEDIR};
 
 fn main() {
     pretty_env_logger::init();
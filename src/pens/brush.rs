@@ -1,5 +1,7 @@
 use std::collections::VecDeque;
 
+use crate::compose::calligraphy::CalligraphyOptions;
+use crate::compose::pencil::PencilOptions;
 use crate::compose::smooth::SmoothOptions;
 use crate::compose::textured::TexturedOptions;
 use crate::input;
@@ -24,6 +26,12 @@ pub enum BrushStyle {
     #[enum_value(name = "Textured", nick = "textured")]
     #[serde(rename = "textured")]
     Textured,
+    #[enum_value(name = "Calligraphy", nick = "calligraphy")]
+    #[serde(rename = "calligraphy")]
+    Calligraphy,
+    #[enum_value(name = "Pencil", nick = "pencil")]
+    #[serde(rename = "pencil")]
+    Pencil,
 }
 
 impl Default for BrushStyle {
@@ -41,6 +49,10 @@ pub struct Brush {
     pub smooth_options: SmoothOptions,
     #[serde(rename = "textured_options")]
     pub textured_options: TexturedOptions,
+    #[serde(rename = "calligraphy_options")]
+    pub calligraphy_options: CalligraphyOptions,
+    #[serde(rename = "pencil_options")]
+    pub pencil_options: PencilOptions,
 
     #[serde(skip)]
     pub current_stroke: Option<StrokeKey>,
@@ -52,6 +64,8 @@ impl Default for Brush {
             style: BrushStyle::default(),
             smooth_options: SmoothOptions::default(),
             textured_options: TexturedOptions::default(),
+            calligraphy_options: CalligraphyOptions::default(),
+            pencil_options: PencilOptions::default(),
             current_stroke: None,
         }
     }
@@ -76,6 +90,25 @@ impl PenBehaviour for Brush {
 
         input::filter_mapped_inputdata(filter_bounds, &mut data_entries);
 
+        // Strokes which begin close to the ruler snap to it
+        let zoom = appwindow.canvas().zoom();
+        if let Some(inputdata) = data_entries.front() {
+            appwindow
+                .canvas()
+                .pens()
+                .borrow_mut()
+                .tools
+                .ruler_tool
+                .snap_begin(inputdata.pos(), zoom);
+        }
+        appwindow
+            .canvas()
+            .pens()
+            .borrow_mut()
+            .tools
+            .ruler_tool
+            .snap_inputdata(&mut data_entries, zoom);
+
         let elements_iter = data_entries
             .into_iter()
             .map(|inputdata| Element::new(inputdata));
@@ -111,6 +144,13 @@ impl PenBehaviour for Brush {
                 .loosened(input::INPUT_OVERSHOOT);
 
             input::filter_mapped_inputdata(filter_bounds, &mut data_entries);
+            appwindow
+                .canvas()
+                .pens()
+                .borrow_mut()
+                .tools
+                .ruler_tool
+                .snap_inputdata(&mut data_entries, appwindow.canvas().zoom());
 
             for inputdata in data_entries {
                 appwindow
@@ -135,6 +175,13 @@ impl PenBehaviour for Brush {
         appwindow
             .canvas()
             .set_cursor(Some(&appwindow.canvas().cursor()));
+        appwindow
+            .canvas()
+            .pens()
+            .borrow_mut()
+            .tools
+            .ruler_tool
+            .snap_end();
 
         if let Some(current_stroke_key) = appwindow
             .canvas()
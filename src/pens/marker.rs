@@ -16,6 +16,9 @@ use super::penbehaviour::PenBehaviour;
 pub struct Marker {
     #[serde(rename = "options")]
     pub options: SmoothOptions,
+    /// Highlighter strokes are rendered below the ink and without darkening where they overlap themselves
+    #[serde(rename = "highlighter")]
+    pub highlighter: bool,
 
     #[serde(skip)]
     pub current_stroke: Option<StrokeKey>,
@@ -25,6 +28,7 @@ impl Default for Marker {
     fn default() -> Self {
         Self {
             options: SmoothOptions::default(),
+            highlighter: false,
             current_stroke: None,
         }
     }
@@ -49,6 +53,25 @@ impl PenBehaviour for Marker {
 
         input::filter_mapped_inputdata(filter_bounds, &mut data_entries);
 
+        // Strokes which begin close to the ruler snap to it
+        let zoom = appwindow.canvas().zoom();
+        if let Some(inputdata) = data_entries.front() {
+            appwindow
+                .canvas()
+                .pens()
+                .borrow_mut()
+                .tools
+                .ruler_tool
+                .snap_begin(inputdata.pos(), zoom);
+        }
+        appwindow
+            .canvas()
+            .pens()
+            .borrow_mut()
+            .tools
+            .ruler_tool
+            .snap_inputdata(&mut data_entries, zoom);
+
         let elements_iter = data_entries
             .into_iter()
             .map(|inputdata| Element::new(inputdata));
@@ -84,6 +107,13 @@ impl PenBehaviour for Marker {
                 .loosened(input::INPUT_OVERSHOOT);
 
             input::filter_mapped_inputdata(filter_bounds, &mut data_entries);
+            appwindow
+                .canvas()
+                .pens()
+                .borrow_mut()
+                .tools
+                .ruler_tool
+                .snap_inputdata(&mut data_entries, appwindow.canvas().zoom());
 
             for inputdata in data_entries {
                 appwindow
@@ -108,6 +138,13 @@ impl PenBehaviour for Marker {
         appwindow
             .canvas()
             .set_cursor(Some(&appwindow.canvas().cursor()));
+        appwindow
+            .canvas()
+            .pens()
+            .borrow_mut()
+            .tools
+            .ruler_tool
+            .snap_end();
 
         if let Some(current_stroke_key) = appwindow
             .canvas()
@@ -2,13 +2,17 @@ pub mod brush;
 pub mod eraser;
 pub mod marker;
 pub mod penbehaviour;
+pub mod presets;
 pub mod selector;
 pub mod shaper;
+pub mod stylusconfig;
 pub mod tools;
 
 use crate::ui::canvas::Canvas;
 
 use self::penbehaviour::PenBehaviour;
+use self::presets::PenPresets;
+use self::stylusconfig::StylusConfig;
 use self::tools::Tools;
 use self::{brush::Brush, eraser::Eraser, marker::Marker, selector::Selector, shaper::Shaper};
 use gtk4::glib;
@@ -201,4 +205,9 @@ pub struct Pens {
     pub selector: Selector,
     #[serde(rename = "tools")]
     pub tools: Tools,
+
+    #[serde(rename = "presets")]
+    pub presets: PenPresets,
+    #[serde(rename = "stylus_config")]
+    pub stylus_config: StylusConfig,
 }
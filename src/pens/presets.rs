@@ -0,0 +1,174 @@
+use gtk4::{gio, glib, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use super::{brush::Brush, marker::Marker, shaper::Shaper, PenStyle, Pens};
+
+/// The configuration of a pen which can be saved as a preset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename = "pen_config")]
+pub enum PenConfig {
+    #[serde(rename = "marker")]
+    Marker(Marker),
+    #[serde(rename = "brush")]
+    Brush(Brush),
+    #[serde(rename = "shaper")]
+    Shaper(Shaper),
+}
+
+impl PenConfig {
+    /// The pen style the config belongs to
+    pub fn pen_style(&self) -> PenStyle {
+        match self {
+            PenConfig::Marker(_) => PenStyle::MarkerStyle,
+            PenConfig::Brush(_) => PenStyle::BrushStyle,
+            PenConfig::Shaper(_) => PenStyle::ShaperStyle,
+        }
+    }
+}
+
+/// A named pen configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename = "pen_preset")]
+pub struct PenPreset {
+    #[serde(rename = "name")]
+    pub name: String,
+    #[serde(rename = "config")]
+    pub config: PenConfig,
+}
+
+impl PenPreset {
+    /// Creates a preset from the current pen. Returns None if the current pen is not configurable as a preset
+    pub fn from_current_pen(name: String, pens: &Pens) -> Option<Self> {
+        let config = match pens.current_pen {
+            PenStyle::MarkerStyle => {
+                let mut marker = pens.marker.clone();
+                marker.current_stroke = None;
+                PenConfig::Marker(marker)
+            }
+            PenStyle::BrushStyle => {
+                let mut brush = pens.brush.clone();
+                brush.current_stroke = None;
+                PenConfig::Brush(brush)
+            }
+            PenStyle::ShaperStyle => {
+                let mut shaper = pens.shaper.clone();
+                shaper.current_stroke = None;
+                PenConfig::Shaper(shaper)
+            }
+            PenStyle::EraserStyle | PenStyle::SelectorStyle | PenStyle::ToolsStyle => {
+                return None;
+            }
+        };
+
+        Some(Self { name, config })
+    }
+
+    /// Applies the preset to the pens and switches to its pen
+    pub fn apply(&self, pens: &mut Pens) {
+        match &self.config {
+            PenConfig::Marker(marker) => {
+                pens.marker = marker.clone();
+            }
+            PenConfig::Brush(brush) => {
+                pens.brush = brush.clone();
+            }
+            PenConfig::Shaper(shaper) => {
+                pens.shaper = shaper.clone();
+            }
+        }
+        pens.current_pen = self.config.pen_style();
+    }
+}
+
+/// A library of pen presets, identified by their names
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename = "pen_presets")]
+pub struct PenPresets {
+    #[serde(rename = "presets")]
+    presets: Vec<PenPreset>,
+}
+
+impl PenPresets {
+    pub fn presets(&self) -> &[PenPreset] {
+        &self.presets
+    }
+
+    pub fn get(&self, name: &str) -> Option<&PenPreset> {
+        self.presets.iter().find(|preset| preset.name == name)
+    }
+
+    /// Inserts the preset. A preset with the same name is replaced
+    pub fn insert(&mut self, preset: PenPreset) {
+        if let Some(existing) = self.presets.iter_mut().find(|p| p.name == preset.name) {
+            *existing = preset;
+        } else {
+            self.presets.push(preset);
+        }
+    }
+
+    /// Removes the preset with the given name. Returns it if it existed
+    pub fn remove(&mut self, name: &str) -> Option<PenPreset> {
+        let index = self.presets.iter().position(|preset| preset.name == name)?;
+        Some(self.presets.remove(index))
+    }
+
+    /// Merges the presets, replacing presets with the same name
+    pub fn merge(&mut self, other: PenPresets) {
+        for preset in other.presets {
+            self.insert(preset);
+        }
+    }
+
+    pub fn export_as_json(&self) -> Result<String, anyhow::Error> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn import_from_json(json: &str) -> Result<Self, anyhow::Error> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Imports presets from a JSON file
+    pub fn import_from_file(file: &gio::File) -> Result<Self, anyhow::Error> {
+        let (contents, _) = file.load_contents(None::<&gio::Cancellable>)?;
+
+        Self::import_from_json(&String::from_utf8(contents)?)
+    }
+
+    /// Exports the presets as JSON file
+    pub fn export_to_file(&self, file: &gio::File) -> Result<(), anyhow::Error> {
+        let json_data = self.export_as_json()?;
+
+        file.replace_async(
+            None,
+            false,
+            gio::FileCreateFlags::REPLACE_DESTINATION,
+            glib::PRIORITY_HIGH_IDLE,
+            None::<&gio::Cancellable>,
+            move |result| {
+                let output_stream = match result {
+                    Ok(output_stream) => output_stream,
+                    Err(e) => {
+                        log::error!("replace_async() failed in export_to_file() with Err {}", e);
+                        return;
+                    }
+                };
+
+                if let Err(e) = output_stream.write(json_data.as_bytes(), None::<&gio::Cancellable>)
+                {
+                    log::error!(
+                        "output_stream().write() failed in export_to_file() with Err {}",
+                        e
+                    );
+                };
+                if let Err(e) = output_stream.close(None::<&gio::Cancellable>) {
+                    log::error!(
+                        "output_stream().close() failed in export_to_file() with Err {}",
+                        e
+                    );
+                };
+            },
+        );
+
+        Ok(())
+    }
+}
@@ -4,7 +4,7 @@ use std::sync::{Arc, RwLock};
 use crate::compose::color::Color;
 use crate::strokes::strokestyle::InputData;
 use crate::ui::appwindow::RnoteAppWindow;
-use crate::{compose, render};
+use crate::{compose, input, render};
 
 use anyhow::Context;
 use gtk4::{gdk, glib, prelude::*, Snapshot};
@@ -26,11 +26,31 @@ pub enum SelectorStyle {
     Rectangle,
 }
 
+/// When strokes are selected by a drawn polygon or rectangle
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename = "selector_containment")]
+pub enum SelectorContainment {
+    /// Strokes that are touched by the selector path
+    #[serde(rename = "touching")]
+    Touching,
+    /// Strokes that are fully contained in the selector path
+    #[serde(rename = "contained")]
+    Contained,
+}
+
+impl Default for SelectorContainment {
+    fn default() -> Self {
+        Self::Contained
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(default, rename = "selector")]
 pub struct Selector {
     #[serde(rename = "style")]
     pub style: SelectorStyle,
+    #[serde(rename = "containment")]
+    pub containment: SelectorContainment,
     #[serde(skip)]
     pub path: Vec<InputData>,
 }
@@ -39,6 +59,7 @@ impl Default for Selector {
     fn default() -> Self {
         Self {
             style: SelectorStyle::Polygon,
+            containment: SelectorContainment::default(),
             path: vec![],
         }
     }
@@ -104,15 +125,65 @@ impl PenBehaviour for Selector {
             .canvas()
             .set_cursor(Some(&appwindow.canvas().cursor()));
 
-        appwindow
+        // With shift held down the selection is extended, or for taps toggled
+        let extend_selection =
+            input::keyboard_modifier_state().contains(gdk::ModifierType::SHIFT_MASK);
+        if !extend_selection {
+            let all_strokes = appwindow
+                .canvas()
+                .sheet()
+                .borrow()
+                .strokes_state
+                .keys_sorted_chrono();
+            appwindow
+                .canvas()
+                .sheet()
+                .borrow_mut()
+                .strokes_state
+                .set_selected_keys(&all_strokes, false);
+        }
+
+        let tap_pos = appwindow
             .canvas()
-            .sheet()
-            .borrow_mut()
-            .strokes_state
-            .update_selection_for_selector(
-                &appwindow.canvas().pens().borrow().selector,
-                Some(appwindow.canvas().viewport_in_sheet_coords()),
-            );
+            .pens()
+            .borrow()
+            .selector
+            .tap_pos(appwindow.canvas().zoom());
+
+        if let Some(tap_pos) = tap_pos {
+            let tapped_key = appwindow
+                .canvas()
+                .sheet()
+                .borrow()
+                .strokes_state
+                .stroke_key_at_pos(tap_pos, Self::TAP_DISTANCE_MAX / appwindow.canvas().zoom());
+
+            if let Some(tapped_key) = tapped_key {
+                let selected = appwindow
+                    .canvas()
+                    .sheet()
+                    .borrow()
+                    .strokes_state
+                    .selected(tapped_key)
+                    .unwrap_or(false);
+                appwindow
+                    .canvas()
+                    .sheet()
+                    .borrow_mut()
+                    .strokes_state
+                    .set_selected(tapped_key, !selected);
+            }
+        } else {
+            appwindow
+                .canvas()
+                .sheet()
+                .borrow_mut()
+                .strokes_state
+                .update_selection_for_selector(
+                    &appwindow.canvas().pens().borrow().selector,
+                    Some(appwindow.canvas().viewport_in_sheet_coords()),
+                );
+        }
         let selection_keys = appwindow
             .canvas()
             .sheet()
@@ -200,6 +271,8 @@ impl PenBehaviour for Selector {
 }
 
 impl Selector {
+    /// The max. distance (in surface coordinates) the pen may move while still counting as a tap
+    pub const TAP_DISTANCE_MAX: f64 = 4.0;
     pub const PATH_WIDTH: f64 = 1.5;
     pub const PATH_COLOR: Color = Color {
         r: 0.5,
@@ -214,6 +287,20 @@ impl Selector {
         a: 0.15,
     };
 
+    /// The position of the tap, if the selector path is short enough to be one
+    pub fn tap_pos(&self, zoom: f64) -> Option<na::Vector2<f64>> {
+        let first = self.path.first()?.pos();
+        let tapped = self.path.iter().all(|inputdata| {
+            (inputdata.pos() - first).magnitude() * zoom <= Self::TAP_DISTANCE_MAX
+        });
+
+        if tapped {
+            Some(first)
+        } else {
+            None
+        }
+    }
+
     pub fn gen_bounds(&self) -> Option<AABB> {
         // Making sure bounds are always outside of coord + width
         let mut path_iter = self.path.iter();
@@ -0,0 +1,120 @@
+use gtk4::{gdk, glib};
+use serde::{Deserialize, Serialize};
+
+use super::PenStyle;
+
+/// What a stylus side button does
+#[derive(Eq, PartialEq, Clone, Copy, Debug, glib::Enum, Serialize, Deserialize)]
+#[repr(u32)]
+#[enum_type(name = "StylusButtonAction")]
+#[serde(rename = "stylus_button_action")]
+pub enum StylusButtonAction {
+    #[enum_value(name = "None", nick = "none")]
+    #[serde(rename = "none")]
+    None = 0,
+    #[enum_value(name = "Temporary Marker", nick = "temporary-marker")]
+    #[serde(rename = "temporary_marker")]
+    TemporaryMarker,
+    #[enum_value(name = "Temporary Brush", nick = "temporary-brush")]
+    #[serde(rename = "temporary_brush")]
+    TemporaryBrush,
+    #[enum_value(name = "Temporary Shaper", nick = "temporary-shaper")]
+    #[serde(rename = "temporary_shaper")]
+    TemporaryShaper,
+    #[enum_value(name = "Temporary Eraser", nick = "temporary-eraser")]
+    #[serde(rename = "temporary_eraser")]
+    TemporaryEraser,
+    #[enum_value(name = "Temporary Selector", nick = "temporary-selector")]
+    #[serde(rename = "temporary_selector")]
+    TemporarySelector,
+    #[enum_value(name = "Temporary Tools", nick = "temporary-tools")]
+    #[serde(rename = "temporary_tools")]
+    TemporaryTools,
+    #[enum_value(name = "Undo", nick = "undo")]
+    #[serde(rename = "undo")]
+    Undo,
+    #[enum_value(name = "Redo", nick = "redo")]
+    #[serde(rename = "redo")]
+    Redo,
+}
+
+impl Default for StylusButtonAction {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+impl StylusButtonAction {
+    /// The pen which is used while the button is held down, if the action is a temporary pen
+    pub fn temporary_pen(self) -> Option<PenStyle> {
+        match self {
+            StylusButtonAction::TemporaryMarker => Some(PenStyle::MarkerStyle),
+            StylusButtonAction::TemporaryBrush => Some(PenStyle::BrushStyle),
+            StylusButtonAction::TemporaryShaper => Some(PenStyle::ShaperStyle),
+            StylusButtonAction::TemporaryEraser => Some(PenStyle::EraserStyle),
+            StylusButtonAction::TemporarySelector => Some(PenStyle::SelectorStyle),
+            StylusButtonAction::TemporaryTools => Some(PenStyle::ToolsStyle),
+            StylusButtonAction::None | StylusButtonAction::Undo | StylusButtonAction::Redo => None,
+        }
+    }
+
+    /// The name of the window action which is activated when the button is clicked, if the action is not a temporary pen
+    pub fn action_name(self) -> Option<&'static str> {
+        match self {
+            StylusButtonAction::Undo => Some("undo-stroke"),
+            StylusButtonAction::Redo => Some("redo-stroke"),
+            _ => None,
+        }
+    }
+
+    pub fn from_nick(nick: &str) -> Option<Self> {
+        match nick {
+            "none" => Some(StylusButtonAction::None),
+            "temporary-marker" => Some(StylusButtonAction::TemporaryMarker),
+            "temporary-brush" => Some(StylusButtonAction::TemporaryBrush),
+            "temporary-shaper" => Some(StylusButtonAction::TemporaryShaper),
+            "temporary-eraser" => Some(StylusButtonAction::TemporaryEraser),
+            "temporary-selector" => Some(StylusButtonAction::TemporarySelector),
+            "temporary-tools" => Some(StylusButtonAction::TemporaryTools),
+            "undo" => Some(StylusButtonAction::Undo),
+            "redo" => Some(StylusButtonAction::Redo),
+            _ => None,
+        }
+    }
+}
+
+/// Configures how the eraser end and the side buttons of a stylus are handled
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default, rename = "stylus_config")]
+pub struct StylusConfig {
+    /// Switches to the eraser while the eraser end of the stylus is used
+    #[serde(rename = "eraser_auto_switch")]
+    pub eraser_auto_switch: bool,
+    /// The action of the primary side button (usually the lower one, reported as the middle button)
+    #[serde(rename = "primary_button_action")]
+    pub primary_button_action: StylusButtonAction,
+    /// The action of the secondary side button (usually the upper one, reported as the secondary button)
+    #[serde(rename = "secondary_button_action")]
+    pub secondary_button_action: StylusButtonAction,
+}
+
+impl Default for StylusConfig {
+    fn default() -> Self {
+        Self {
+            eraser_auto_switch: true,
+            primary_button_action: StylusButtonAction::TemporarySelector,
+            secondary_button_action: StylusButtonAction::TemporaryEraser,
+        }
+    }
+}
+
+impl StylusConfig {
+    /// The action mapped to the given gdk button, if it is a side button
+    pub fn button_action(&self, button: u32) -> Option<StylusButtonAction> {
+        match button {
+            gdk::BUTTON_MIDDLE => Some(self.primary_button_action),
+            gdk::BUTTON_SECONDARY => Some(self.secondary_button_action),
+            _ => None,
+        }
+    }
+}
@@ -1,17 +1,20 @@
 use std::collections::VecDeque;
 use std::sync::{Arc, RwLock};
+use std::time;
 
 use crate::compose::color::Color;
-use crate::compose::geometry;
+use crate::compose::{geometry, regionfill, shapes};
 use crate::render::Renderer;
-use crate::strokes::strokestyle::InputData;
-use crate::strokesstate::StrokeKey;
+use crate::sheet::format::MeasureUnit;
+use crate::strokes::shapestroke::ShapeStroke;
+use crate::strokes::strokestyle::{InputData, StrokeStyle};
+use crate::strokesstate::{StrokeKey, StrokesState};
 use crate::ui::appwindow::RnoteAppWindow;
 use crate::{compose, render};
 
 use anyhow::Context;
-use gtk4::{glib, prelude::*, Snapshot};
-use p2d::bounding_volume::AABB;
+use gtk4::{glib, gsk, prelude::*, Snapshot};
+use p2d::bounding_volume::{BoundingVolume, AABB};
 use serde::{Deserialize, Serialize};
 
 use super::penbehaviour::PenBehaviour;
@@ -26,6 +29,21 @@ pub enum ToolStyle {
     #[serde(rename = "dragproximity")]
     #[enum_value(name = "Dragproximity", nick = "dragproximity")]
     DragProximity,
+    #[serde(rename = "ruler")]
+    #[enum_value(name = "Ruler", nick = "ruler")]
+    Ruler,
+    #[serde(rename = "protractor")]
+    #[enum_value(name = "Protractor", nick = "protractor")]
+    Protractor,
+    #[serde(rename = "laser")]
+    #[enum_value(name = "Laser", nick = "laser")]
+    Laser,
+    #[serde(rename = "vanishingink")]
+    #[enum_value(name = "Vanishingink", nick = "vanishingink")]
+    VanishingInk,
+    #[serde(rename = "fill")]
+    #[enum_value(name = "Fill", nick = "fill")]
+    Fill,
 }
 
 impl Default for ToolStyle {
@@ -233,6 +251,1047 @@ impl DragProximityTool {
     }
 }
 
+/// The shape of the ruler tool guide
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename = "ruler_shape")]
+pub enum RulerShape {
+    #[serde(rename = "straightedge")]
+    Straightedge,
+    #[serde(rename = "protractor")]
+    Protractor,
+}
+
+impl Default for RulerShape {
+    fn default() -> Self {
+        Self::Straightedge
+    }
+}
+
+/// How the ruler is currently moved by the tools pen
+#[derive(Debug, Clone, Copy)]
+pub enum RulerDrag {
+    Translate {
+        start_pos: na::Vector2<f64>,
+        start_input: na::Vector2<f64>,
+    },
+    Rotate,
+}
+
+/// How the current stroke is snapped to the ruler
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RulerSnap {
+    /// Along the edge of the straightedge. Holds the start and current position along the edge
+    Edge { start: f64, current: f64 },
+    /// Along a ray from the center of the protractor. The angle is determined when the input leaves the center
+    Ray { angle: Option<f64> },
+    /// Along the arc of the protractor
+    Arc,
+}
+
+/// The state the rendering of the ruler depends on. It only needs to be regenerated when it changes
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct RulerRenderState {
+    zoom: f64,
+    dpi: f64,
+    shape: RulerShape,
+    pos: na::Vector2<f64>,
+    angle: f64,
+    unit: MeasureUnit,
+    snap: Option<RulerSnap>,
+}
+
+/// A guide on the canvas, which brush and marker strokes snap to. Stays visible while other pens are used
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, rename = "ruler_tool")]
+pub struct RulerTool {
+    #[serde(skip)]
+    pub shown: bool,
+    #[serde(skip)]
+    pub shape: RulerShape,
+    /// The center of the straightedge edge or the center of the protractor
+    #[serde(skip)]
+    pub pos: na::Vector2<f64>,
+    /// The angle in radians
+    #[serde(skip)]
+    pub angle: f64,
+    /// The unit the length and the ticks are shown in
+    #[serde(rename = "unit")]
+    pub unit: MeasureUnit,
+    #[serde(skip)]
+    pub drag: Option<RulerDrag>,
+    #[serde(skip)]
+    pub snap: Option<RulerSnap>,
+    /// The rendered guide, together with the state it was rendered with
+    #[serde(skip)]
+    rendernode: Option<(RulerRenderState, gsk::RenderNode)>,
+}
+
+impl Default for RulerTool {
+    fn default() -> Self {
+        Self {
+            shown: false,
+            shape: RulerShape::default(),
+            pos: na::Vector2::zeros(),
+            angle: 0.0,
+            unit: MeasureUnit::Mm,
+            drag: None,
+            snap: None,
+            rendernode: None,
+        }
+    }
+}
+
+impl RulerTool {
+    pub const STRAIGHTEDGE_LENGTH: f64 = 800.0;
+    pub const STRAIGHTEDGE_WIDTH: f64 = 64.0;
+    pub const PROTRACTOR_RADIUS: f64 = 200.0;
+    /// The angle step (in degrees) rays from the protractor center snap to
+    pub const PROTRACTOR_ANGLE_STEP: f64 = 5.0;
+    /// The distance (in px of the view) in which strokes start snapping
+    pub const SNAP_DISTANCE: f64 = 16.0;
+    /// The radius of the rotation handle
+    pub const HANDLE_RADIUS: f64 = 12.0;
+    pub const OUTLINE_WIDTH: f64 = 1.0;
+    pub const LABEL_FONT_SIZE: f64 = 12.0;
+    pub const OUTLINE_COLOR: Color = Color {
+        r: 0.3,
+        g: 0.5,
+        b: 0.6,
+        a: 1.0,
+    };
+    pub const FILL_COLOR: Color = Color {
+        r: 0.8,
+        g: 0.9,
+        b: 1.0,
+        a: 0.4,
+    };
+    pub const HANDLE_COLOR: Color = Color {
+        r: 0.0,
+        g: 0.7,
+        b: 1.0,
+        a: 0.8,
+    };
+
+    /// Shows the guide with the given shape. If it was hidden, it is placed at the given position
+    pub fn show(&mut self, shape: RulerShape, pos: na::Vector2<f64>) {
+        if !self.shown {
+            self.pos = pos;
+            self.angle = 0.0;
+        }
+        self.shape = shape;
+        self.shown = true;
+    }
+
+    pub fn hide(&mut self) {
+        self.shown = false;
+        self.drag = None;
+        self.snap = None;
+    }
+
+    /// The unit vector along the edge
+    pub fn direction(&self) -> na::Vector2<f64> {
+        na::vector![self.angle.cos(), self.angle.sin()]
+    }
+
+    /// The unit vector pointing into the straightedge body. The protractor arc lies in the opposite direction
+    pub fn normal(&self) -> na::Vector2<f64> {
+        na::vector![-self.angle.sin(), self.angle.cos()]
+    }
+
+    /// The position of the rotation handle
+    pub fn handle_pos(&self) -> na::Vector2<f64> {
+        match self.shape {
+            RulerShape::Straightedge => {
+                self.pos
+                    + self.direction() * Self::STRAIGHTEDGE_LENGTH * 0.5
+                    + self.normal() * Self::STRAIGHTEDGE_WIDTH * 0.5
+            }
+            RulerShape::Protractor => {
+                self.pos + self.direction() * Self::PROTRACTOR_RADIUS
+                    - self.normal() * Self::HANDLE_RADIUS * 2.0
+            }
+        }
+    }
+
+    /// The position along the edge, relative to its center
+    fn edge_param(&self, pos: na::Vector2<f64>) -> f64 {
+        (pos - self.pos).dot(&self.direction())
+    }
+
+    /// The angle in degrees between the base line and the position, measured towards the protractor arc
+    fn protractor_angle(&self, pos: na::Vector2<f64>) -> f64 {
+        let offset = pos - self.pos;
+        let x = offset.dot(&self.direction());
+        let y = -offset.dot(&self.normal());
+
+        y.atan2(x).to_degrees()
+    }
+
+    /// The position on the ray from the protractor center with the given angle in degrees
+    fn protractor_ray_pos(&self, angle: f64, distance: f64) -> na::Vector2<f64> {
+        let angle = angle.to_radians();
+        self.pos + (self.direction() * angle.cos() - self.normal() * angle.sin()) * distance
+    }
+
+    pub fn drag_begin(&mut self, pos: na::Vector2<f64>) {
+        if (pos - self.handle_pos()).magnitude() < Self::HANDLE_RADIUS * 2.0 {
+            self.drag = Some(RulerDrag::Rotate);
+        } else {
+            self.drag = Some(RulerDrag::Translate {
+                start_pos: self.pos,
+                start_input: pos,
+            });
+        }
+    }
+
+    pub fn drag_motion(&mut self, pos: na::Vector2<f64>) {
+        match self.drag {
+            Some(RulerDrag::Translate {
+                start_pos,
+                start_input,
+            }) => {
+                self.pos = start_pos + (pos - start_input);
+            }
+            Some(RulerDrag::Rotate) => {
+                let offset = pos - self.pos;
+                let angle = offset[1].atan2(offset[0]);
+                // The handle of the straightedge sits below the edge
+                let angle = match self.shape {
+                    RulerShape::Straightedge => {
+                        angle
+                            - (Self::STRAIGHTEDGE_WIDTH * 0.5)
+                                .atan2(Self::STRAIGHTEDGE_LENGTH * 0.5)
+                    }
+                    RulerShape::Protractor => angle,
+                };
+                // Snap to whole degrees
+                self.angle = angle.to_degrees().round().to_radians();
+            }
+            None => {}
+        }
+    }
+
+    pub fn drag_end(&mut self) {
+        self.drag = None;
+    }
+
+    /// Starts snapping when the stroke begins close to the guide
+    pub fn snap_begin(&mut self, pos: na::Vector2<f64>, zoom: f64) {
+        self.snap = None;
+        if !self.shown {
+            return;
+        }
+        let snap_distance = Self::SNAP_DISTANCE / zoom;
+
+        match self.shape {
+            RulerShape::Straightedge => {
+                let param = self.edge_param(pos);
+                let distance = (pos - self.pos).dot(&self.normal()).abs();
+
+                if distance < snap_distance && param.abs() < Self::STRAIGHTEDGE_LENGTH * 0.5 {
+                    self.snap = Some(RulerSnap::Edge {
+                        start: param,
+                        current: param,
+                    });
+                }
+            }
+            RulerShape::Protractor => {
+                let distance = (pos - self.pos).magnitude();
+
+                if distance < snap_distance {
+                    self.snap = Some(RulerSnap::Ray { angle: None });
+                } else if (distance - Self::PROTRACTOR_RADIUS).abs() < snap_distance {
+                    self.snap = Some(RulerSnap::Arc);
+                }
+            }
+        }
+    }
+
+    /// Snaps the input data of the current stroke to the guide
+    pub fn snap_inputdata(&mut self, data_entries: &mut VecDeque<InputData>, zoom: f64) {
+        for inputdata in data_entries.iter_mut() {
+            let pos = inputdata.pos();
+
+            let snapped_pos = match self.snap {
+                Some(RulerSnap::Edge { start, .. }) => {
+                    let param = self.edge_param(pos).clamp(
+                        -Self::STRAIGHTEDGE_LENGTH * 0.5,
+                        Self::STRAIGHTEDGE_LENGTH * 0.5,
+                    );
+                    self.snap = Some(RulerSnap::Edge {
+                        start,
+                        current: param,
+                    });
+
+                    self.pos + self.direction() * param
+                }
+                Some(RulerSnap::Ray { angle }) => {
+                    let distance = (pos - self.pos).magnitude();
+                    let angle = match angle {
+                        Some(angle) => Some(angle),
+                        None if distance > Self::SNAP_DISTANCE / zoom => {
+                            let angle = (self.protractor_angle(pos) / Self::PROTRACTOR_ANGLE_STEP)
+                                .round()
+                                * Self::PROTRACTOR_ANGLE_STEP;
+                            self.snap = Some(RulerSnap::Ray { angle: Some(angle) });
+                            Some(angle)
+                        }
+                        None => None,
+                    };
+
+                    match angle {
+                        Some(angle) => {
+                            let direction = self.protractor_ray_pos(angle, 1.0) - self.pos;
+                            self.pos + direction * (pos - self.pos).dot(&direction).max(0.0)
+                        }
+                        None => self.pos,
+                    }
+                }
+                Some(RulerSnap::Arc) => {
+                    let offset = pos - self.pos;
+                    if offset.magnitude() > 0.0 {
+                        self.pos + offset.normalize() * Self::PROTRACTOR_RADIUS
+                    } else {
+                        pos
+                    }
+                }
+                None => pos,
+            };
+
+            inputdata.set_pos(snapped_pos);
+        }
+    }
+
+    pub fn snap_end(&mut self) {
+        self.snap = None;
+    }
+
+    /// Formats a length in px in the unit of the ruler
+    pub fn format_length(&self, length: f64, dpi: f64) -> String {
+        let length = MeasureUnit::convert_measurement(length, MeasureUnit::Px, dpi, self.unit, dpi);
+
+        match self.unit {
+            MeasureUnit::Px => format!("{:.0} px", length),
+            MeasureUnit::Mm => format!("{:.1} mm", length),
+            MeasureUnit::Cm => format!("{:.2} cm", length),
+        }
+    }
+
+    fn label(text: String, pos: na::Vector2<f64>) -> svg::node::element::Text {
+        svg::node::element::Text::new()
+            .set("x", pos[0])
+            .set("y", pos[1])
+            .set("font-size", Self::LABEL_FONT_SIZE)
+            .set("font-family", "sans-serif")
+            .set("text-anchor", "middle")
+            .set("fill", Self::OUTLINE_COLOR.to_css_color())
+            .add(svg::node::Text::new(text))
+    }
+
+    /// The distance between the ticks in px, and every how many ticks a long and a labeled tick is drawn
+    fn tick_spacing(&self, dpi: f64) -> (f64, usize, usize) {
+        match self.unit {
+            MeasureUnit::Px => (10.0, 5, 10),
+            MeasureUnit::Mm | MeasureUnit::Cm => (
+                MeasureUnit::convert_measurement(1.0, MeasureUnit::Mm, dpi, MeasureUnit::Px, dpi),
+                5,
+                10,
+            ),
+        }
+    }
+
+    fn gen_straightedge_group(&self, dpi: f64) -> svg::node::element::Group {
+        let (half_length, width) = (Self::STRAIGHTEDGE_LENGTH * 0.5, Self::STRAIGHTEDGE_WIDTH);
+
+        // Drawn in the local coordinate space of the ruler, with the edge on the x axis
+        let mut group = svg::node::element::Group::new().set(
+            "transform",
+            format!(
+                "translate({} {}) rotate({})",
+                self.pos[0],
+                self.pos[1],
+                self.angle.to_degrees()
+            ),
+        );
+
+        let body = svg::node::element::Rectangle::new()
+            .set("x", -half_length)
+            .set("y", 0.0)
+            .set("width", Self::STRAIGHTEDGE_LENGTH)
+            .set("height", width)
+            .set("stroke", Self::OUTLINE_COLOR.to_css_color())
+            .set("stroke-width", Self::OUTLINE_WIDTH)
+            .set("fill", Self::FILL_COLOR.to_css_color());
+        group = group.add(body);
+
+        let (tick_spacing, long_tick_every, label_every) = self.tick_spacing(dpi);
+        let n_ticks = (Self::STRAIGHTEDGE_LENGTH / tick_spacing).floor() as usize;
+        for i in 0..=n_ticks {
+            let x = -half_length + i as f64 * tick_spacing;
+            let tick_length = if i % long_tick_every == 0 {
+                width * 0.3
+            } else {
+                width * 0.15
+            };
+
+            group = group.add(
+                svg::node::element::Line::new()
+                    .set("x1", x)
+                    .set("y1", 0.0)
+                    .set("x2", x)
+                    .set("y2", tick_length)
+                    .set("stroke", Self::OUTLINE_COLOR.to_css_color())
+                    .set("stroke-width", Self::OUTLINE_WIDTH),
+            );
+
+            if i % label_every == 0 && i != 0 {
+                let value = MeasureUnit::convert_measurement(
+                    i as f64 * tick_spacing,
+                    MeasureUnit::Px,
+                    dpi,
+                    self.unit,
+                    dpi,
+                );
+                group = group.add(Self::label(
+                    format!("{:.0}", value),
+                    na::vector![x, width * 0.3 + Self::LABEL_FONT_SIZE],
+                ));
+            }
+        }
+
+        if let Some(RulerSnap::Edge { start, current }) = self.snap {
+            group = group.add(
+                svg::node::element::Line::new()
+                    .set("x1", start)
+                    .set("y1", 0.0)
+                    .set("x2", current)
+                    .set("y2", 0.0)
+                    .set("stroke", Self::HANDLE_COLOR.to_css_color())
+                    .set("stroke-width", Self::OUTLINE_WIDTH * 3.0),
+            );
+            group = group.add(Self::label(
+                self.format_length((current - start).abs(), dpi),
+                na::vector![(start + current) * 0.5, width - Self::LABEL_FONT_SIZE * 0.5],
+            ));
+        } else {
+            group = group.add(Self::label(
+                format!("{:.0}°", self.angle.to_degrees()),
+                na::vector![0.0, width - Self::LABEL_FONT_SIZE * 0.5],
+            ));
+        }
+
+        group
+    }
+
+    fn gen_protractor_group(&self) -> svg::node::element::Group {
+        let r = Self::PROTRACTOR_RADIUS;
+
+        // Drawn in the local coordinate space of the protractor, with the base line on the x axis and the arc above it
+        let mut group = svg::node::element::Group::new().set(
+            "transform",
+            format!(
+                "translate({} {}) rotate({})",
+                self.pos[0],
+                self.pos[1],
+                self.angle.to_degrees()
+            ),
+        );
+
+        let body = svg::node::element::Path::new()
+            .set(
+                "d",
+                svg::node::element::path::Data::new()
+                    .move_to((-r, 0.0))
+                    .elliptical_arc_to((r, r, 0.0, 0.0, 1.0, r, 0.0))
+                    .close(),
+            )
+            .set("stroke", Self::OUTLINE_COLOR.to_css_color())
+            .set("stroke-width", Self::OUTLINE_WIDTH)
+            .set("fill", Self::FILL_COLOR.to_css_color());
+        group = group.add(body);
+
+        for degrees in 0..=180 {
+            let angle = f64::from(degrees).to_radians();
+            let tick_length = if degrees % 10 == 0 {
+                r * 0.12
+            } else if degrees % 5 == 0 {
+                r * 0.08
+            } else {
+                r * 0.04
+            };
+            let direction = na::vector![angle.cos(), -angle.sin()];
+            let outer = direction * r;
+            let inner = direction * (r - tick_length);
+
+            group = group.add(
+                svg::node::element::Line::new()
+                    .set("x1", outer[0])
+                    .set("y1", outer[1])
+                    .set("x2", inner[0])
+                    .set("y2", inner[1])
+                    .set("stroke", Self::OUTLINE_COLOR.to_css_color())
+                    .set("stroke-width", Self::OUTLINE_WIDTH),
+            );
+
+            if degrees % 30 == 0 {
+                group = group.add(Self::label(
+                    format!("{}°", degrees),
+                    direction * (r - tick_length - Self::LABEL_FONT_SIZE),
+                ));
+            }
+        }
+
+        // The center mark
+        group = group.add(
+            svg::node::element::Circle::new()
+                .set("cx", 0.0)
+                .set("cy", 0.0)
+                .set("r", 3.0)
+                .set("fill", Self::OUTLINE_COLOR.to_css_color()),
+        );
+
+        if let Some(RulerSnap::Ray { angle: Some(angle) }) = self.snap {
+            let direction = na::vector![angle.to_radians().cos(), -angle.to_radians().sin()];
+            let end = direction * r;
+
+            group = group.add(
+                svg::node::element::Line::new()
+                    .set("x1", 0.0)
+                    .set("y1", 0.0)
+                    .set("x2", end[0])
+                    .set("y2", end[1])
+                    .set("stroke", Self::HANDLE_COLOR.to_css_color())
+                    .set("stroke-width", Self::OUTLINE_WIDTH * 2.0),
+            );
+            group = group.add(Self::label(
+                format!("{:.0}°", angle),
+                na::vector![0.0, -r * 0.4],
+            ));
+        }
+
+        group
+    }
+
+    pub fn bounds(&self) -> AABB {
+        let padding = Self::HANDLE_RADIUS * 2.0 + Self::LABEL_FONT_SIZE;
+        let corners = match self.shape {
+            RulerShape::Straightedge => {
+                let (half_length, width) = (
+                    self.direction() * Self::STRAIGHTEDGE_LENGTH * 0.5,
+                    self.normal() * Self::STRAIGHTEDGE_WIDTH,
+                );
+                [
+                    self.pos - half_length,
+                    self.pos + half_length,
+                    self.pos + half_length + width,
+                    self.pos - half_length + width,
+                ]
+            }
+            RulerShape::Protractor => {
+                let (half_length, height) = (
+                    self.direction() * Self::PROTRACTOR_RADIUS,
+                    -self.normal() * Self::PROTRACTOR_RADIUS,
+                );
+                [
+                    self.pos - half_length,
+                    self.pos + half_length,
+                    self.pos + half_length + height,
+                    self.pos - half_length + height,
+                ]
+            }
+        };
+
+        let mut bounds =
+            geometry::aabb_new_positive(na::Point2::from(corners[0]), na::Point2::from(corners[1]));
+        bounds.take_point(na::Point2::from(corners[2]));
+        bounds.take_point(na::Point2::from(corners[3]));
+
+        bounds.loosened(padding)
+    }
+
+    /// Draws the guide. It is only rendered again when its position, angle, shape, snapping or the zoom changed
+    pub fn draw(
+        &mut self,
+        dpi: f64,
+        zoom: f64,
+        snapshot: &Snapshot,
+        renderer: Arc<RwLock<Renderer>>,
+    ) -> Result<(), anyhow::Error> {
+        if !self.shown {
+            return Ok(());
+        }
+        let render_state = RulerRenderState {
+            zoom,
+            dpi,
+            shape: self.shape,
+            pos: self.pos,
+            angle: self.angle,
+            unit: self.unit,
+            snap: self.snap,
+        };
+
+        if self
+            .rendernode
+            .as_ref()
+            .map_or(true, |(rendered_state, _)| *rendered_state != render_state)
+        {
+            self.rendernode = self
+                .gen_rendernode(dpi, zoom, &renderer)?
+                .map(|rendernode| (render_state, rendernode));
+        }
+
+        if let Some((_, rendernode)) = self.rendernode.as_ref() {
+            snapshot.append_node(rendernode);
+        }
+
+        Ok(())
+    }
+
+    fn gen_rendernode(
+        &self,
+        dpi: f64,
+        zoom: f64,
+        renderer: &Arc<RwLock<Renderer>>,
+    ) -> Result<Option<gsk::RenderNode>, anyhow::Error> {
+        let bounds = geometry::aabb_ceil(self.bounds());
+
+        let guide_group = match self.shape {
+            RulerShape::Straightedge => self.gen_straightedge_group(dpi),
+            RulerShape::Protractor => self.gen_protractor_group(),
+        };
+        let handle_pos = self.handle_pos();
+        let handle = svg::node::element::Circle::new()
+            .set("cx", handle_pos[0])
+            .set("cy", handle_pos[1])
+            .set("r", Self::HANDLE_RADIUS)
+            .set("stroke", Self::OUTLINE_COLOR.to_css_color())
+            .set("stroke-width", Self::OUTLINE_WIDTH)
+            .set("fill", Self::HANDLE_COLOR.to_css_color());
+
+        let group = svg::node::element::Group::new()
+            .add(guide_group)
+            .add(handle);
+
+        let svg_data = compose::svg_node_to_string(&group)?;
+        let svg = render::Svg { svg_data, bounds };
+
+        match renderer.read().unwrap().gen_image(zoom, &[svg], bounds)? {
+            Some(image) => Ok(Some(
+                render::image_to_rendernode(&image, zoom)
+                    .context("RulerTool gen_rendernode() failed")?,
+            )),
+            None => Ok(None),
+        }
+    }
+}
+
+/// A position of a laser trail
+#[derive(Clone, Debug)]
+pub struct LaserPoint {
+    pub pos: na::Vector2<f64>,
+    /// When the position was added. The trail fades out from there
+    pub added: time::Instant,
+    /// The segment from the previous position, rendered at full opacity. Together with the zoom it was rendered at
+    rendernode: Option<(f64, gsk::RenderNode)>,
+}
+
+impl LaserPoint {
+    pub fn new(pos: na::Vector2<f64>) -> Self {
+        Self {
+            pos,
+            added: time::Instant::now(),
+            rendernode: None,
+        }
+    }
+}
+
+/// A laser pointer, which draws a glowing trail that fades out. Is never inserted into the strokes state
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, rename = "laser_tool")]
+pub struct LaserTool {
+    /// The trails, starting with the oldest position
+    #[serde(skip)]
+    pub trails: Vec<VecDeque<LaserPoint>>,
+}
+
+impl Default for LaserTool {
+    fn default() -> Self {
+        Self { trails: vec![] }
+    }
+}
+
+impl LaserTool {
+    pub const TRAIL_DURATION: time::Duration = time::Duration::from_millis(800);
+    /// The width of the core (in px of the view)
+    pub const CORE_WIDTH: f64 = 3.0;
+    /// The width of the glow (in px of the view)
+    pub const GLOW_WIDTH: f64 = 14.0;
+    pub const CORE_COLOR: Color = Color {
+        r: 1.0,
+        g: 0.9,
+        b: 0.9,
+        a: 1.0,
+    };
+    pub const GLOW_COLOR: Color = Color {
+        r: 1.0,
+        g: 0.1,
+        b: 0.1,
+        a: 0.4,
+    };
+
+    pub fn begin_trail(&mut self, pos: na::Vector2<f64>) {
+        let mut trail = VecDeque::new();
+        trail.push_back(LaserPoint::new(pos));
+        self.trails.push(trail);
+    }
+
+    pub fn extend_trail(&mut self, pos: na::Vector2<f64>) {
+        if let Some(trail) = self.trails.last_mut() {
+            trail.push_back(LaserPoint::new(pos));
+        }
+    }
+
+    /// Removes the faded out positions. Returns true if trails are left
+    pub fn prune(&mut self, now: time::Instant) -> bool {
+        for trail in self.trails.iter_mut() {
+            while trail.front().map_or(false, |point| {
+                now.duration_since(point.added) > Self::TRAIL_DURATION
+            }) {
+                trail.pop_front();
+            }
+        }
+        self.trails.retain(|trail| !trail.is_empty());
+
+        !self.trails.is_empty()
+    }
+
+    /// Draws the trails. The segments are only rendered once for each zoom, fading them out only changes their opacity
+    pub fn draw(
+        &mut self,
+        zoom: f64,
+        snapshot: &Snapshot,
+        renderer: Arc<RwLock<Renderer>>,
+    ) -> Result<(), anyhow::Error> {
+        let now = time::Instant::now();
+
+        for trail in self.trails.iter_mut() {
+            let mut previous_pos = None;
+
+            for point in trail.iter_mut() {
+                let from = previous_pos.unwrap_or(point.pos);
+                previous_pos = Some(point.pos);
+
+                if point
+                    .rendernode
+                    .as_ref()
+                    .map_or(true, |(rendered_zoom, _)| *rendered_zoom != zoom)
+                {
+                    point.rendernode =
+                        Self::gen_segment_rendernode(from, point.pos, zoom, &renderer)?
+                            .map(|rendernode| (zoom, rendernode));
+                }
+
+                if let Some((_, rendernode)) = point.rendernode.as_ref() {
+                    let opacity = 1.0
+                        - now.duration_since(point.added).as_secs_f64()
+                            / Self::TRAIL_DURATION.as_secs_f64();
+
+                    snapshot.push_opacity(opacity.clamp(0.0, 1.0));
+                    snapshot.append_node(rendernode);
+                    snapshot.pop();
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Renders the glowing segment between the positions. The round caps of the glow form the head of the trail
+    fn gen_segment_rendernode(
+        from: na::Vector2<f64>,
+        to: na::Vector2<f64>,
+        zoom: f64,
+        renderer: &Arc<RwLock<Renderer>>,
+    ) -> Result<Option<gsk::RenderNode>, anyhow::Error> {
+        let (core_width, glow_width) = (Self::CORE_WIDTH / zoom, Self::GLOW_WIDTH / zoom);
+
+        let mut group = svg::node::element::Group::new();
+        for (width, color) in [
+            (glow_width, Self::GLOW_COLOR),
+            (core_width, Self::CORE_COLOR),
+        ] {
+            group = group.add(
+                svg::node::element::Line::new()
+                    .set("x1", from[0])
+                    .set("y1", from[1])
+                    .set("x2", to[0])
+                    .set("y2", to[1])
+                    .set("stroke", color.to_css_color())
+                    .set("stroke-width", width)
+                    .set("stroke-linecap", "round"),
+            );
+        }
+
+        let bounds = geometry::aabb_ceil(
+            geometry::aabb_new_positive(na::Point2::from(from), na::Point2::from(to))
+                .loosened(glow_width),
+        );
+        let svg_data = compose::svg_node_to_string(&group)?;
+        let svg = render::Svg { svg_data, bounds };
+
+        match renderer.read().unwrap().gen_image(zoom, &[svg], bounds)? {
+            Some(image) => Ok(Some(
+                render::image_to_rendernode(&image, zoom)
+                    .context("LaserTool gen_segment_rendernode() failed")?,
+            )),
+            None => Ok(None),
+        }
+    }
+}
+
+/// A stroke of the vanishing ink
+#[derive(Clone, Debug)]
+pub struct VanishingStroke {
+    pub points: Vec<na::Vector2<f64>>,
+    pub color: Color,
+    pub width: f64,
+    /// When the stroke was finished. The timeout starts from there
+    pub finished: Option<time::Instant>,
+    /// The stroke rendered at full opacity, together with the zoom it was rendered at
+    rendernode: Option<(f64, gsk::RenderNode)>,
+}
+
+/// Ink which fades out and is removed after a timeout. The strokes are never inserted into the strokes state
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, rename = "vanishing_ink_tool")]
+pub struct VanishingInkTool {
+    /// The timeout in seconds, after which the strokes are removed
+    #[serde(rename = "timeout")]
+    pub timeout: f64,
+    #[serde(skip)]
+    pub strokes: Vec<VanishingStroke>,
+}
+
+impl Default for VanishingInkTool {
+    fn default() -> Self {
+        Self {
+            timeout: Self::TIMEOUT_DEFAULT,
+            strokes: vec![],
+        }
+    }
+}
+
+impl VanishingInkTool {
+    pub const TIMEOUT_MIN: f64 = 1.0;
+    pub const TIMEOUT_MAX: f64 = 60.0;
+    pub const TIMEOUT_DEFAULT: f64 = 3.0;
+    /// The strokes fade out in the last part of the timeout
+    pub const FADE_DURATION: f64 = 0.5;
+
+    pub fn begin_stroke(&mut self, pos: na::Vector2<f64>, color: Color, width: f64) {
+        self.strokes.push(VanishingStroke {
+            points: vec![pos],
+            color,
+            width,
+            finished: None,
+            rendernode: None,
+        });
+    }
+
+    pub fn extend_stroke(&mut self, pos: na::Vector2<f64>) {
+        if let Some(stroke) = self.strokes.last_mut() {
+            stroke.points.push(pos);
+            stroke.rendernode = None;
+        }
+    }
+
+    pub fn finish_stroke(&mut self) {
+        if let Some(stroke) = self.strokes.last_mut() {
+            stroke.finished.get_or_insert_with(time::Instant::now);
+        }
+    }
+
+    /// The opacity of a stroke, which fades out at the end of the timeout
+    fn stroke_opacity(&self, stroke: &VanishingStroke, now: time::Instant) -> f64 {
+        match stroke.finished {
+            Some(finished) => {
+                let remaining = self.timeout - now.duration_since(finished).as_secs_f64();
+                (remaining / Self::FADE_DURATION).clamp(0.0, 1.0)
+            }
+            None => 1.0,
+        }
+    }
+
+    /// Removes the strokes after the timeout. Returns true if strokes are left
+    pub fn prune(&mut self, now: time::Instant) -> bool {
+        let timeout = self.timeout;
+        self.strokes.retain(|stroke| {
+            stroke.finished.map_or(true, |finished| {
+                now.duration_since(finished).as_secs_f64() < timeout
+            })
+        });
+
+        !self.strokes.is_empty()
+    }
+
+    /// Draws the strokes. They are only rendered again when they are extended or the zoom changes,
+    /// fading them out only changes their opacity
+    pub fn draw(
+        &mut self,
+        zoom: f64,
+        snapshot: &Snapshot,
+        renderer: Arc<RwLock<Renderer>>,
+    ) -> Result<(), anyhow::Error> {
+        let now = time::Instant::now();
+
+        for i in 0..self.strokes.len() {
+            if self.strokes[i]
+                .rendernode
+                .as_ref()
+                .map_or(true, |(rendered_zoom, _)| *rendered_zoom != zoom)
+            {
+                self.strokes[i].rendernode =
+                    Self::gen_stroke_rendernode(&self.strokes[i], zoom, &renderer)?
+                        .map(|rendernode| (zoom, rendernode));
+            }
+
+            let stroke = &self.strokes[i];
+            if let Some((_, rendernode)) = stroke.rendernode.as_ref() {
+                snapshot.push_opacity(self.stroke_opacity(stroke, now));
+                snapshot.append_node(rendernode);
+                snapshot.pop();
+            }
+        }
+
+        Ok(())
+    }
+
+    fn gen_stroke_rendernode(
+        stroke: &VanishingStroke,
+        zoom: f64,
+        renderer: &Arc<RwLock<Renderer>>,
+    ) -> Result<Option<gsk::RenderNode>, anyhow::Error> {
+        let mut points = stroke.points.iter();
+        let first = match points.next() {
+            Some(first) => first,
+            None => return Ok(None),
+        };
+
+        let mut data = svg::node::element::path::Data::new().move_to((first[0], first[1]));
+        for point in points {
+            data = data.line_to((point[0], point[1]));
+        }
+        // A single point is drawn as a dot
+        if stroke.points.len() == 1 {
+            data = data.line_to((first[0], first[1]));
+        }
+
+        let path = svg::node::element::Path::new()
+            .set("d", data)
+            .set("fill", "none")
+            .set("stroke", stroke.color.to_css_color())
+            .set("stroke-width", stroke.width)
+            .set("stroke-linecap", "round")
+            .set("stroke-linejoin", "round");
+
+        let mut bounds = AABB::from_half_extents(
+            na::Point2::from(*first),
+            na::Vector2::from_element(stroke.width),
+        );
+        for point in stroke.points.iter() {
+            bounds.merge(&AABB::from_half_extents(
+                na::Point2::from(*point),
+                na::Vector2::from_element(stroke.width),
+            ));
+        }
+        let bounds = geometry::aabb_ceil(bounds);
+
+        let svg_data = compose::svg_node_to_string(&path)?;
+        let svg = render::Svg { svg_data, bounds };
+
+        match renderer.read().unwrap().gen_image(zoom, &[svg], bounds)? {
+            Some(image) => Ok(Some(
+                render::image_to_rendernode(&image, zoom)
+                    .context("VanishingInkTool gen_stroke_rendernode() failed")?,
+            )),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Fills the closed region around a position, which is bounded by the strokes, with a fill shape rendered behind them
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, rename = "fill_tool")]
+pub struct FillTool {
+    #[serde(rename = "color")]
+    pub color: Color,
+}
+
+impl Default for FillTool {
+    fn default() -> Self {
+        Self {
+            color: Self::COLOR_DEFAULT,
+        }
+    }
+}
+
+impl FillTool {
+    pub const COLOR_DEFAULT: Color = Color {
+        r: 0.6,
+        g: 0.75,
+        b: 0.95,
+        a: 1.0,
+    };
+    /// Gaps in the boundaries smaller than this are closed, so that the fill doesn't leak through slightly open strokes
+    pub const GAP_TOLERANCE: f64 = 3.0;
+    /// The size of the cells of the grid in which the region is searched, in pixels on screen
+    pub const CELL_SIZE_PIXELS: f64 = 2.0;
+
+    /// Finds the region around pos and creates a fill stroke for it. None if no closed region is found.
+    /// The region is first searched in the viewport, and if it is not closed inside it, in the entire sheet
+    pub fn fill_region(
+        &self,
+        pos: na::Vector2<f64>,
+        viewport: AABB,
+        sheet_bounds: AABB,
+        strokes_state: &StrokesState,
+        zoom: f64,
+    ) -> Option<StrokeStyle> {
+        self.fill_region_in_bounds(pos, viewport, strokes_state, zoom)
+            .or_else(|| self.fill_region_in_bounds(pos, sheet_bounds, strokes_state, zoom))
+    }
+
+    /// Finds the region around pos inside the search bounds. None if it touches the search bounds
+    fn fill_region_in_bounds(
+        &self,
+        pos: na::Vector2<f64>,
+        search_bounds: AABB,
+        strokes_state: &StrokesState,
+        zoom: f64,
+    ) -> Option<StrokeStyle> {
+        let boundary_paths = strokes_state.boundary_paths_intersecting_bounds(search_bounds);
+        // The fill extends below the boundaries to avoid gaps between the fill and the strokes
+        let overlap = boundary_paths
+            .iter()
+            .map(|path| path.width * 0.5)
+            .fold(Self::GAP_TOLERANCE * 0.5, f64::max);
+
+        let region = regionfill::enclosed_region(
+            &boundary_paths,
+            pos,
+            search_bounds,
+            Self::CELL_SIZE_PIXELS / zoom,
+            Self::GAP_TOLERANCE,
+            overlap,
+        )?;
+
+        Some(StrokeStyle::ShapeStroke(ShapeStroke::new_fill(
+            shapes::Polygon::from_geo_polygon(&region),
+            self.color,
+        )))
+    }
+}
+
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 #[serde(default, rename = "tools")]
 pub struct Tools {
@@ -242,6 +1301,67 @@ pub struct Tools {
     pub expand_sheet_tool: ExpandSheetTool,
     #[serde(skip)]
     pub drag_proximity_tool: DragProximityTool,
+    #[serde(rename = "ruler_tool")]
+    pub ruler_tool: RulerTool,
+    #[serde(skip)]
+    pub laser_tool: LaserTool,
+    #[serde(rename = "vanishing_ink_tool")]
+    pub vanishing_ink_tool: VanishingInkTool,
+    #[serde(rename = "fill_tool")]
+    pub fill_tool: FillTool,
+    /// Wether the canvas is redrawn every frame to fade out the laser trails and the vanishing ink
+    #[serde(skip)]
+    pub fading_animation: bool,
+}
+
+impl Tools {
+    /// Removes faded out laser trails and vanishing ink strokes. Returns true if anything is left to fade out
+    pub fn prune_fading(&mut self) -> bool {
+        let now = time::Instant::now();
+        let laser_active = self.laser_tool.prune(now);
+        let vanishing_ink_active = self.vanishing_ink_tool.prune(now);
+
+        laser_active || vanishing_ink_active
+    }
+
+    /// Draws the laser trails and the vanishing ink, which stay visible while other pens are used
+    pub fn draw_fading(
+        &mut self,
+        zoom: f64,
+        snapshot: &Snapshot,
+        renderer: Arc<RwLock<Renderer>>,
+    ) -> Result<(), anyhow::Error> {
+        self.vanishing_ink_tool
+            .draw(zoom, snapshot, Arc::clone(&renderer))?;
+        self.laser_tool.draw(zoom, snapshot, renderer)?;
+
+        Ok(())
+    }
+
+    /// Redraws the canvas every frame until the laser trails and the vanishing ink strokes are faded out
+    fn start_fading_animation(appwindow: &RnoteAppWindow) {
+        if appwindow.canvas().pens().borrow().tools.fading_animation {
+            return;
+        }
+        appwindow
+            .canvas()
+            .pens()
+            .borrow_mut()
+            .tools
+            .fading_animation = true;
+
+        appwindow
+            .canvas()
+            .add_tick_callback(|canvas, _frame_clock| {
+                let active = canvas.pens().borrow_mut().tools.prune_fading();
+                canvas.queue_draw();
+
+                if !active {
+                    canvas.pens().borrow_mut().tools.fading_animation = false;
+                }
+                glib::Continue(active)
+            });
+    }
 }
 
 impl PenBehaviour for Tools {
@@ -307,6 +1427,81 @@ impl PenBehaviour for Tools {
                         .drag_proximity_tool
                         .offset = na::Vector2::zeros();
                 }
+                ToolStyle::Ruler | ToolStyle::Protractor => {
+                    appwindow
+                        .canvas()
+                        .pens()
+                        .borrow_mut()
+                        .tools
+                        .ruler_tool
+                        .drag_begin(inputdata.pos());
+                }
+                ToolStyle::Laser => {
+                    appwindow
+                        .canvas()
+                        .pens()
+                        .borrow_mut()
+                        .tools
+                        .laser_tool
+                        .begin_trail(inputdata.pos());
+
+                    Self::start_fading_animation(appwindow);
+                }
+                ToolStyle::VanishingInk => {
+                    // Uses the width and color of the marker
+                    let (width, color) = {
+                        let marker_options = appwindow.canvas().pens().borrow().marker.options;
+                        (
+                            marker_options.width,
+                            marker_options.stroke_color.unwrap_or(Color::BLACK),
+                        )
+                    };
+
+                    appwindow
+                        .canvas()
+                        .pens()
+                        .borrow_mut()
+                        .tools
+                        .vanishing_ink_tool
+                        .begin_stroke(inputdata.pos(), color, width);
+                }
+                ToolStyle::Fill => {
+                    let viewport = appwindow.canvas().viewport_in_sheet_coords();
+                    let sheet_bounds = appwindow.canvas().sheet().borrow().bounds();
+                    let fill = appwindow
+                        .canvas()
+                        .pens()
+                        .borrow()
+                        .tools
+                        .fill_tool
+                        .fill_region(
+                            inputdata.pos(),
+                            viewport,
+                            sheet_bounds,
+                            &appwindow.canvas().sheet().borrow().strokes_state,
+                            appwindow.canvas().zoom(),
+                        );
+
+                    if let Some(fill) = fill {
+                        let inserted = appwindow
+                            .canvas()
+                            .sheet()
+                            .borrow_mut()
+                            .strokes_state
+                            .insert_stroke(fill);
+                        appwindow
+                            .canvas()
+                            .sheet()
+                            .borrow_mut()
+                            .strokes_state
+                            .regenerate_rendering_for_stroke_threaded(
+                                inserted,
+                                appwindow.canvas().renderer(),
+                                appwindow.canvas().zoom(),
+                            );
+                        appwindow.canvas().set_unsaved_changes(true);
+                    }
+                }
             }
         }
     }
@@ -407,6 +1602,34 @@ impl PenBehaviour for Tools {
                             .offset = na::Vector2::zeros();
                     }
                 }
+                ToolStyle::Ruler | ToolStyle::Protractor => {
+                    appwindow
+                        .canvas()
+                        .pens()
+                        .borrow_mut()
+                        .tools
+                        .ruler_tool
+                        .drag_motion(inputdata.pos());
+                }
+                ToolStyle::Laser => {
+                    appwindow
+                        .canvas()
+                        .pens()
+                        .borrow_mut()
+                        .tools
+                        .laser_tool
+                        .extend_trail(inputdata.pos());
+                }
+                ToolStyle::VanishingInk => {
+                    appwindow
+                        .canvas()
+                        .pens()
+                        .borrow_mut()
+                        .tools
+                        .vanishing_ink_tool
+                        .extend_stroke(inputdata.pos());
+                }
+                ToolStyle::Fill => {}
             }
         }
     }
@@ -450,6 +1673,27 @@ impl PenBehaviour for Tools {
                     .drag_proximity_tool
                     .offset = na::Vector2::zeros();
             }
+            ToolStyle::Ruler | ToolStyle::Protractor => {
+                appwindow
+                    .canvas()
+                    .pens()
+                    .borrow_mut()
+                    .tools
+                    .ruler_tool
+                    .drag_end();
+            }
+            ToolStyle::Laser | ToolStyle::Fill => {}
+            ToolStyle::VanishingInk => {
+                appwindow
+                    .canvas()
+                    .pens()
+                    .borrow_mut()
+                    .tools
+                    .vanishing_ink_tool
+                    .finish_stroke();
+
+                Self::start_fading_animation(appwindow);
+            }
         }
 
         appwindow.canvas().resize_endless();
@@ -474,6 +1718,12 @@ impl PenBehaviour for Tools {
                 self.drag_proximity_tool
                     .draw(sheet_bounds, zoom, snapshot, renderer)?;
             }
+            // Drawn by the canvas, because they stay visible while other pens are used
+            ToolStyle::Ruler
+            | ToolStyle::Protractor
+            | ToolStyle::Laser
+            | ToolStyle::VanishingInk => {}
+            ToolStyle::Fill => {}
         }
 
         Ok(())
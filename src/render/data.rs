@@ -0,0 +1,108 @@
+/*
+The images and svgs the strokes are rendered into, and converting them into encoded bytes.
+Nothing in here depends on GTK, so it can be used together with the tinyskia module in headless tools and tests.
+*/
+
+use anyhow::Context;
+use p2d::bounding_volume::AABB;
+
+#[derive(Debug, Copy, Clone)]
+pub enum ImageMemoryFormat {
+    R8g8b8a8Premultiplied,
+    B8g8r8a8Premultiplied,
+}
+
+#[derive(Debug, Clone)]
+pub struct Image {
+    pub data: Vec<u8>,
+    /// bounds in the coordinate space of the sheet
+    pub bounds: AABB,
+    /// width of the data
+    pub pixel_width: u32,
+    /// height of the data
+    pub pixel_height: u32,
+    /// the memory format
+    pub memory_format: ImageMemoryFormat,
+    /// the zoom the image was rendered at
+    pub zoom: f64,
+}
+
+impl Image {
+    pub fn to_imgbuf(self) -> Result<image::ImageBuffer<image::Rgba<u8>, Vec<u8>>, anyhow::Error> {
+        match self.memory_format {
+            ImageMemoryFormat::R8g8b8a8Premultiplied => {
+                image::RgbaImage::from_vec(self.pixel_width, self.pixel_height, self.data).ok_or(
+                    anyhow::anyhow!(
+                    "RgbaImage::from_vec() failed in Image to_imgbuf() for image with Format {:?}",
+                    self.memory_format
+                ),
+                )
+            }
+            ImageMemoryFormat::B8g8r8a8Premultiplied => {
+                let imgbuf_bgra8 = image::ImageBuffer::<image::Bgra<u8>, Vec<u8>>::from_vec(
+                    self.pixel_width,
+                    self.pixel_height,
+                    self.data,
+                )
+                .ok_or(anyhow::anyhow!(
+                    "RgbaImage::from_vec() failed in Image to_imgbuf() for image with Format {:?}",
+                    self.memory_format
+                ))?;
+
+                Ok(image::DynamicImage::ImageBgra8(imgbuf_bgra8).into_rgba8())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Svg {
+    pub svg_data: String,
+    pub bounds: AABB,
+}
+
+pub fn image_into_bytes(
+    image: Image,
+    format: image::ImageOutputFormat,
+) -> Result<Vec<u8>, anyhow::Error> {
+    let mut bytes_buf: Vec<u8> = vec![];
+
+    let dynamic_image = image::DynamicImage::ImageRgba8(
+        image
+            .to_imgbuf()
+            .context("image.to_imgbuf() failed in image_to_bytes()")?,
+    );
+    dynamic_image
+        .write_to(&mut bytes_buf, format)
+        .context("dynamic_image.write_to() failed in image_to_bytes()")?;
+
+    Ok(bytes_buf)
+}
+
+pub fn assert_bounds(bounds: AABB) -> Result<(), anyhow::Error> {
+    if bounds.extents()[0] < 0.0
+        || bounds.extents()[1] < 0.0
+        || bounds.maxs[0] < bounds.mins[0]
+        || bounds.maxs[1] < bounds.mins[1]
+    {
+        Err(anyhow::anyhow!(
+            "assert_bounds() failed, invalid bounds `{:?}`",
+            bounds,
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+pub fn assert_image(image: &Image) -> Result<(), anyhow::Error> {
+    assert_bounds(image.bounds)?;
+
+    if image.pixel_width == 0
+        || image.pixel_width == 0
+        || image.data.len() as u32 != 4 * image.pixel_width * image.pixel_height
+    {
+        Err(anyhow::anyhow!("assert_image() failed, invalid image data"))
+    } else {
+        Ok(())
+    }
+}
@@ -1,3 +1,8 @@
+pub mod data;
+pub mod tinyskia;
+
+pub use data::{assert_bounds, assert_image, image_into_bytes, Image, ImageMemoryFormat, Svg};
+
 use std::ops::Deref;
 
 use anyhow::Context;
@@ -16,12 +21,6 @@ pub enum RendererBackend {
     Resvg,
 }
 
-#[derive(Debug, Copy, Clone)]
-pub enum ImageMemoryFormat {
-    R8g8b8a8Premultiplied,
-    B8g8r8a8Premultiplied,
-}
-
 impl TryFrom<gdk::MemoryFormat> for ImageMemoryFormat {
     type Error = anyhow::Error;
     fn try_from(gdk_memory_format: gdk::MemoryFormat) -> Result<Self, Self::Error> {
@@ -46,53 +45,6 @@ impl From<ImageMemoryFormat> for gdk::MemoryFormat {
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct Image {
-    pub data: Vec<u8>,
-    /// bounds in the coordinate space of the sheet
-    pub bounds: AABB,
-    /// width of the data
-    pub pixel_width: u32,
-    /// height of the data
-    pub pixel_height: u32,
-    /// the memory format
-    pub memory_format: ImageMemoryFormat,
-}
-
-impl Image {
-    pub fn to_imgbuf(self) -> Result<image::ImageBuffer<image::Rgba<u8>, Vec<u8>>, anyhow::Error> {
-        match self.memory_format {
-            ImageMemoryFormat::R8g8b8a8Premultiplied => {
-                image::RgbaImage::from_vec(self.pixel_width, self.pixel_height, self.data).ok_or(
-                    anyhow::anyhow!(
-                    "RgbaImage::from_vec() failed in Image to_imgbuf() for image with Format {:?}",
-                    self.memory_format
-                ),
-                )
-            }
-            ImageMemoryFormat::B8g8r8a8Premultiplied => {
-                let imgbuf_bgra8 = image::ImageBuffer::<image::Bgra<u8>, Vec<u8>>::from_vec(
-                    self.pixel_width,
-                    self.pixel_height,
-                    self.data,
-                )
-                .ok_or(anyhow::anyhow!(
-                    "RgbaImage::from_vec() failed in Image to_imgbuf() for image with Format {:?}",
-                    self.memory_format
-                ))?;
-
-                Ok(image::DynamicImage::ImageBgra8(imgbuf_bgra8).into_rgba8())
-            }
-        }
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct Svg {
-    pub svg_data: String,
-    pub bounds: AABB,
-}
-
 #[derive(Debug, Clone)]
 pub struct Renderer {
     pub backend: RendererBackend,
@@ -222,69 +174,21 @@ impl Renderer {
             pixel_width: width_scaled,
             pixel_height: height_scaled,
             memory_format: ImageMemoryFormat::B8g8r8a8Premultiplied,
+            zoom,
         }))
     }
 
+    /// Rasterizes without GTK, see the tinyskia module
     fn gen_image_resvg(
         &self,
         zoom: f64,
         svgs: &[Svg],
         bounds: AABB,
     ) -> Result<Option<Image>, anyhow::Error> {
-        let bounds = geometry::aabb_ceil(bounds);
-        let width_scaled = ((bounds.extents()[0]) * zoom).round() as u32;
-        let height_scaled = ((bounds.extents()[1]) * zoom).round() as u32;
-
-        if width_scaled == 0 || height_scaled == 0 {
-            return Ok(None);
-        }
-
-        let mut svg_data = svgs
-            .iter()
-            .map(|svg| svg.svg_data.as_str())
-            .collect::<Vec<&str>>()
-            .join("\n");
-        svg_data = compose::wrap_svg_root(svg_data.as_str(), Some(bounds), Some(bounds), true);
-
-        let mut pixmap = tiny_skia::Pixmap::new(width_scaled, height_scaled).ok_or_else(|| {
-            anyhow::Error::msg("tiny_skia::Pixmap::new() failed in gen_image_resvg()")
-        })?;
-
-        let rtree = usvg::Tree::from_data(svg_data.as_bytes(), &self.usvg_options.to_ref())?;
-
-        resvg::render(&rtree, usvg::FitTo::Zoom(zoom as f32), pixmap.as_mut())
-            .ok_or_else(|| anyhow::Error::msg("resvg::render failed in gen_image_resvg."))?;
-
-        let data = pixmap.data().to_vec();
-
-        Ok(Some(Image {
-            data,
-            bounds,
-            pixel_width: width_scaled,
-            pixel_height: height_scaled,
-            memory_format: ImageMemoryFormat::R8g8b8a8Premultiplied,
-        }))
+        tinyskia::gen_image(zoom, svgs, bounds, &self.usvg_options)
     }
 }
 
-pub fn image_into_bytes(
-    image: Image,
-    format: image::ImageOutputFormat,
-) -> Result<Vec<u8>, anyhow::Error> {
-    let mut bytes_buf: Vec<u8> = vec![];
-
-    let dynamic_image = image::DynamicImage::ImageRgba8(
-        image
-            .to_imgbuf()
-            .context("image.to_imgbuf() failed in image_to_bytes()")?,
-    );
-    dynamic_image
-        .write_to(&mut bytes_buf, format)
-        .context("dynamic_image.write_to() failed in image_to_bytes()")?;
-
-    Ok(bytes_buf)
-}
-
 pub fn image_to_memtexture(image: &Image) -> Result<gdk::MemoryTexture, anyhow::Error> {
     assert_image(image)?;
 
@@ -421,31 +325,3 @@ fn gen_caironode_librsvg(zoom: f64, svg: &Svg) -> Result<gsk::CairoNode, anyhow:
 
     Ok(new_caironode)
 }
-
-pub fn assert_bounds(bounds: AABB) -> Result<(), anyhow::Error> {
-    if bounds.extents()[0] < 0.0
-        || bounds.extents()[1] < 0.0
-        || bounds.maxs[0] < bounds.mins[0]
-        || bounds.maxs[1] < bounds.mins[1]
-    {
-        Err(anyhow::anyhow!(
-            "assert_bounds() failed, invalid bounds `{:?}`",
-            bounds,
-        ))
-    } else {
-        Ok(())
-    }
-}
-
-pub fn assert_image(image: &Image) -> Result<(), anyhow::Error> {
-    assert_bounds(image.bounds)?;
-
-    if image.pixel_width == 0
-        || image.pixel_width == 0
-        || image.data.len() as u32 != 4 * image.pixel_width * image.pixel_height
-    {
-        Err(anyhow::anyhow!("assert_image() failed, invalid image data"))
-    } else {
-        Ok(())
-    }
-}
@@ -0,0 +1,146 @@
+/*
+Rendering purely in Rust with resvg and tiny-skia.
+Nothing in here depends on GTK, cairo or librsvg, so it can be used from worker threads and headless tools without a display.
+The output only depends on the input and the loaded fonts, which makes it usable for comparing rendered pixels.
+*/
+
+use p2d::bounding_volume::AABB;
+
+use super::data::{assert_bounds, Image, ImageMemoryFormat, Svg};
+use crate::compose::{self, geometry};
+
+/// A renderer which rasterizes svgs into images with resvg and tiny-skia
+#[derive(Debug, Clone)]
+pub struct TinySkiaRenderer {
+    pub usvg_options: usvg::Options,
+}
+
+impl Default for TinySkiaRenderer {
+    /// No fonts are loaded by default, so that the rendering does not depend on the system
+    fn default() -> Self {
+        Self {
+            usvg_options: usvg::Options::default(),
+        }
+    }
+}
+
+impl TinySkiaRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A renderer which renders text with the fonts installed on the system
+    pub fn with_system_fonts() -> Self {
+        let mut usvg_options = usvg::Options::default();
+        usvg_options.fontdb.load_system_fonts();
+
+        Self { usvg_options }
+    }
+
+    /// generates a image from SVGs. bounds are in coordinate space of the sheet, (not zoomed)
+    /// expects the svgs to be raw svg tags, no svg root or xml header needed
+    pub fn gen_image(
+        &self,
+        zoom: f64,
+        svgs: &[Svg],
+        bounds: AABB,
+    ) -> Result<Option<Image>, anyhow::Error> {
+        gen_image(zoom, svgs, bounds, &self.usvg_options)
+    }
+}
+
+/// generates a image from SVGs with the given usvg options. Returns Ok(None) if there is nothing to render
+pub fn gen_image(
+    zoom: f64,
+    svgs: &[Svg],
+    bounds: AABB,
+    usvg_options: &usvg::Options,
+) -> Result<Option<Image>, anyhow::Error> {
+    if svgs.is_empty() {
+        return Ok(None);
+    }
+    assert_bounds(bounds)?;
+
+    let bounds = geometry::aabb_ceil(bounds);
+    let width_scaled = ((bounds.extents()[0]) * zoom).round() as u32;
+    let height_scaled = ((bounds.extents()[1]) * zoom).round() as u32;
+
+    if width_scaled == 0 || height_scaled == 0 {
+        return Ok(None);
+    }
+
+    let mut svg_data = svgs
+        .iter()
+        .map(|svg| svg.svg_data.as_str())
+        .collect::<Vec<&str>>()
+        .join("\n");
+    svg_data = compose::wrap_svg_root(svg_data.as_str(), Some(bounds), Some(bounds), true);
+
+    let mut pixmap = tiny_skia::Pixmap::new(width_scaled, height_scaled).ok_or_else(|| {
+        anyhow::Error::msg("tiny_skia::Pixmap::new() failed in tinyskia gen_image()")
+    })?;
+
+    let rtree = usvg::Tree::from_data(svg_data.as_bytes(), &usvg_options.to_ref())?;
+
+    resvg::render(&rtree, usvg::FitTo::Zoom(zoom as f32), pixmap.as_mut())
+        .ok_or_else(|| anyhow::Error::msg("resvg::render failed in tinyskia gen_image()."))?;
+
+    Ok(Some(Image {
+        data: pixmap.take(),
+        bounds,
+        pixel_width: width_scaled,
+        pixel_height: height_scaled,
+        memory_format: ImageMemoryFormat::R8g8b8a8Premultiplied,
+        zoom,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compose::color::Color;
+    use crate::compose::shapes;
+    use crate::compose::transformable::Transform;
+    use crate::drawbehaviour::DrawBehaviour;
+    use crate::strokes::shapestroke::ShapeStroke;
+
+    /// Reads the rgba value of the pixel, the images are rendered in a premultiplied format
+    fn pixel(image: &Image, x: u32, y: u32) -> [u8; 4] {
+        let i = ((y * image.pixel_width + x) * 4) as usize;
+        [
+            image.data[i],
+            image.data[i + 1],
+            image.data[i + 2],
+            image.data[i + 3],
+        ]
+    }
+
+    #[test]
+    fn renders_stroke_pixels() {
+        let square = shapes::Polygon {
+            exterior: vec![
+                na::vector![10.0, 10.0],
+                na::vector![30.0, 10.0],
+                na::vector![30.0, 30.0],
+                na::vector![10.0, 30.0],
+            ],
+            interiors: vec![],
+            transform: Transform::default(),
+        };
+        let stroke = ShapeStroke::new_fill(square, Color::new(1.0, 0.0, 0.0, 1.0));
+
+        let bounds = AABB::new(na::point![0.0, 0.0], na::point![40.0, 40.0]);
+        let image = TinySkiaRenderer::new()
+            .gen_image(1.0, &stroke.gen_svgs(na::Vector2::zeros()).unwrap(), bounds)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!((image.pixel_width, image.pixel_height), (40, 40));
+        // inside the filled square
+        assert_eq!(pixel(&image, 20, 20), [255, 0, 0, 255]);
+        assert_eq!(pixel(&image, 11, 28), [255, 0, 0, 255]);
+        // outside of it
+        assert_eq!(pixel(&image, 5, 5), [0, 0, 0, 0]);
+        assert_eq!(pixel(&image, 35, 20), [0, 0, 0, 0]);
+    }
+}
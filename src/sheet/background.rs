@@ -213,6 +213,17 @@ impl Background {
         tile_size
     }
 
+    /// The spacings of the pattern columns and rows which strokes can be aligned to. None if the pattern has none in that direction
+    pub fn pattern_line_spacings(&self) -> (Option<f64>, Option<f64>) {
+        match self.pattern {
+            PatternStyle::None => (None, None),
+            PatternStyle::Lines => (None, Some(self.pattern_size[1])),
+            PatternStyle::Grid | PatternStyle::Dots => {
+                (Some(self.pattern_size[0]), Some(self.pattern_size[1]))
+            }
+        }
+    }
+
     /// Generates the background svg, without xml header or svg root
     pub fn gen_svg(&self, bounds: AABB) -> Result<render::Svg, anyhow::Error> {
         let mut group = element::Group::new();
@@ -1,6 +1,7 @@
 pub mod background;
 pub mod format;
 
+use std::ops::Range;
 use std::sync::{Arc, RwLock};
 
 use crate::compose::color::Color;
@@ -8,6 +9,7 @@ use crate::compose::shapes;
 use crate::compose::smooth::SmoothOptions;
 use crate::compose::transformable::{Transform, Transformable};
 use crate::pens::brush::{Brush, BrushStyle};
+use crate::render::tinyskia::TinySkiaRenderer;
 use crate::render::Renderer;
 use crate::strokes::bitmapimage::{self, BitmapImage};
 use crate::strokes::brushstroke::BrushStroke;
@@ -94,6 +96,36 @@ impl Sheet {
             .collect::<Vec<AABB>>()
     }
 
+    /// Clones the given pages into a new sheet, the first of them at the top. Pages beyond the last are ignored.
+    /// Strokes which reach into neighbouring pages are kept whole.
+    pub fn clone_pages(&self, pages: Range<u32>) -> Self {
+        let n_pages = self.calc_n_pages();
+        let pages = pages.start.min(n_pages)..pages.end.min(n_pages);
+
+        let page_height = self.format.height;
+        let offset = na::vector![0.0, f64::from(pages.start * page_height)];
+        let pages_bounds = AABB::new(
+            na::point![0.0, offset[1]],
+            na::point![f64::from(self.width), f64::from(pages.end * page_height)],
+        );
+
+        let mut sheet = Self {
+            width: self.width,
+            height: (pages.end - pages.start) * page_height,
+            format: self.format.clone(),
+            background: self.background.clone(),
+            ..Self::default()
+        };
+
+        let keys = self.strokes_state.keys_intersect_bounds(pages_bounds);
+        for mut stroke in self.strokes_state.clone_strokes_for_keys(&keys) {
+            stroke.translate(-offset);
+            sheet.strokes_state.insert_stroke(stroke);
+        }
+
+        sheet
+    }
+
     // a new sheet should always be imported with this method, as to not replace the threadpool, channel handlers, ..
     pub fn import_sheet(&mut self, sheet: Self) {
         self.width = sheet.width;
@@ -121,20 +153,30 @@ impl Sheet {
         snapshot.pop();
     }
 
-    pub fn open_sheet_from_rnote_bytes(&mut self, bytes: glib::Bytes) -> Result<(), anyhow::Error> {
-        let decompressed_bytes = utils::decompress_from_gzip(&bytes)?;
-        let sheet: Sheet = serde_json::from_str(&String::from_utf8(decompressed_bytes)?)?;
+    /// Loads a sheet from the bytes of a rnote file. Unlike opening it into an existing sheet,
+    /// this does not need a sheet which is set up for the canvas, e.g. for loading files in the background
+    pub fn from_rnote_bytes(bytes: &[u8]) -> Result<Self, anyhow::Error> {
+        let decompressed_bytes = utils::decompress_from_gzip(bytes)?;
+        let mut sheet: Sheet = serde_json::from_str(&String::from_utf8(decompressed_bytes)?)?;
+
+        sheet.strokes_state.restore_unsaved_state();
+
+        Ok(sheet)
+    }
+
+    pub fn open_sheet_from_rnote_bytes(&mut self, bytes: &[u8]) -> Result<(), anyhow::Error> {
+        let sheet = Self::from_rnote_bytes(bytes)?;
 
         self.import_sheet(sheet);
 
         Ok(())
     }
 
-    pub fn open_from_xopp_bytes(&mut self, bytes: glib::Bytes) -> Result<(), anyhow::Error> {
+    pub fn open_from_xopp_bytes(&mut self, bytes: &[u8]) -> Result<(), anyhow::Error> {
         // We set the sheet dpi to the hardcoded xournal++ dpi, so no need to convert values or coordinates anywhere
         self.format.dpi = xoppformat::XoppFile::DPI;
 
-        let xopp_file = xoppformat::XoppFile::load_from_bytes(&bytes)?;
+        let xopp_file = xoppformat::XoppFile::load_from_bytes(bytes)?;
 
         // Extract the largest width of all sheets, add together all heights
         let (sheet_width, sheet_height) = xopp_file
@@ -283,7 +325,7 @@ impl Sheet {
             .gen_pages_bounds()
             .iter()
             .map(|&page_bounds| {
-                let page_keys = self.strokes_state.stroke_keys_intersect_bounds(page_bounds);
+                let page_keys = self.strokes_state.keys_intersect_bounds(page_bounds);
 
                 let strokes = self.strokes_state.clone_strokes_for_keys(&page_keys);
 
@@ -377,27 +419,32 @@ impl Sheet {
 
         svgs.push(self.background.gen_svg(sheet_bounds.loosened(1.0))?);
 
-        svgs.append(&mut self.strokes_state.gen_svgs_for_strokes()?);
+        svgs.append(&mut self.strokes_state.gen_svgs_for_bounds(sheet_bounds)?);
 
         Ok(svgs)
     }
 
-    pub fn export_sheet_as_svg(&self, file: &gio::File) -> Result<(), anyhow::Error> {
+    /// Generates the svg document of the entire sheet, wrapped in a svg root
+    pub fn export_sheet_as_svg_string(&self) -> Result<String, anyhow::Error> {
         let sheet_bounds = self.bounds();
         let svgs = self.gen_svgs()?;
 
-        let mut svg_data = svgs
+        let svg_data = svgs
             .iter()
             .map(|svg| svg.svg_data.as_str())
             .collect::<Vec<&str>>()
             .join("\n");
 
-        svg_data = compose::wrap_svg_root(
+        Ok(compose::wrap_svg_root(
             svg_data.as_str(),
             Some(sheet_bounds),
             Some(sheet_bounds),
             true,
-        );
+        ))
+    }
+
+    pub fn export_sheet_as_svg(&self, file: &gio::File) -> Result<(), anyhow::Error> {
+        let svg_data = self.export_sheet_as_svg_string()?;
 
         file.replace_async(
             None,
@@ -436,6 +483,55 @@ impl Sheet {
         Ok(())
     }
 
+    /// Renders every page into a separate png with resvg, so it does not depend on GTK.
+    /// The zoom is relative to the sheet dpi
+    pub fn export_pages_as_png_bytes(&self, zoom: f64) -> Result<Vec<Vec<u8>>, anyhow::Error> {
+        let renderer = TinySkiaRenderer::with_system_fonts();
+        let background_svg = self.background.gen_svg(self.bounds().loosened(1.0))?;
+
+        self.gen_pages_bounds()
+            .into_iter()
+            .map(|page_bounds| {
+                let mut svgs = vec![background_svg.clone()];
+                svgs.append(&mut self.strokes_state.gen_svgs_for_bounds(page_bounds)?);
+
+                let image = renderer
+                    .gen_image(zoom, &svgs, page_bounds)?
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "gen_image() returned no image in export_pages_as_png_bytes() for page bounds {:?}",
+                            page_bounds
+                        )
+                    })?;
+
+                render::image_into_bytes(image, image::ImageOutputFormat::Png)
+            })
+            .collect()
+    }
+
+    /// Renders the first page into a thumbnail which fits into a square of the given size in pixels.
+    /// Used by the thumbnailer and the workspace browser, so both show the same thumbnails
+    pub fn gen_thumbnail(
+        &self,
+        size: u32,
+        renderer: &TinySkiaRenderer,
+    ) -> Result<Option<render::Image>, anyhow::Error> {
+        let page_bounds = self
+            .gen_pages_bounds()
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| self.bounds());
+        if page_bounds.extents().min() <= 0.0 {
+            return Ok(None);
+        }
+        let zoom = f64::from(size) / page_bounds.extents().max();
+
+        let mut svgs = vec![self.background.gen_svg(page_bounds)?];
+        svgs.append(&mut self.strokes_state.gen_svgs_for_bounds(page_bounds)?);
+
+        renderer.gen_image(zoom, &svgs, page_bounds)
+    }
+
     pub fn export_sheet_in_pdf_bytes(&self, title: &str) -> Result<Vec<u8>, anyhow::Error> {
         let sheet_svgs = self.gen_svgs()?;
         let sheet_bounds = self.bounds();
@@ -524,3 +620,81 @@ impl Sheet {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A sheet with three pages and a stroke on the given page, starting at 0
+    fn sheet_w_stroke_on_page(page: u32) -> (Sheet, crate::strokesstate::StrokeKey) {
+        let mut sheet = Sheet::default();
+        sheet.height = sheet.format.height * 3;
+
+        let page_height = f64::from(sheet.format.height);
+        let elements = (0..10).map(|i| {
+            Element::new(InputData::new(
+                na::vector![
+                    100.0 + f64::from(i) * 10.0,
+                    page_height * (f64::from(page) + 0.5)
+                ],
+                InputData::PRESSURE_DEFAULT,
+            ))
+        });
+        let stroke = BrushStroke::new_w_elements(elements, &Brush::default()).unwrap();
+        let key = sheet
+            .strokes_state
+            .insert_stroke(StrokeStyle::BrushStroke(stroke));
+
+        (sheet, key)
+    }
+
+    #[test]
+    fn clone_pages_keeps_selected_strokes() {
+        let (mut sheet, key) = sheet_w_stroke_on_page(1);
+        sheet.strokes_state.set_selected(key, true);
+
+        let cloned = sheet.clone_pages(1..2);
+
+        assert_eq!(cloned.calc_n_pages(), 1);
+        assert_eq!(
+            cloned
+                .strokes_state
+                .keys_intersect_bounds(cloned.bounds())
+                .len(),
+            1
+        );
+        assert!(sheet
+            .clone_pages(0..1)
+            .strokes_state
+            .keys_intersect_bounds(sheet.bounds())
+            .is_empty());
+    }
+
+    #[test]
+    fn exports_include_selected_strokes() {
+        let (mut sheet, key) = sheet_w_stroke_on_page(1);
+        let svgs_unselected = sheet.gen_svgs().unwrap().len();
+
+        sheet.strokes_state.set_selected(key, true);
+
+        // background and stroke
+        assert!(svgs_unselected > 1);
+        assert_eq!(sheet.gen_svgs().unwrap().len(), svgs_unselected);
+    }
+
+    #[test]
+    fn thumbnail_includes_selected_strokes() {
+        let renderer = TinySkiaRenderer::new();
+        let (mut sheet, key) = sheet_w_stroke_on_page(0);
+        sheet.strokes_state.set_selected(key, true);
+
+        let thumbnail = sheet.gen_thumbnail(64, &renderer).unwrap().unwrap();
+        let empty_thumbnail = Sheet::default()
+            .gen_thumbnail(64, &renderer)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(thumbnail.pixel_height, 64);
+        assert_ne!(thumbnail.data, empty_thumbnail.data);
+    }
+}
@@ -1,11 +1,13 @@
-use crate::compose::geometry;
+use crate::compose::calligraphy::CalligraphyOptions;
+use crate::compose::pencil::PencilOptions;
+use crate::compose::regionfill::BoundaryPath;
 use crate::compose::smooth::SmoothOptions;
 use crate::compose::textured::TexturedOptions;
-use crate::compose::transformable::Transformable;
-use crate::compose::{self, curves, smooth, textured};
+use crate::compose::transformable::{self, Transformable};
+use crate::compose::{self, calligraphy, curves, pencil, smooth, textured};
 use crate::drawbehaviour::DrawBehaviour;
 use crate::pens::brush::BrushStyle;
-use crate::strokes::strokestyle::Element;
+use crate::strokes::strokestyle::{Element, StyleChange};
 use crate::utils;
 use crate::{pens::brush::Brush, render};
 
@@ -13,7 +15,7 @@ use p2d::bounding_volume::{BoundingVolume, AABB};
 use rand::{Rng, SeedableRng};
 use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
 use serde::{Deserialize, Serialize};
-use svg::node::element::path;
+use svg::node::element::{self, path};
 
 use super::strokestyle::InputData;
 
@@ -30,6 +32,16 @@ pub enum BrushStrokeStyle {
         #[serde(rename = "options")]
         options: TexturedOptions,
     },
+    #[serde(rename = "calligraphy")]
+    Calligraphy {
+        #[serde(rename = "options")]
+        options: CalligraphyOptions,
+    },
+    #[serde(rename = "pencil")]
+    Pencil {
+        #[serde(rename = "options")]
+        options: PencilOptions,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,8 +53,6 @@ pub struct BrushStroke {
     pub style: BrushStrokeStyle,
     #[serde(rename = "bounds")]
     pub bounds: AABB,
-    #[serde(skip)]
-    pub hitboxes: Vec<AABB>,
 }
 
 impl Default for BrushStroke {
@@ -78,6 +88,8 @@ impl DrawBehaviour for BrushStroke {
                         let width = match &self.style {
                             BrushStrokeStyle::Solid { options } => options.width,
                             BrushStrokeStyle::Textured { options } => options.width,
+                            BrushStrokeStyle::Calligraphy { options } => options.width,
+                            BrushStrokeStyle::Pencil { options } => options.width,
                         };
 
                         if let Some(cubbez) = curves::gen_cubbez_w_catmull_rom(
@@ -120,6 +132,10 @@ impl DrawBehaviour for BrushStroke {
             BrushStrokeStyle::Textured { options } => {
                 self.gen_svgs_textured(options, offset, svg_root)
             }
+            BrushStrokeStyle::Calligraphy { options } => {
+                self.gen_svgs_calligraphy(options, offset, svg_root)
+            }
+            BrushStrokeStyle::Pencil { options } => self.gen_svgs_pencil(options, offset, svg_root),
         }
     }
 }
@@ -152,11 +168,21 @@ impl Transformable for BrushStroke {
         });
         self.update_geometry();
     }
+    fn transform_wrt_point(&mut self, matrix: na::Matrix2<f64>, center: na::Point2<f64>) {
+        self.elements.iter_mut().for_each(|element| {
+            element
+                .inputdata
+                .set_pos(transformable::transform_pos_wrt_point(
+                    matrix,
+                    element.inputdata.pos(),
+                    center,
+                ));
+        });
+        self.update_geometry();
+    }
 }
 
 impl BrushStroke {
-    pub const HITBOX_DEFAULT: f64 = 10.0;
-
     pub fn new(element: Element, brush: &Brush) -> Self {
         let seed = Some(rand_pcg::Pcg64::from_entropy().gen());
 
@@ -173,19 +199,26 @@ impl BrushStroke {
 
                 BrushStrokeStyle::Textured { options }
             }
+            BrushStyle::Calligraphy => BrushStrokeStyle::Calligraphy {
+                options: brush.calligraphy_options,
+            },
+            BrushStyle::Pencil => {
+                let mut options = brush.pencil_options;
+                options.seed = seed;
+
+                BrushStrokeStyle::Pencil { options }
+            }
         };
         let elements = Vec::with_capacity(4);
         let bounds = AABB::new(
             na::point![element.inputdata.pos()[0], element.inputdata.pos()[1]],
             na::point![element.inputdata.pos()[0], element.inputdata.pos()[1]],
         );
-        let hitbox = Vec::new();
 
         let mut brushstroke = Self {
             elements,
             style,
             bounds,
-            hitboxes: hitbox,
         };
 
         // Pushing with push_elem() instead filling vector, because bounds are getting updated there too
@@ -226,11 +259,122 @@ impl BrushStroke {
         element
     }
 
+    /// The width of the segment between the two elements, as it is composed by the style from the pressures (and the tilt)
+    pub fn segment_width(&self, start: &Element, end: &Element) -> f64 {
+        let start_pressure = start.inputdata.pressure();
+        let end_pressure = end.inputdata.pressure();
+
+        match &self.style {
+            BrushStrokeStyle::Solid { options } => options.width * start_pressure.max(end_pressure),
+            BrushStrokeStyle::Textured { options } => {
+                options.width * (start_pressure + end_pressure) * 0.5
+            }
+            BrushStrokeStyle::Calligraphy { options } => {
+                let direction = end.inputdata.pos() - start.inputdata.pos();
+
+                options
+                    .width_for_direction(direction, start_pressure, start.inputdata.tilt())
+                    .max(options.width_for_direction(direction, end_pressure, end.inputdata.tilt()))
+            }
+            BrushStrokeStyle::Pencil { options } => {
+                (0.7 + 0.3 * start_pressure.max(end_pressure)) * options.width
+            }
+        }
+    }
+
+    /// The segments of the stroke with their widths, which bound the regions of the fill tool and are used for hit-testing
+    pub fn boundary_paths(&self) -> Vec<BoundaryPath> {
+        if let [element] = self.elements.as_slice() {
+            return vec![BoundaryPath::new(
+                vec![element.inputdata.pos()],
+                self.segment_width(element, element),
+            )];
+        }
+
+        self.elements
+            .windows(2)
+            .map(|elements| {
+                BoundaryPath::new(
+                    vec![elements[0].inputdata.pos(), elements[1].inputdata.pos()],
+                    self.segment_width(&elements[0], &elements[1]),
+                )
+            })
+            .collect()
+    }
+
+    pub fn restyle(&mut self, change: StyleChange) {
+        match change {
+            StyleChange::StrokeColor(color) => match &mut self.style {
+                BrushStrokeStyle::Solid { options } => options.stroke_color = Some(color),
+                BrushStrokeStyle::Textured { options } => options.stroke_color = Some(color),
+                BrushStrokeStyle::Calligraphy { options } => options.stroke_color = Some(color),
+                BrushStrokeStyle::Pencil { options } => options.stroke_color = Some(color),
+            },
+            StyleChange::Width(width) => match &mut self.style {
+                BrushStrokeStyle::Solid { options } => options.width = width,
+                BrushStrokeStyle::Textured { options } => options.width = width,
+                BrushStrokeStyle::Calligraphy { options } => options.width = width,
+                BrushStrokeStyle::Pencil { options } => options.width = width,
+            },
+            StyleChange::BrushStyle(brush_style) => {
+                // Width, color and seed are kept, the other options are reset to their defaults. Calligraphy strokes have no seed
+                let (width, stroke_color, seed) = match &self.style {
+                    BrushStrokeStyle::Solid { options } => {
+                        (options.width, options.stroke_color, options.seed)
+                    }
+                    BrushStrokeStyle::Textured { options } => {
+                        (options.width, options.stroke_color, options.seed)
+                    }
+                    BrushStrokeStyle::Calligraphy { options } => {
+                        (options.width, options.stroke_color, None)
+                    }
+                    BrushStrokeStyle::Pencil { options } => {
+                        (options.width, options.stroke_color, options.seed)
+                    }
+                };
+
+                self.style = match brush_style {
+                    BrushStyle::Solid => BrushStrokeStyle::Solid {
+                        options: SmoothOptions {
+                            width,
+                            stroke_color,
+                            seed,
+                            ..SmoothOptions::default()
+                        },
+                    },
+                    BrushStyle::Textured => BrushStrokeStyle::Textured {
+                        options: TexturedOptions {
+                            width,
+                            stroke_color,
+                            seed,
+                            ..TexturedOptions::default()
+                        },
+                    },
+                    BrushStyle::Calligraphy => BrushStrokeStyle::Calligraphy {
+                        options: CalligraphyOptions {
+                            width,
+                            stroke_color,
+                            ..CalligraphyOptions::default()
+                        },
+                    },
+                    BrushStyle::Pencil => BrushStrokeStyle::Pencil {
+                        options: PencilOptions {
+                            width,
+                            stroke_color,
+                            seed,
+                            ..PencilOptions::default()
+                        },
+                    },
+                };
+            }
+            _ => {}
+        }
+    }
+
     pub fn update_geometry(&mut self) {
         if let Some(new_bounds) = self.gen_bounds() {
             self.set_bounds(new_bounds);
         }
-        self.hitboxes = self.gen_hitboxes();
     }
 
     fn update_bounds_to_last_elem(&mut self) {
@@ -238,6 +382,8 @@ impl BrushStroke {
             let width = match self.style {
                 BrushStrokeStyle::Solid { options } => options.width,
                 BrushStrokeStyle::Textured { options } => options.width,
+                BrushStrokeStyle::Calligraphy { options } => options.width,
+                BrushStrokeStyle::Pencil { options } => options.width,
             };
 
             self.bounds.merge(&AABB::new(
@@ -247,55 +393,6 @@ impl BrushStroke {
         }
     }
 
-    fn gen_hitboxes(&self) -> Vec<AABB> {
-        let mut hitbox: Vec<AABB> = Vec::with_capacity(self.elements.len() as usize);
-        let mut elements_iter = self.elements.iter().peekable();
-        while let Some(first) = elements_iter.next() {
-            let second = if let Some(&second) = elements_iter.peek() {
-                Some(second)
-            } else {
-                None
-            };
-            hitbox.push(self.gen_hitbox_for_elems(first, second));
-        }
-
-        hitbox
-    }
-
-    fn gen_hitbox_for_elems(&self, first: &Element, second: Option<&Element>) -> AABB {
-        let width = match self.style {
-            BrushStrokeStyle::Solid { options } => options.width,
-            BrushStrokeStyle::Textured { options } => options.width,
-        };
-
-        let first = first.inputdata.pos();
-        if let Some(second) = second {
-            let second = second.inputdata.pos();
-
-            let delta = second - first;
-            let brush_x = if delta[0] < 0.0 { -width } else { width };
-            let brush_y = if delta[1] < 0.0 { -width } else { width };
-
-            geometry::aabb_new_positive(
-                na::Point2::from(first - na::vector![brush_x / 2.0, brush_y / 2.0]),
-                na::Point2::from(first + delta + na::vector![brush_x / 2.0, brush_y / 2.0]),
-            )
-        } else {
-            geometry::aabb_new_positive(
-                na::Point2::from(
-                    first
-                        - na::vector![
-                            (Self::HITBOX_DEFAULT + width) / 2.0,
-                            (Self::HITBOX_DEFAULT + width / 2.0)
-                        ],
-                ),
-                na::Point2::from(
-                    first + na::vector![Self::HITBOX_DEFAULT + width, Self::HITBOX_DEFAULT + width],
-                ),
-            )
-        }
-    }
-
     pub fn gen_svg_for_elems(
         &self,
         elements: (&Element, &Element, &Element, &Element),
@@ -327,6 +424,12 @@ impl BrushStroke {
                     &options, elements, offset, svg_root,
                 ))
             }
+            BrushStrokeStyle::Calligraphy { options } => Ok(Self::gen_svg_elem_calligraphy(
+                &options, elements, offset, svg_root,
+            )),
+            BrushStrokeStyle::Pencil { options } => Ok(Self::gen_svg_elem_pencil(
+                &options, elements, offset, svg_root,
+            )),
         }
     }
 
@@ -525,4 +628,331 @@ impl BrushStroke {
 
         Ok(svgs)
     }
+
+    pub fn gen_svg_elem_calligraphy(
+        options: &CalligraphyOptions,
+        elements: (&Element, &Element, &Element, &Element),
+        offset: na::Vector2<f64>,
+        svg_root: bool,
+    ) -> Option<render::Svg> {
+        let mut commands = Vec::new();
+
+        let width = options.width;
+        let start_pressure = elements.1.inputdata.pressure();
+        let end_pressure = elements.2.inputdata.pressure();
+        let start_tilt = elements.1.inputdata.tilt();
+        let end_tilt = elements.2.inputdata.tilt();
+
+        let mut bounds = AABB::new_invalid();
+
+        if let Some(mut cubbez) = curves::gen_cubbez_w_catmull_rom(
+            elements.0.inputdata.pos(),
+            elements.1.inputdata.pos(),
+            elements.2.inputdata.pos(),
+            elements.3.inputdata.pos(),
+        ) {
+            cubbez.start += offset;
+            cubbez.cp1 += offset;
+            cubbez.cp2 += offset;
+            cubbez.end += offset;
+
+            // Bounds are definitely inside the polygon of the control points. (Could be improved with the second derivative of the bezier curve)
+            bounds.take_point(na::Point2::from(cubbez.start));
+            bounds.take_point(na::Point2::from(cubbez.cp1));
+            bounds.take_point(na::Point2::from(cubbez.cp2));
+            bounds.take_point(na::Point2::from(cubbez.end));
+
+            let n_splits = 5;
+            // Number of splits for the bezier curve approximation
+            let lines = curves::approx_cubbez_with_lines(cubbez, n_splits);
+            let n_lines = lines.len() as i32;
+
+            for (i, line) in lines.iter().enumerate() {
+                // Pressure and tilt are linearly interpolated between the start and end of the splitted lines
+                let t_start = f64::from(i as i32) / f64::from(n_lines);
+                let t_end = f64::from(i as i32 + 1) / f64::from(n_lines);
+
+                let line_pressures = (
+                    start_pressure + (end_pressure - start_pressure) * t_start,
+                    start_pressure + (end_pressure - start_pressure) * t_end,
+                );
+                let line_tilts = match (start_tilt, end_tilt) {
+                    (Some(start_tilt), Some(end_tilt)) => (
+                        Some(start_tilt.lerp(&end_tilt, t_start)),
+                        Some(start_tilt.lerp(&end_tilt, t_end)),
+                    ),
+                    _ => (start_tilt, end_tilt),
+                };
+
+                commands.append(&mut calligraphy::compose_line(
+                    *line,
+                    line_pressures,
+                    line_tilts,
+                    options,
+                ));
+            }
+        } else if let Some(mut line) =
+            curves::gen_line(elements.1.inputdata.pos(), elements.2.inputdata.pos())
+        {
+            line.start += offset;
+            line.end += offset;
+
+            bounds.take_point(na::Point2::from(line.start));
+            bounds.take_point(na::Point2::from(line.end));
+
+            commands.append(&mut calligraphy::compose_line(
+                line,
+                (start_pressure, end_pressure),
+                (start_tilt, end_tilt),
+                options,
+            ));
+        } else {
+            return None;
+        }
+
+        // The tilt can widen the nib
+        bounds.loosen(width * (1.0 + options.tilt_sensitivity) + 1.0);
+
+        let fill = options
+            .stroke_color
+            .map_or(String::from(""), |color| color.to_css_color());
+
+        let path = svg::node::element::Path::new()
+            .set("stroke", "none")
+            .set("fill", fill)
+            .set("fill-rule", "nonzero")
+            .set("d", path::Data::from(commands));
+
+        let mut svg_data = compose::svg_node_to_string(&path)
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "node_to_string() failed in gen_svg_elem_calligraphy() of brushstroke with Err `{}`",
+                    e
+                )
+            })
+            .ok()?;
+
+        if svg_root {
+            svg_data = compose::wrap_svg_root(&svg_data, Some(bounds), Some(bounds), true);
+        }
+        Some(render::Svg { svg_data, bounds })
+    }
+
+    pub fn gen_svgs_calligraphy(
+        &self,
+        options: CalligraphyOptions,
+        offset: na::Vector2<f64>,
+        svg_root: bool,
+    ) -> Result<Vec<render::Svg>, anyhow::Error> {
+        let svgs: Vec<render::Svg> = self
+            .elements
+            .iter()
+            .zip(self.elements.iter().skip(1))
+            .zip(self.elements.iter().skip(2))
+            .zip(self.elements.iter().skip(3))
+            .filter_map(|(((first, second), third), forth)| {
+                Self::gen_svg_elem_calligraphy(
+                    &options,
+                    (first, second, third, forth),
+                    offset,
+                    svg_root,
+                )
+            })
+            .collect();
+
+        Ok(svgs)
+    }
+
+    /// Generates the svg for the pencil style. The returned svg contains the grain pattern, so it can be rendered on its own.
+    pub fn gen_svg_elem_pencil(
+        options: &PencilOptions,
+        elements: (&Element, &Element, &Element, &Element),
+        offset: na::Vector2<f64>,
+        svg_root: bool,
+    ) -> Option<render::Svg> {
+        let (path, level, bounds) = Self::gen_path_pencil(options, elements, offset)?;
+
+        let group = element::Group::new()
+            .add(
+                element::Definitions::new()
+                    .add(pencil::compose_grain_pattern(level, offset, options)),
+            )
+            .add(path);
+
+        let mut svg_data = compose::svg_node_to_string(&group)
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "node_to_string() failed in gen_svg_elem_pencil() of brushstroke with Err `{}`",
+                    e
+                )
+            })
+            .ok()?;
+
+        if svg_root {
+            svg_data = compose::wrap_svg_root(&svg_data, Some(bounds), Some(bounds), true);
+        }
+        Some(render::Svg { svg_data, bounds })
+    }
+
+    /// Generates the path of the segment, filled with the grain pattern of the returned grain level
+    fn gen_path_pencil(
+        options: &PencilOptions,
+        elements: (&Element, &Element, &Element, &Element),
+        offset: na::Vector2<f64>,
+    ) -> Option<(element::Path, u32, AABB)> {
+        let mut commands = Vec::new();
+
+        let width = options.width;
+        // Pressure mostly builds up the grain, but also slightly widens the stroke
+        let start_width = (0.7 + 0.3 * elements.1.inputdata.pressure()) * options.width;
+        let end_width = (0.7 + 0.3 * elements.2.inputdata.pressure()) * options.width;
+        let level = options
+            .grain_level((elements.1.inputdata.pressure() + elements.2.inputdata.pressure()) * 0.5);
+
+        let mut bounds = AABB::new_invalid();
+
+        if let Some(mut cubbez) = curves::gen_cubbez_w_catmull_rom(
+            elements.0.inputdata.pos(),
+            elements.1.inputdata.pos(),
+            elements.2.inputdata.pos(),
+            elements.3.inputdata.pos(),
+        ) {
+            cubbez.start += offset;
+            cubbez.cp1 += offset;
+            cubbez.cp2 += offset;
+            cubbez.end += offset;
+
+            // Bounds are definitely inside the polygon of the control points. (Could be improved with the second derivative of the bezier curve)
+            bounds.take_point(na::Point2::from(cubbez.start));
+            bounds.take_point(na::Point2::from(cubbez.cp1));
+            bounds.take_point(na::Point2::from(cubbez.cp2));
+            bounds.take_point(na::Point2::from(cubbez.end));
+
+            let n_splits = 5;
+            // Number of splits for the bezier curve approximation
+            let lines = curves::approx_cubbez_with_lines(cubbez, n_splits);
+            let n_lines = lines.len() as i32;
+
+            for (i, line) in lines.iter().enumerate() {
+                let line_start_width = start_width
+                    + (end_width - start_width) * (f64::from(i as i32) / f64::from(n_lines));
+                let line_end_width = start_width
+                    + (end_width - start_width) * (f64::from(i as i32 + 1) / f64::from(n_lines));
+
+                commands.append(&mut pencil::compose_line(
+                    *line,
+                    line_start_width,
+                    line_end_width,
+                    options,
+                ));
+            }
+        } else if let Some(mut line) =
+            curves::gen_line(elements.1.inputdata.pos(), elements.2.inputdata.pos())
+        {
+            line.start += offset;
+            line.end += offset;
+
+            bounds.take_point(na::Point2::from(line.start));
+            bounds.take_point(na::Point2::from(line.end));
+
+            commands.append(&mut pencil::compose_line(
+                line,
+                start_width,
+                end_width,
+                options,
+            ));
+        } else {
+            return None;
+        }
+
+        bounds.loosen(width + 1.0);
+
+        let path = element::Path::new()
+            .set("stroke", "none")
+            .set("fill", format!("url(#{})", options.grain_pattern_id(level)))
+            .set("d", path::Data::from(commands));
+
+        Some((path, level, bounds))
+    }
+
+    /// Generates a single svg for the entire stroke, so that every grain pattern is only included once
+    pub fn gen_svgs_pencil(
+        &self,
+        options: PencilOptions,
+        offset: na::Vector2<f64>,
+        svg_root: bool,
+    ) -> Result<Vec<render::Svg>, anyhow::Error> {
+        let mut levels = Vec::new();
+        let mut bounds = AABB::new_invalid();
+        let mut paths_group = element::Group::new();
+
+        for (((first, second), third), forth) in self
+            .elements
+            .iter()
+            .zip(self.elements.iter().skip(1))
+            .zip(self.elements.iter().skip(2))
+            .zip(self.elements.iter().skip(3))
+        {
+            if let Some((path, level, path_bounds)) =
+                Self::gen_path_pencil(&options, (first, second, third, forth), offset)
+            {
+                if !levels.contains(&level) {
+                    levels.push(level);
+                }
+                bounds.merge(&path_bounds);
+                paths_group = paths_group.add(path);
+            }
+        }
+
+        if levels.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let defs = levels
+            .into_iter()
+            .fold(element::Definitions::new(), |defs, level| {
+                defs.add(pencil::compose_grain_pattern(level, offset, &options))
+            });
+        let group = element::Group::new().add(defs).add(paths_group);
+
+        let mut svg_data = compose::svg_node_to_string(&group).map_err(|e| {
+            anyhow::anyhow!(
+                "node_to_string() failed in gen_svgs_pencil() of brushstroke with Err `{}`",
+                e
+            )
+        })?;
+
+        if svg_root {
+            svg_data = compose::wrap_svg_root(&svg_data, Some(bounds), Some(bounds), true);
+        }
+
+        Ok(vec![render::Svg { svg_data, bounds }])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strokes::strokestyle::StrokeStyle;
+
+    #[test]
+    fn hitshape_follows_pressure() {
+        let brush = Brush::default();
+        let width = brush.smooth_options.width;
+
+        // light pressure on the left half, full pressure on the right half
+        let elements = (0..=10).map(|i| {
+            let pressure = if i < 5 { 0.2 } else { 1.0 };
+            Element::new(InputData::new(
+                na::vector![f64::from(i) * 10.0, 0.0],
+                pressure,
+            ))
+        });
+        let stroke =
+            StrokeStyle::BrushStroke(BrushStroke::new_w_elements(elements, &brush).unwrap());
+        let hitshape = stroke.hitshape();
+
+        assert!(!hitshape.contains_point(na::vector![15.0, width * 0.4], 0.0));
+        assert!(hitshape.contains_point(na::vector![85.0, width * 0.4], 0.0));
+    }
 }
@@ -1,15 +1,17 @@
+use crate::compose::color::Color;
 use crate::compose::smooth::SmoothOptions;
 use crate::compose::{self, curves, geometry, smooth};
 use crate::{
     drawbehaviour::DrawBehaviour, pens::marker::Marker, render, strokes::strokestyle::Element,
+    strokes::strokestyle::StyleChange,
 };
 use p2d::bounding_volume::{BoundingVolume, AABB};
 use rand::{Rng, SeedableRng};
 use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
 use serde::{Deserialize, Serialize};
-use svg::node::element::path;
+use svg::node::element::{self, path};
 
-use crate::compose::transformable::Transformable;
+use crate::compose::transformable::{self, Transformable};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default, rename = "markerstroke")]
@@ -18,10 +20,10 @@ pub struct MarkerStroke {
     pub elements: Vec<Element>,
     #[serde(rename = "marker")]
     pub options: SmoothOptions,
+    #[serde(rename = "highlighter")]
+    pub highlighter: bool,
     #[serde(rename = "bounds")]
     pub bounds: AABB,
-    #[serde(skip)]
-    pub hitbox: Vec<AABB>,
 }
 
 impl Default for MarkerStroke {
@@ -29,8 +31,8 @@ impl Default for MarkerStroke {
         Self {
             elements: vec![],
             options: SmoothOptions::default(),
+            highlighter: false,
             bounds: geometry::aabb_new_zero(),
-            hitbox: vec![],
         }
     }
 }
@@ -96,6 +98,13 @@ impl DrawBehaviour for MarkerStroke {
     fn gen_svgs(&self, offset: na::Vector2<f64>) -> Result<Vec<render::Svg>, anyhow::Error> {
         let svg_root = false;
 
+        if self.highlighter {
+            return Ok(self
+                .gen_svg_highlighter(offset, svg_root)
+                .into_iter()
+                .collect());
+        }
+
         let svgs: Vec<render::Svg> = self
             .elements
             .iter()
@@ -144,11 +153,21 @@ impl Transformable for MarkerStroke {
         });
         self.update_geometry();
     }
+    fn transform_wrt_point(&mut self, matrix: na::Matrix2<f64>, center: na::Point2<f64>) {
+        self.elements.iter_mut().for_each(|element| {
+            element
+                .inputdata
+                .set_pos(transformable::transform_pos_wrt_point(
+                    matrix,
+                    element.inputdata.pos(),
+                    center,
+                ));
+        });
+        self.update_geometry();
+    }
 }
 
 impl MarkerStroke {
-    pub const HITBOX_DEFAULT: f64 = 10.0;
-
     pub fn new(element: Element, marker: &Marker) -> Self {
         let seed = Some(rand_pcg::Pcg64::from_entropy().gen());
 
@@ -157,7 +176,6 @@ impl MarkerStroke {
             na::point![element.inputdata.pos()[0], element.inputdata.pos()[1]],
             na::point![element.inputdata.pos()[0], element.inputdata.pos()[1]],
         );
-        let hitbox: Vec<AABB> = Vec::new();
 
         let mut options = marker.options;
         options.seed = seed;
@@ -165,8 +183,8 @@ impl MarkerStroke {
         let mut markerstroke = Self {
             elements,
             options,
+            highlighter: marker.highlighter,
             bounds,
-            hitbox,
         };
 
         // Pushing with push_elem() instead pushing vector, because bounds are getting updated there too
@@ -206,11 +224,18 @@ impl MarkerStroke {
         element
     }
 
+    pub fn restyle(&mut self, change: StyleChange) {
+        match change {
+            StyleChange::StrokeColor(color) => self.options.stroke_color = Some(color),
+            StyleChange::Width(width) => self.options.width = width,
+            _ => {}
+        }
+    }
+
     pub fn update_geometry(&mut self) {
         if let Some(new_bounds) = self.gen_bounds() {
             self.set_bounds(new_bounds);
         }
-        self.hitbox = self.gen_hitbox();
     }
 
     fn update_bounds_to_last_elem(&mut self) {
@@ -227,53 +252,6 @@ impl MarkerStroke {
         }
     }
 
-    fn gen_hitbox(&self) -> Vec<AABB> {
-        let mut hitbox: Vec<AABB> = Vec::with_capacity(self.elements.len() as usize);
-        let mut elements_iter = self.elements.iter().peekable();
-        while let Some(first) = elements_iter.next() {
-            let second = if let Some(&second) = elements_iter.peek() {
-                Some(second)
-            } else {
-                None
-            };
-
-            hitbox.push(self.gen_hitbox_for_elems(first, second));
-        }
-
-        hitbox
-    }
-
-    fn gen_hitbox_for_elems(&self, first: &Element, second: Option<&Element>) -> AABB {
-        let width = self.options.width;
-
-        let first = first.inputdata.pos();
-        if let Some(second) = second {
-            let second = second.inputdata.pos();
-
-            let delta = second - first;
-            let marker_x = if delta[0] < 0.0 { -width } else { width };
-            let marker_y = if delta[1] < 0.0 { -width } else { width };
-
-            geometry::aabb_new_positive(
-                na::Point2::from(first - na::vector![marker_x / 2.0, marker_y / 2.0]),
-                na::Point2::from(first + delta + na::vector![marker_x / 2.0, marker_y / 2.0]),
-            )
-        } else {
-            geometry::aabb_new_positive(
-                na::Point2::from(
-                    first
-                        - na::vector![
-                            (Self::HITBOX_DEFAULT + width) / 2.0,
-                            (Self::HITBOX_DEFAULT + width / 2.0)
-                        ],
-                ),
-                na::Point2::from(
-                    first + na::vector![Self::HITBOX_DEFAULT + width, Self::HITBOX_DEFAULT + width],
-                ),
-            )
-        }
-    }
-
     pub fn gen_svg_elem(
         options: &SmoothOptions,
         elements: (&Element, &Element, &Element, &Element),
@@ -345,4 +323,87 @@ impl MarkerStroke {
         }
         Some(render::Svg { svg_data, bounds })
     }
+
+    /// Generates a single svg for the highlighter. The segments are drawn opaque and the transparency is applied to the entire group,
+    /// so the stroke does not darken where it overlaps itself
+    pub fn gen_svg_highlighter(
+        &self,
+        offset: na::Vector2<f64>,
+        svg_root: bool,
+    ) -> Option<render::Svg> {
+        let mut commands = Vec::new();
+        let width = self.options.width;
+
+        let mut bounds = AABB::new_invalid();
+
+        for (((first, second), third), forth) in self
+            .elements
+            .iter()
+            .zip(self.elements.iter().skip(1))
+            .zip(self.elements.iter().skip(2))
+            .zip(self.elements.iter().skip(3))
+        {
+            if let Some(mut cubbez) = curves::gen_cubbez_w_catmull_rom(
+                first.inputdata.pos(),
+                second.inputdata.pos(),
+                third.inputdata.pos(),
+                forth.inputdata.pos(),
+            ) {
+                cubbez.start += offset;
+                cubbez.cp1 += offset;
+                cubbez.cp2 += offset;
+                cubbez.end += offset;
+
+                bounds.take_point(na::Point2::from(cubbez.start));
+                bounds.take_point(na::Point2::from(cubbez.cp1));
+                bounds.take_point(na::Point2::from(cubbez.cp2));
+                bounds.take_point(na::Point2::from(cubbez.end));
+
+                commands.append(&mut smooth::compose_cubbez(cubbez, true, &self.options));
+            } else if let Some(mut line) =
+                curves::gen_line(second.inputdata.pos(), third.inputdata.pos())
+            {
+                line.start += offset;
+                line.end += offset;
+
+                bounds.take_point(na::Point2::from(line.start));
+                bounds.take_point(na::Point2::from(line.end));
+
+                commands.append(&mut smooth::compose_line(line, true, &self.options));
+            }
+        }
+
+        if commands.is_empty() {
+            return None;
+        }
+
+        bounds.loosen(width + 1.0);
+
+        let color = self.options.stroke_color?;
+        let opaque_color = Color { a: 1.0, ..color };
+
+        let path = element::Path::new()
+            .set("stroke", opaque_color.to_css_color())
+            .set("stroke-width", width)
+            .set("stroke-linejoin", "round")
+            .set("stroke-linecap", "round")
+            .set("fill", "none")
+            .set("d", path::Data::from(commands));
+
+        let group = element::Group::new().set("opacity", color.a).add(path);
+
+        let mut svg_data = compose::svg_node_to_string(&group)
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "node_to_string() failed in gen_svg_highlighter() of markerstroke with Err `{}`",
+                    e
+                )
+            })
+            .ok()?;
+
+        if svg_root {
+            svg_data = compose::wrap_svg_root(&svg_data, Some(bounds), Some(bounds), true);
+        }
+        Some(render::Svg { svg_data, bounds })
+    }
 }
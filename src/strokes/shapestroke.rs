@@ -1,10 +1,13 @@
+use crate::compose::color::Color;
+use crate::compose::hitshape::HitShape;
+use crate::compose::regionfill::BoundaryPath;
 use crate::compose::rough::roughoptions::RoughOptions;
 use crate::compose::smooth::SmoothOptions;
 use crate::compose::transformable::{Transform, Transformable};
 use crate::compose::{curves, geometry, rough, shapes};
 use crate::drawbehaviour::DrawBehaviour;
 use crate::pens::shaper::ShaperDrawStyle;
-use crate::strokes::strokestyle::Element;
+use crate::strokes::strokestyle::{Element, StyleChange};
 use crate::{compose, render};
 use crate::{pens::shaper::Shaper, pens::shaper::ShaperStyle};
 
@@ -23,6 +26,8 @@ pub enum Shape {
     Rectangle(shapes::Rectangle),
     #[serde(rename = "ellipse")]
     Ellipse(shapes::Ellipse),
+    #[serde(rename = "polygon")]
+    Polygon(shapes::Polygon),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -43,6 +48,9 @@ impl Transformable for Shape {
             Self::Ellipse(ellipse) => {
                 ellipse.translate(offset);
             }
+            Self::Polygon(polygon) => {
+                polygon.translate(offset);
+            }
         }
     }
 
@@ -57,6 +65,9 @@ impl Transformable for Shape {
             Self::Ellipse(ellipse) => {
                 ellipse.rotate(angle, center);
             }
+            Self::Polygon(polygon) => {
+                polygon.rotate(angle, center);
+            }
         }
     }
 
@@ -71,6 +82,26 @@ impl Transformable for Shape {
             Self::Ellipse(ellipse) => {
                 ellipse.scale(scale);
             }
+            Self::Polygon(polygon) => {
+                polygon.scale(scale);
+            }
+        }
+    }
+
+    fn transform_wrt_point(&mut self, matrix: na::Matrix2<f64>, center: na::Point2<f64>) {
+        match self {
+            Self::Line(line) => {
+                line.transform_wrt_point(matrix, center);
+            }
+            Self::Rectangle(rectangle) => {
+                rectangle.transform_wrt_point(matrix, center);
+            }
+            Self::Ellipse(ellipse) => {
+                ellipse.transform_wrt_point(matrix, center);
+            }
+            Self::Polygon(polygon) => {
+                polygon.transform_wrt_point(matrix, center);
+            }
         }
     }
 }
@@ -81,6 +112,7 @@ impl Shape {
             Self::Line(line) => line.global_aabb(),
             Self::Rectangle(rectangle) => rectangle.global_aabb(),
             Self::Ellipse(ellipse) => ellipse.global_aabb(),
+            Self::Polygon(polygon) => polygon.global_aabb(),
         }
     }
 }
@@ -96,6 +128,9 @@ pub struct ShapeStroke {
     pub drawstyle: ShapeDrawStyle,
     #[serde(rename = "bounds")]
     pub bounds: AABB,
+    /// Wether the shape is a fill created with the fill tool
+    #[serde(rename = "fill")]
+    pub fill: bool,
 
     #[serde(skip)]
     pub rect_start: na::Vector2<f64>,
@@ -200,6 +235,17 @@ impl DrawBehaviour for ShapeStroke {
                     ShapeDrawStyle::Rough { options } => rough::ellipse(ellipse, options).into(),
                 }
             }
+            Shape::Polygon(ref polygon) => {
+                let mut polygon = polygon.clone();
+                polygon.transform.append_translation_mut(offset);
+
+                match &self.drawstyle {
+                    ShapeDrawStyle::Smooth { options } => {
+                        compose::smooth::compose_polygon(polygon, &options)
+                    }
+                    ShapeDrawStyle::Rough { options } => rough::polygon(polygon, options).into(),
+                }
+            }
         };
 
         let svg_data = compose::svg_node_to_string(&element).map_err(|e| {
@@ -230,9 +276,16 @@ impl Transformable for ShapeStroke {
         self.shape.scale(scale);
         self.update_geometry();
     }
+    fn transform_wrt_point(&mut self, matrix: na::Matrix2<f64>, center: na::Point2<f64>) {
+        self.shape.transform_wrt_point(matrix, center);
+        self.update_geometry();
+    }
 }
 
 impl ShapeStroke {
+    /// The amount of segments which approximate an ellipse outline
+    pub const ELLIPSE_BOUNDARY_SEGMENTS: u32 = 64;
+
     pub fn new(element: Element, shaper: &Shaper) -> Self {
         let seed = Some(rand_pcg::Pcg64::from_entropy().gen());
 
@@ -276,6 +329,7 @@ impl ShapeStroke {
             shape,
             drawstyle,
             bounds,
+            fill: false,
             seed,
             rect_start: element.inputdata.pos(),
             rect_current: element.inputdata.pos(),
@@ -288,6 +342,139 @@ impl ShapeStroke {
         shapestroke
     }
 
+    /// A new fill of the given region, which has no outline
+    pub fn new_fill(region: shapes::Polygon, fill_color: Color) -> Self {
+        let options = SmoothOptions {
+            width: 0.0,
+            stroke_color: None,
+            fill_color: Some(fill_color),
+            ..SmoothOptions::default()
+        };
+        let shape = Shape::Polygon(region);
+        let bounds = shape.bounds();
+
+        let mut shapestroke = Self {
+            shape,
+            drawstyle: ShapeDrawStyle::Smooth { options },
+            bounds,
+            fill: true,
+            seed: None,
+            rect_start: na::Vector2::zeros(),
+            rect_current: na::Vector2::zeros(),
+        };
+
+        if let Some(new_bounds) = shapestroke.gen_bounds() {
+            shapestroke.bounds = new_bounds;
+        }
+
+        shapestroke
+    }
+
+    /// Whether the shape is a fill created with the fill tool
+    pub fn is_fill(&self) -> bool {
+        self.fill
+    }
+
+    /// The outline of the shape, which bounds the regions of the fill tool. Fills don't bound regions
+    pub fn boundary_paths(&self) -> Vec<BoundaryPath> {
+        if self.is_fill() {
+            return vec![];
+        }
+
+        vec![BoundaryPath::new(self.outline(), self.outline_width())]
+    }
+
+    /// The exact geometry for hit-testing. The outline is hit if it is drawn, the inside if the shape is filled
+    pub fn hitshape(&self) -> HitShape {
+        let (stroke_color, fill_color) = match &self.drawstyle {
+            ShapeDrawStyle::Smooth { options } => (options.stroke_color, options.fill_color),
+            ShapeDrawStyle::Rough { options } => (options.stroke_color, options.fill_color),
+        };
+
+        let paths = if stroke_color.is_some() && !self.is_fill() {
+            vec![BoundaryPath::new(self.outline(), self.outline_width())]
+        } else {
+            vec![]
+        };
+
+        let areas = match &self.shape {
+            // Lines have no inside
+            Shape::Line(_) => vec![],
+            _ if fill_color.is_none() => vec![],
+            Shape::Rectangle(_) | Shape::Ellipse(_) => vec![geo::Polygon::new(
+                points_to_linestring(&self.outline()),
+                vec![],
+            )],
+            Shape::Polygon(polygon) => {
+                let transform_ring = |ring: &Vec<na::Vector2<f64>>| {
+                    points_to_linestring(
+                        &ring
+                            .iter()
+                            .map(|&point| {
+                                polygon
+                                    .transform
+                                    .transform_point(na::Point2::from(point))
+                                    .coords
+                            })
+                            .collect::<Vec<na::Vector2<f64>>>(),
+                    )
+                };
+
+                vec![geo::Polygon::new(
+                    transform_ring(&polygon.exterior),
+                    polygon.interiors.iter().map(transform_ring).collect(),
+                )]
+            }
+        };
+
+        HitShape::new(paths, areas)
+    }
+
+    fn outline_width(&self) -> f64 {
+        match &self.drawstyle {
+            ShapeDrawStyle::Smooth { options } => options.width,
+            ShapeDrawStyle::Rough { options } => options.stroke_width(),
+        }
+    }
+
+    /// The points of the outline of lines, rectangles and ellipses in sheet coordinates. Empty for polygons
+    fn outline(&self) -> Vec<na::Vector2<f64>> {
+        match &self.shape {
+            Shape::Line(line) => vec![line.start, line.end],
+            Shape::Rectangle(rectangle) => {
+                let half_extents = rectangle.cuboid.half_extents;
+                [
+                    na::vector![-half_extents[0], -half_extents[1]],
+                    na::vector![half_extents[0], -half_extents[1]],
+                    na::vector![half_extents[0], half_extents[1]],
+                    na::vector![-half_extents[0], half_extents[1]],
+                    na::vector![-half_extents[0], -half_extents[1]],
+                ]
+                .iter()
+                .map(|&corner| {
+                    rectangle
+                        .transform
+                        .transform_point(na::Point2::from(corner))
+                        .coords
+                })
+                .collect::<Vec<na::Vector2<f64>>>()
+            }
+            Shape::Ellipse(ellipse) => (0..=Self::ELLIPSE_BOUNDARY_SEGMENTS)
+                .map(|i| {
+                    let angle = 2.0 * std::f64::consts::PI * f64::from(i)
+                        / f64::from(Self::ELLIPSE_BOUNDARY_SEGMENTS);
+                    let point = na::point![
+                        ellipse.radii[0] * angle.cos(),
+                        ellipse.radii[1] * angle.sin()
+                    ];
+
+                    ellipse.transform.transform_point(point).coords
+                })
+                .collect::<Vec<na::Vector2<f64>>>(),
+            Shape::Polygon(_) => vec![],
+        }
+    }
+
     pub fn update_shape(&mut self, element: Element) {
         match self.shape {
             Shape::Line(ref mut line) => {
@@ -313,14 +500,91 @@ impl ShapeStroke {
 
                 ellipse.radii = diff.abs();
             }
+            // Polygons are not drawn with the shaper
+            Shape::Polygon(_) => {}
         }
 
         self.update_geometry();
     }
 
+    pub fn restyle(&mut self, change: StyleChange) {
+        // Fills only have a fill color, which can't be removed
+        if self.is_fill() {
+            if let (StyleChange::FillColor(Some(color)), ShapeDrawStyle::Smooth { options }) =
+                (change, &mut self.drawstyle)
+            {
+                options.fill_color = Some(color);
+            }
+            return;
+        }
+
+        match change {
+            StyleChange::StrokeColor(color) => match &mut self.drawstyle {
+                ShapeDrawStyle::Smooth { options } => options.stroke_color = Some(color),
+                ShapeDrawStyle::Rough { options } => options.stroke_color = Some(color),
+            },
+            StyleChange::FillColor(fill_color) => match &mut self.drawstyle {
+                ShapeDrawStyle::Smooth { options } => options.fill_color = fill_color,
+                ShapeDrawStyle::Rough { options } => options.fill_color = fill_color,
+            },
+            StyleChange::Width(width) => match &mut self.drawstyle {
+                ShapeDrawStyle::Smooth { options } => options.width = width,
+                ShapeDrawStyle::Rough { options } => options.set_stroke_width(width),
+            },
+            StyleChange::ShapeDrawStyle(drawstyle) => {
+                self.drawstyle = match (drawstyle, &self.drawstyle) {
+                    (ShaperDrawStyle::Smooth, ShapeDrawStyle::Rough { options }) => {
+                        ShapeDrawStyle::Smooth {
+                            options: SmoothOptions {
+                                seed: self.seed,
+                                width: options.stroke_width(),
+                                stroke_color: options.stroke_color,
+                                fill_color: options.fill_color,
+                            },
+                        }
+                    }
+                    (ShaperDrawStyle::Rough, ShapeDrawStyle::Smooth { options }) => {
+                        let mut rough_options = RoughOptions {
+                            seed: self.seed,
+                            stroke_color: options.stroke_color,
+                            fill_color: options.fill_color,
+                            ..RoughOptions::default()
+                        };
+                        rough_options.set_stroke_width(options.width);
+
+                        ShapeDrawStyle::Rough {
+                            options: rough_options,
+                        }
+                    }
+                    (_, drawstyle) => drawstyle.clone(),
+                };
+            }
+            StyleChange::Roughness(roughness) => {
+                if let ShapeDrawStyle::Rough { options } = &mut self.drawstyle {
+                    options.set_roughness(roughness);
+                }
+            }
+            StyleChange::Bowing(bowing) => {
+                if let ShapeDrawStyle::Rough { options } = &mut self.drawstyle {
+                    options.set_bowing(bowing);
+                }
+            }
+            StyleChange::BrushStyle(_) => {}
+        }
+    }
+
     pub fn update_geometry(&mut self) {
         if let Some(new_bounds) = self.gen_bounds() {
             self.bounds = new_bounds;
         }
     }
 }
+
+fn points_to_linestring(points: &[na::Vector2<f64>]) -> geo::LineString<f64> {
+    geo::LineString::from(
+        points
+            .iter()
+            .map(|point| (point[0], point[1]))
+            .collect::<Vec<(f64, f64)>>(),
+    )
+}
@@ -1,6 +1,8 @@
 use std::sync::{Arc, RwLock};
 
 use crate::drawbehaviour::DrawBehaviour;
+use crate::pens::brush::BrushStyle;
+use crate::pens::shaper::ShaperDrawStyle;
 use crate::render::Renderer;
 use crate::{render, utils};
 
@@ -14,8 +16,11 @@ use serde::{Deserialize, Serialize};
 use super::bitmapimage::BitmapImage;
 use super::brushstroke::{BrushStroke, BrushStrokeStyle};
 use super::markerstroke::MarkerStroke;
-use super::shapestroke::ShapeStroke;
+use super::shapestroke::{ShapeDrawStyle, ShapeStroke};
 use super::vectorimage::VectorImage;
+use crate::compose::color::Color;
+use crate::compose::hitshape::HitShape;
+use crate::compose::regionfill::BoundaryPath;
 use crate::compose::transformable::Transformable;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -131,9 +136,157 @@ impl Transformable for StrokeStyle {
             }
         }
     }
+
+    fn transform_wrt_point(&mut self, matrix: na::Matrix2<f64>, center: na::Point2<f64>) {
+        match self {
+            Self::MarkerStroke(markerstroke) => {
+                markerstroke.transform_wrt_point(matrix, center);
+            }
+            Self::BrushStroke(brushstroke) => {
+                brushstroke.transform_wrt_point(matrix, center);
+            }
+            Self::ShapeStroke(shapestroke) => {
+                shapestroke.transform_wrt_point(matrix, center);
+            }
+            Self::VectorImage(vectorimage) => {
+                vectorimage.transform_wrt_point(matrix, center);
+            }
+            Self::BitmapImage(bitmapimage) => {
+                bitmapimage.transform_wrt_point(matrix, center);
+            }
+        }
+    }
+}
+
+/// A change of the style which is applied to strokes in place, e.g. to restyle the selection.
+/// Strokes ignore changes which don't apply to them
+#[derive(Debug, Clone, Copy)]
+pub enum StyleChange {
+    StrokeColor(Color),
+    /// None removes the fill
+    FillColor(Option<Color>),
+    Width(f64),
+    BrushStyle(BrushStyle),
+    ShapeDrawStyle(ShaperDrawStyle),
+    Roughness(f64),
+    Bowing(f64),
 }
 
 impl StrokeStyle {
+    /// Whether the stroke is rendered below the ink of all other strokes (e.g. highlighters and fills)
+    pub fn renders_under_ink(&self) -> bool {
+        match self {
+            StrokeStyle::MarkerStroke(markerstroke) => markerstroke.highlighter,
+            StrokeStyle::ShapeStroke(shapestroke) => shapestroke.is_fill(),
+            StrokeStyle::BrushStroke(_)
+            | StrokeStyle::VectorImage(_)
+            | StrokeStyle::BitmapImage(_) => false,
+        }
+    }
+
+    /// The stroke color as it is set in the options, not blended with the underlying content. The fill color for fills, None for images
+    pub fn stroke_color(&self) -> Option<Color> {
+        match self {
+            StrokeStyle::MarkerStroke(markerstroke) => markerstroke.options.stroke_color,
+            StrokeStyle::BrushStroke(brushstroke) => match &brushstroke.style {
+                BrushStrokeStyle::Solid { options } => options.stroke_color,
+                BrushStrokeStyle::Textured { options } => options.stroke_color,
+                BrushStrokeStyle::Calligraphy { options } => options.stroke_color,
+                BrushStrokeStyle::Pencil { options } => options.stroke_color,
+            },
+            StrokeStyle::ShapeStroke(shapestroke) => match &shapestroke.drawstyle {
+                ShapeDrawStyle::Smooth { options } if shapestroke.is_fill() => options.fill_color,
+                ShapeDrawStyle::Smooth { options } => options.stroke_color,
+                ShapeDrawStyle::Rough { options } => options.stroke_color,
+            },
+            StrokeStyle::VectorImage(_) | StrokeStyle::BitmapImage(_) => None,
+        }
+    }
+
+    /// The fill color of shapes, None for fills (see stroke_color()) and all other strokes
+    pub fn fill_color(&self) -> Option<Color> {
+        match self {
+            StrokeStyle::ShapeStroke(shapestroke) if !shapestroke.is_fill() => {
+                match &shapestroke.drawstyle {
+                    ShapeDrawStyle::Smooth { options } => options.fill_color,
+                    ShapeDrawStyle::Rough { options } => options.fill_color,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// The stroke width as it is set in the options. None for fills and images
+    pub fn width(&self) -> Option<f64> {
+        match self {
+            StrokeStyle::MarkerStroke(markerstroke) => Some(markerstroke.options.width),
+            StrokeStyle::BrushStroke(brushstroke) => match &brushstroke.style {
+                BrushStrokeStyle::Solid { options } => Some(options.width),
+                BrushStrokeStyle::Textured { options } => Some(options.width),
+                BrushStrokeStyle::Calligraphy { options } => Some(options.width),
+                BrushStrokeStyle::Pencil { options } => Some(options.width),
+            },
+            StrokeStyle::ShapeStroke(shapestroke) if shapestroke.is_fill() => None,
+            StrokeStyle::ShapeStroke(shapestroke) => match &shapestroke.drawstyle {
+                ShapeDrawStyle::Smooth { options } => Some(options.width),
+                ShapeDrawStyle::Rough { options } => Some(options.stroke_width()),
+            },
+            StrokeStyle::VectorImage(_) | StrokeStyle::BitmapImage(_) => None,
+        }
+    }
+
+    /// The roughness and bowing of rough shapes
+    pub fn roughness_bowing(&self) -> Option<(f64, f64)> {
+        match self {
+            StrokeStyle::ShapeStroke(ShapeStroke {
+                drawstyle: ShapeDrawStyle::Rough { options },
+                ..
+            }) => Some((options.roughness(), options.bowing())),
+            _ => None,
+        }
+    }
+
+    /// Applies the style change. The geometry needs to be updated afterwards
+    pub fn restyle(&mut self, change: StyleChange) {
+        match self {
+            StrokeStyle::MarkerStroke(markerstroke) => markerstroke.restyle(change),
+            StrokeStyle::BrushStroke(brushstroke) => brushstroke.restyle(change),
+            StrokeStyle::ShapeStroke(shapestroke) => shapestroke.restyle(change),
+            StrokeStyle::VectorImage(_) | StrokeStyle::BitmapImage(_) => {}
+        }
+    }
+
+    /// The paths which bound the regions of the fill tool, with the widths the strokes are composed with
+    pub fn boundary_paths(&self) -> Vec<BoundaryPath> {
+        match self {
+            // Markers are composed with a constant width, independent of the pressure
+            StrokeStyle::MarkerStroke(markerstroke) => vec![BoundaryPath::new(
+                markerstroke
+                    .elements
+                    .iter()
+                    .map(|element| element.inputdata.pos())
+                    .collect(),
+                markerstroke.options.width,
+            )],
+            StrokeStyle::BrushStroke(brushstroke) => brushstroke.boundary_paths(),
+            StrokeStyle::ShapeStroke(shapestroke) => shapestroke.boundary_paths(),
+            StrokeStyle::VectorImage(_) | StrokeStyle::BitmapImage(_) => vec![],
+        }
+    }
+
+    /// The exact geometry for hit-testing. Images are hit anywhere inside their bounds
+    pub fn hitshape(&self) -> HitShape {
+        match self {
+            StrokeStyle::MarkerStroke(_) | StrokeStyle::BrushStroke(_) => {
+                HitShape::new(self.boundary_paths(), vec![])
+            }
+            StrokeStyle::ShapeStroke(shapestroke) => shapestroke.hitshape(),
+            StrokeStyle::VectorImage(_) | StrokeStyle::BitmapImage(_) => {
+                HitShape::from_bounds(self.bounds())
+            }
+        }
+    }
+
     pub fn to_xopp(
         self,
         current_dpi: f64,
@@ -150,7 +303,11 @@ impl StrokeStyle {
                     .options
                     .stroke_color
                     .map(|color| color.into())?;
-                let tool = xoppformat::XoppTool::Pen;
+                let tool = if markerstroke.highlighter {
+                    xoppformat::XoppTool::Highlighter
+                } else {
+                    xoppformat::XoppTool::Pen
+                };
                 let width = vec![utils::convert_value_dpi(
                     markerstroke.options.width,
                     current_dpi,
@@ -194,6 +351,12 @@ impl StrokeStyle {
                     BrushStrokeStyle::Textured { options } => {
                         (options.width, options.stroke_color?.into())
                     }
+                    BrushStrokeStyle::Calligraphy { options } => {
+                        (options.width, options.stroke_color?.into())
+                    }
+                    BrushStrokeStyle::Pencil { options } => {
+                        (options.width, options.stroke_color?.into())
+                    }
                 };
 
                 let tool = xoppformat::XoppTool::Pen;
@@ -205,11 +368,41 @@ impl StrokeStyle {
                 let mut width_vec = vec![stroke_width];
 
                 // the rest are pressures between 0.0 and 1.0
-                let mut pressures = brushstroke
-                    .elements
-                    .iter()
-                    .map(|element| stroke_width * element.inputdata.pressure())
-                    .collect::<Vec<f64>>();
+                let mut pressures = match brushstroke.style {
+                    // Xopp has no nib, so the calligraphy width is approximated by the width perpendicular to the stroke direction
+                    BrushStrokeStyle::Calligraphy { options } => brushstroke
+                        .elements
+                        .iter()
+                        .zip(
+                            brushstroke
+                                .elements
+                                .iter()
+                                .skip(1)
+                                .map(Some)
+                                .chain(std::iter::once(None)),
+                        )
+                        .map(|(element, next)| {
+                            let direction = next.map_or(na::Vector2::zeros(), |next| {
+                                next.inputdata.pos() - element.inputdata.pos()
+                            });
+
+                            utils::convert_value_dpi(
+                                options.width_for_direction(
+                                    direction,
+                                    element.inputdata.pressure(),
+                                    element.inputdata.tilt(),
+                                ),
+                                current_dpi,
+                                xoppformat::XoppFile::DPI,
+                            )
+                        })
+                        .collect::<Vec<f64>>(),
+                    _ => brushstroke
+                        .elements
+                        .iter()
+                        .map(|element| stroke_width * element.inputdata.pressure())
+                        .collect::<Vec<f64>>(),
+                };
                 width_vec.append(&mut pressures);
 
                 let coords = brushstroke
@@ -366,6 +559,8 @@ impl StrokeStyle {
 pub struct InputData {
     pos: na::Vector2<f64>,
     pressure: f64,
+    /// The stylus tilt in x and y direction (between -1.0 and 1.0), if available
+    tilt: Option<na::Vector2<f64>>,
 }
 
 impl Default for InputData {
@@ -373,6 +568,7 @@ impl Default for InputData {
         Self {
             pos: na::vector![0.0, 0.0],
             pressure: Self::PRESSURE_DEFAULT,
+            tilt: None,
         }
     }
 }
@@ -403,6 +599,15 @@ impl InputData {
     pub fn set_pressure(&mut self, pressure: f64) {
         self.pressure = pressure.clamp(0.0, 1.0);
     }
+
+    pub fn tilt(&self) -> Option<na::Vector2<f64>> {
+        self.tilt
+    }
+
+    pub fn set_tilt(&mut self, tilt: Option<na::Vector2<f64>>) {
+        self.tilt =
+            tilt.map(|tilt| na::vector![tilt[0].clamp(-1.0, 1.0), tilt[1].clamp(-1.0, 1.0)]);
+    }
 }
 
 // Represents a single Stroke Element
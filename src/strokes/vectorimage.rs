@@ -100,6 +100,11 @@ impl Transformable for VectorImage {
         self.rectangle.scale(scale);
         self.update_geometry();
     }
+
+    fn transform_wrt_point(&mut self, matrix: na::Matrix2<f64>, center: na::Point2<f64>) {
+        self.rectangle.transform_wrt_point(matrix, center);
+        self.update_geometry();
+    }
 }
 
 impl VectorImage {
@@ -0,0 +1,115 @@
+use p2d::bounding_volume::AABB;
+use slotmap::SecondaryMap;
+
+use super::StrokeKey;
+
+/// A stroke key with the bounds of its stroke, the objects stored in the key tree
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct KeyTreeObject {
+    key: StrokeKey,
+    envelope: rstar::AABB<[f64; 2]>,
+}
+
+impl rstar::RTreeObject for KeyTreeObject {
+    type Envelope = rstar::AABB<[f64; 2]>;
+
+    fn envelope(&self) -> Self::Envelope {
+        self.envelope
+    }
+}
+
+impl KeyTreeObject {
+    fn new(key: StrokeKey, bounds: AABB) -> Self {
+        Self {
+            key,
+            envelope: envelope_from_bounds(bounds),
+        }
+    }
+}
+
+fn envelope_from_bounds(bounds: AABB) -> rstar::AABB<[f64; 2]> {
+    rstar::AABB::from_corners(
+        [bounds.mins[0], bounds.mins[1]],
+        [bounds.maxs[0], bounds.maxs[1]],
+    )
+}
+
+/// A spatial index over the bounds of the strokes, backed by a R-tree.
+/// It must be updated every time strokes are inserted, removed or their bounds change, so that the queries stay correct.
+#[derive(Debug, Default)]
+pub struct KeyTree {
+    tree: rstar::RTree<KeyTreeObject>,
+    /// the currently indexed objects, needed to find them again in the tree when they are updated or removed
+    objects: SecondaryMap<StrokeKey, KeyTreeObject>,
+}
+
+impl KeyTree {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts the key with the stroke bounds, replacing the previous bounds if it is already indexed
+    pub fn insert_with_key(&mut self, key: StrokeKey, bounds: AABB) {
+        self.remove_with_key(key);
+
+        let object = KeyTreeObject::new(key, bounds);
+        self.tree.insert(object);
+        self.objects.insert(key, object);
+    }
+
+    /// Updates the bounds of the key. Does nothing when they are unchanged
+    pub fn update_with_key(&mut self, key: StrokeKey, bounds: AABB) {
+        if let Some(object) = self.objects.get(key) {
+            if object.envelope == envelope_from_bounds(bounds) {
+                return;
+            }
+        }
+
+        self.insert_with_key(key, bounds);
+    }
+
+    pub fn remove_with_key(&mut self, key: StrokeKey) {
+        if let Some(object) = self.objects.remove(key) {
+            self.tree.remove(&object);
+        }
+    }
+
+    /// Rebuilds the tree from scratch, which is much faster than inserting the keys one by one
+    pub fn rebuild_from(&mut self, keys_bounds: Vec<(StrokeKey, AABB)>) {
+        let objects = keys_bounds
+            .into_iter()
+            .map(|(key, bounds)| KeyTreeObject::new(key, bounds))
+            .collect::<Vec<KeyTreeObject>>();
+
+        self.objects.clear();
+        for &object in objects.iter() {
+            self.objects.insert(object.key, object);
+        }
+        self.tree = rstar::RTree::bulk_load(objects);
+    }
+
+    pub fn clear(&mut self) {
+        self.tree = rstar::RTree::new();
+        self.objects.clear();
+    }
+
+    /// The keys whose bounds intersect the given bounds, in no particular order
+    pub fn keys_intersecting_bounds(&self, bounds: AABB) -> Vec<StrokeKey> {
+        self.tree
+            .locate_in_envelope_intersecting(&envelope_from_bounds(bounds))
+            .map(|object| object.key)
+            .collect::<Vec<StrokeKey>>()
+    }
+
+    /// The keys whose bounds are entirely below the y position, in no particular order
+    pub fn keys_below_y_pos(&self, y_pos: f64) -> Vec<StrokeKey> {
+        self.tree
+            .locate_in_envelope(&rstar::AABB::from_corners(
+                [f64::MIN, y_pos],
+                [f64::MAX, f64::MAX],
+            ))
+            .filter(|object| object.envelope.lower()[1] > y_pos)
+            .map(|object| object.key)
+            .collect::<Vec<StrokeKey>>()
+    }
+}
@@ -1,17 +1,24 @@
 pub mod chrono_comp;
+pub mod keytree;
 pub mod render_comp;
 pub mod selection_comp;
 pub mod trash_comp;
+pub mod zorder_comp;
 
+use std::cmp::Ordering;
 use std::sync::{Arc, RwLock};
 
 use chrono_comp::ChronoComponent;
+use keytree::KeyTree;
 use p2d::query::PointQuery;
 use render_comp::RenderComponent;
 use selection_comp::SelectionComponent;
 use trash_comp::TrashComponent;
+use zorder_comp::ZOrderComponent;
 
+use crate::compose::color::Color;
 use crate::compose::geometry;
+use crate::compose::regionfill::BoundaryPath;
 use crate::compose::transformable::Transformable;
 use crate::drawbehaviour::DrawBehaviour;
 use crate::pens::tools::DragProximityTool;
@@ -22,6 +29,7 @@ use crate::strokes::vectorimage::VectorImage;
 use crate::ui::appwindow::RnoteAppWindow;
 
 use gtk4::{glib, glib::clone, prelude::*};
+use once_cell::sync::Lazy;
 use p2d::bounding_volume::{BoundingSphere, BoundingVolume, AABB};
 use rayon::iter::{ParallelBridge, ParallelIterator};
 use serde::{Deserialize, Serialize};
@@ -34,11 +42,15 @@ The Entities are the StrokeKey's, which represent a stroke. There are different
     * 'trash_components': Hold state wether the strokes are trashed
     * 'selection_components': Hold state wether the strokes are selected
     * 'chrono_components': Hold state about the time, chronological ordering
+    * 'zorder_components': Hold state about the stacking of the strokes, the order in which they are rendered
     * 'render_components': Hold state about the current rendering of the strokes.
 
+Additionally the 'key_tree' is a spatial index over the bounds of the strokes. It is not a component, but must be kept in sync with the strokes
+    whenever they are inserted, removed or their bounds change. Queries for strokes in an area should always go through it.
+
 The systems are implemented as methods on StrokesState, loosely categorized to the different components (but often modify others as well).
 Most systems take a key or a slice of keys, and iterate with them over the different components.
-There also is a different category of methods which return filtered keys, e.g. `.keys_sorted_chrono` returns the keys in chronological ordering,
+There also is a different category of methods which return filtered keys, e.g. `.keys_sorted_chrono` returns the keys in chronological ordering, `.keys_sorted_zorder` in stacking order,
     `.stoke_keys_in_order_rendering` returns keys in the order which they should be rendered.
 */
 
@@ -58,13 +70,24 @@ pub enum StateTask {
     Quit,
 }
 
-pub fn default_threadpool() -> rayon::ThreadPool {
-    rayon::ThreadPoolBuilder::default()
-        .build()
-        .unwrap_or_else(|e| {
-            log::error!("default_render_threadpool() failed with Err {}", e);
-            panic!()
-        })
+/// The threadpool is shared by all strokes states, so loading a document does not spawn new threads
+static THREADPOOL: Lazy<Arc<rayon::ThreadPool>> = Lazy::new(|| {
+    Arc::new(
+        rayon::ThreadPoolBuilder::default()
+            .build()
+            .unwrap_or_else(|e| {
+                log::error!("default_render_threadpool() failed with Err {}", e);
+                panic!()
+            }),
+    )
+});
+
+pub fn default_threadpool() -> Arc<rayon::ThreadPool> {
+    Arc::clone(&THREADPOOL)
+}
+
+pub fn default_render_cache_budget() -> usize {
+    RenderComponent::RENDER_CACHE_BUDGET_DEFAULT
 }
 
 slotmap::new_key_type! {
@@ -85,11 +108,28 @@ pub struct StrokesState {
     chrono_components: SecondaryMap<StrokeKey, ChronoComponent>,
     #[serde(rename = "render_components")]
     render_components: SecondaryMap<StrokeKey, RenderComponent>,
+    #[serde(rename = "zorder_components")]
+    zorder_components: SecondaryMap<StrokeKey, ZOrderComponent>,
 
     // Other state
     /// value is equal chrono_component of the newest inserted or modified stroke.
     #[serde(rename = "chrono_counter")]
     chrono_counter: u32,
+    /// value is equal zorder_component of the topmost stroke.
+    #[serde(rename = "zorder_counter")]
+    zorder_counter: f64,
+    /// the angle (in radians) the current selection was rotated by since its strokes were selected
+    #[serde(skip)]
+    selection_rotation: f64,
+    /// spatial index over the stroke bounds
+    #[serde(skip)]
+    key_tree: KeyTree,
+    /// incremented every time the rendering of the current view is regenerated
+    #[serde(skip)]
+    render_tick: u64,
+    /// the memory budget for the cached images of all strokes, in bytes
+    #[serde(skip, default = "default_render_cache_budget")]
+    pub render_cache_budget: usize,
 
     #[serde(skip)]
     pub tasks_tx: Option<glib::Sender<StateTask>>,
@@ -98,7 +138,7 @@ pub struct StrokesState {
     #[serde(skip)]
     pub channel_source: Option<glib::Source>,
     #[serde(skip, default = "default_threadpool")]
-    pub threadpool: rayon::ThreadPool,
+    pub threadpool: Arc<rayon::ThreadPool>,
 }
 
 impl Default for StrokesState {
@@ -113,8 +153,14 @@ impl Default for StrokesState {
             selection_components: SecondaryMap::new(),
             chrono_components: SecondaryMap::new(),
             render_components: SecondaryMap::new(),
+            zorder_components: SecondaryMap::new(),
 
             chrono_counter: 0,
+            zorder_counter: 0.0,
+            selection_rotation: 0.0,
+            key_tree: KeyTree::new(),
+            render_tick: 0,
+            render_cache_budget: default_render_cache_budget(),
 
             tasks_tx: Some(render_tx),
             tasks_rx: Some(render_rx),
@@ -136,7 +182,17 @@ impl StrokesState {
         self.selection_components = strokes_state.selection_components;
         self.chrono_components = strokes_state.chrono_components;
         self.render_components = strokes_state.render_components;
+        self.zorder_components = strokes_state.zorder_components;
         self.chrono_counter = strokes_state.chrono_counter;
+        self.zorder_counter = strokes_state.zorder_counter;
+
+        self.restore_unsaved_state();
+    }
+
+    /// Restores the state which is not saved in the file, needed after deserializing
+    pub fn restore_unsaved_state(&mut self) {
+        self.rebuild_key_tree();
+        self.ensure_zorder_components();
     }
 
     /// No self as parameter to avoid already borrowed errors!
@@ -283,6 +339,8 @@ impl StrokesState {
             .insert(key, RenderComponent::default());
         self.chrono_components
             .insert(key, ChronoComponent::new(self.chrono_counter));
+        self.insert_zorder_component(key);
+        self.update_key_tree_for_stroke(key);
 
         // set flag for rendering regeneration
         if let Some(render_comp) = self.render_components.get_mut(key) {
@@ -296,6 +354,8 @@ impl StrokesState {
         self.selection_components.remove(key);
         self.chrono_components.remove(key);
         self.render_components.remove(key);
+        self.zorder_components.remove(key);
+        self.key_tree.remove_with_key(key);
 
         self.strokes.remove(key)
     }
@@ -321,6 +381,9 @@ impl StrokesState {
             StrokeStyle::VectorImage(_vectorimage) => {}
             StrokeStyle::BitmapImage(_bitmapimage) => {}
         }
+        self.update_key_tree_for_stroke(key);
+        // Highlighters grow into the strokes they are drawn over
+        self.place_under_overlapping_ink(key);
 
         self.append_rendering_new_elem_threaded_fifo(key, renderer, zoom);
     }
@@ -328,19 +391,52 @@ impl StrokesState {
     /// Clears every stroke and every component
     pub fn clear(&mut self) {
         self.chrono_counter = 0;
+        self.zorder_counter = 0.0;
+        self.selection_rotation = 0.0;
 
         self.strokes.clear();
         self.trash_components.clear();
         self.selection_components.clear();
         self.chrono_components.clear();
         self.render_components.clear();
+        self.zorder_components.clear();
+        self.key_tree.clear();
+    }
+
+    /// Updates the bounds of the stroke in the key tree. Must be called every time the bounds of a stroke might have changed
+    pub fn update_key_tree_for_stroke(&mut self, key: StrokeKey) {
+        if let Some(stroke) = self.strokes.get(key) {
+            self.key_tree.update_with_key(key, stroke.bounds());
+        }
+    }
+
+    /// Rebuilds the key tree for all strokes
+    pub fn rebuild_key_tree(&mut self) {
+        let keys_bounds = self
+            .strokes
+            .iter()
+            .map(|(key, stroke)| (key, stroke.bounds()))
+            .collect::<Vec<(StrokeKey, AABB)>>();
+
+        self.key_tree.rebuild_from(keys_bounds);
+    }
+
+    /// Sorts the keys in the order that they should be rendered, which is the z-order.
+    fn sort_keys_in_order_rendered(&self, keys: &mut Vec<StrokeKey>) {
+        let z = |key: StrokeKey| {
+            self.zorder_components
+                .get(key)
+                .map(|zorder_comp| zorder_comp.z)
+                .unwrap_or(0.0)
+        };
+        keys.sort_by(|&first, &second| z(first).partial_cmp(&z(second)).unwrap_or(Ordering::Equal));
     }
 
     /// Returns the stroke keys in the order that they should be rendered. Does not return the selection keys!
     pub fn stroke_keys_in_order_rendered(&self) -> Vec<StrokeKey> {
-        let keys_sorted_chrono = self.keys_sorted_chrono();
+        let keys_sorted_zorder = self.keys_sorted_zorder();
 
-        keys_sorted_chrono
+        keys_sorted_zorder
             .iter()
             .filter_map(|&key| {
                 if self.does_render(key).unwrap_or(false)
@@ -355,18 +451,70 @@ impl StrokesState {
             .collect::<Vec<StrokeKey>>()
     }
 
+    /// Returns the stroke keys intersecting the bounds in the order that they should be rendered. Does not return the selection keys!
     pub fn stroke_keys_intersect_bounds(&self, bounds: AABB) -> Vec<StrokeKey> {
-        self.stroke_keys_in_order_rendered()
-            .iter()
-            .filter_map(|&key| {
-                let stroke = self.strokes.get(key)?;
-                if stroke.bounds().intersects(&bounds) {
-                    Some(key)
-                } else {
-                    None
-                }
+        let mut keys = self
+            .key_tree
+            .keys_intersecting_bounds(bounds)
+            .into_iter()
+            .filter(|&key| {
+                self.does_render(key).unwrap_or(false)
+                    && !(self.trashed(key).unwrap_or(false))
+                    && !(self.selected(key).unwrap_or(false))
             })
-            .collect::<Vec<StrokeKey>>()
+            .collect::<Vec<StrokeKey>>();
+
+        self.sort_keys_in_order_rendered(&mut keys);
+        keys
+    }
+
+    /// Returns the stroke and the selection keys intersecting the bounds in the order that they should be rendered.
+    /// Used when exporting, where the selection is part of the content
+    pub fn keys_intersect_bounds(&self, bounds: AABB) -> Vec<StrokeKey> {
+        let mut keys = self
+            .key_tree
+            .keys_intersecting_bounds(bounds)
+            .into_iter()
+            .filter(|&key| {
+                self.does_render(key).unwrap_or(false) && !(self.trashed(key).unwrap_or(false))
+            })
+            .collect::<Vec<StrokeKey>>();
+
+        self.sort_keys_in_order_rendered(&mut keys);
+        keys
+    }
+
+    /// Returns the selection keys intersecting the bounds in the order that they should be rendered. Does not return the stroke keys!
+    pub fn selection_keys_intersect_bounds(&self, bounds: AABB) -> Vec<StrokeKey> {
+        let mut keys = self
+            .key_tree
+            .keys_intersecting_bounds(bounds)
+            .into_iter()
+            .filter(|&key| {
+                self.does_render(key).unwrap_or(false)
+                    && !(self.trashed(key).unwrap_or(false))
+                    && (self.selected(key).unwrap_or(false))
+            })
+            .collect::<Vec<StrokeKey>>();
+
+        self.sort_keys_in_order_rendered(&mut keys);
+        keys
+    }
+
+    /// The stroke color of the topmost stroke whose hit shape is at the given position. The tolerance widens the hit shape.
+    /// None if there is no stroke or it has no stroke color
+    pub fn stroke_color_at_pos(&self, pos: na::Vector2<f64>, tolerance: f64) -> Option<Color> {
+        self.stroke_key_at_pos(pos, tolerance)
+            .and_then(|key| self.strokes.get(key)?.stroke_color())
+    }
+
+    /// The boundary paths of all strokes intersecting the bounds, used to find the regions of the fill tool
+    pub fn boundary_paths_intersecting_bounds(&self, bounds: AABB) -> Vec<BoundaryPath> {
+        self.stroke_keys_intersect_bounds(bounds)
+            .iter()
+            .filter_map(|&key| self.strokes.get(key))
+            .flat_map(|stroke| stroke.boundary_paths())
+            .collect::<Vec<BoundaryPath>>()
     }
 
     pub fn clone_strokes_for_keys(&self, keys: &[StrokeKey]) -> Vec<StrokeStyle> {
@@ -489,12 +637,17 @@ impl StrokesState {
     pub fn import_state(&mut self, strokes_state: &Self) {
         self.clear();
         self.chrono_counter = strokes_state.chrono_counter;
+        self.zorder_counter = strokes_state.zorder_counter;
 
         self.strokes = strokes_state.strokes.clone();
         self.trash_components = strokes_state.trash_components.clone();
         self.selection_components = strokes_state.selection_components.clone();
         self.chrono_components = strokes_state.chrono_components.clone();
         self.render_components = strokes_state.render_components.clone();
+        self.zorder_components = strokes_state.zorder_components.clone();
+
+        self.rebuild_key_tree();
+        self.ensure_zorder_components();
     }
 
     pub fn update_geometry_for_stroke(&mut self, key: StrokeKey) {
@@ -516,6 +669,7 @@ impl StrokesState {
                     bitmapimage.update_geometry();
                 }
             }
+            self.update_key_tree_for_stroke(key);
 
             // set flag for rendering regeneration
             if let Some(render_comp) = self.render_components.get_mut(key) {
@@ -589,18 +743,12 @@ impl StrokesState {
         None
     }
 
-    /// Generates a Svg for all strokes as drawn onto the canvas without xml headers or svg roots. Does not include the selection.
-    pub fn gen_svgs_for_strokes(&self) -> Result<Vec<render::Svg>, anyhow::Error> {
-        let chrono_sorted = self.keys_sorted_chrono();
-
-        let svgs = chrono_sorted
+    /// Generates a Svg for all strokes intersecting the bounds without xml headers or svg roots. Includes the selection.
+    pub fn gen_svgs_for_bounds(&self, bounds: AABB) -> Result<Vec<render::Svg>, anyhow::Error> {
+        // Same ordering as when drawing onto the canvas, so highlighters are exported under the ink
+        let svgs = self
+            .keys_intersect_bounds(bounds)
             .iter()
-            .filter(|&&key| {
-                self.does_render(key).unwrap_or(false)
-                    && !(self.trashed(key).unwrap_or(false))
-                    && !(self.selected(key).unwrap_or(false))
-                    && (self.does_render(key).unwrap_or(false))
-            })
             .filter_map(|&key| {
                 let stroke = self.strokes.get(key)?;
 
@@ -608,7 +756,7 @@ impl StrokesState {
                     Ok(svgs) => Some(svgs),
                     Err(e) => {
                         log::error!(
-                            "stroke.gen_svgs() failed in gen_svg_all_strokes() with Err {}",
+                            "stroke.gen_svgs() failed in gen_svgs_for_bounds() with Err {}",
                             e
                         );
                         None
@@ -631,6 +779,7 @@ impl StrokesState {
         strokes.iter().for_each(|&key| {
             if let Some(stroke) = self.strokes.get_mut(key) {
                 stroke.translate(offset);
+                self.key_tree.update_with_key(key, stroke.bounds());
 
                 if let Some(render_comp) = self.render_components.get_mut(key) {
                     for image in render_comp.images.iter_mut() {
@@ -664,6 +813,62 @@ impl StrokesState {
         strokes.iter().for_each(|&key| {
             if let Some(stroke) = self.strokes.get_mut(key) {
                 stroke.rotate(angle, center);
+                self.update_key_tree_for_stroke(key);
+
+                self.regenerate_rendering_for_stroke(key, Arc::clone(&renderer), zoom);
+            }
+        });
+    }
+
+    /// Mirrors the strokes horizontally at the vertical axis through the center
+    pub fn flip_strokes_horizontal(
+        &mut self,
+        strokes: &[StrokeKey],
+        center: na::Point2<f64>,
+        renderer: Arc<RwLock<Renderer>>,
+        zoom: f64,
+    ) {
+        strokes.iter().for_each(|&key| {
+            if let Some(stroke) = self.strokes.get_mut(key) {
+                stroke.flip_horizontal(center);
+                self.update_key_tree_for_stroke(key);
+
+                self.regenerate_rendering_for_stroke(key, Arc::clone(&renderer), zoom);
+            }
+        });
+    }
+
+    /// Mirrors the strokes vertically at the horizontal axis through the center
+    pub fn flip_strokes_vertical(
+        &mut self,
+        strokes: &[StrokeKey],
+        center: na::Point2<f64>,
+        renderer: Arc<RwLock<Renderer>>,
+        zoom: f64,
+    ) {
+        strokes.iter().for_each(|&key| {
+            if let Some(stroke) = self.strokes.get_mut(key) {
+                stroke.flip_vertical(center);
+                self.update_key_tree_for_stroke(key);
+
+                self.regenerate_rendering_for_stroke(key, Arc::clone(&renderer), zoom);
+            }
+        });
+    }
+
+    /// Skews the strokes with the angles (rad) relative to the center
+    pub fn skew_strokes(
+        &mut self,
+        strokes: &[StrokeKey],
+        angles: na::Vector2<f64>,
+        center: na::Point2<f64>,
+        renderer: Arc<RwLock<Renderer>>,
+        zoom: f64,
+    ) {
+        strokes.iter().for_each(|&key| {
+            if let Some(stroke) = self.strokes.get_mut(key) {
+                stroke.skew(angles, center);
+                self.update_key_tree_for_stroke(key);
 
                 self.regenerate_rendering_for_stroke(key, Arc::clone(&renderer), zoom);
             }
@@ -695,6 +900,7 @@ impl StrokesState {
 
                 stroke.translate(offset);
                 stroke.scale(scale);
+                self.update_key_tree_for_stroke(key);
 
                 self.regenerate_rendering_for_stroke(key, Arc::clone(&renderer), zoom);
             }
@@ -703,16 +909,7 @@ impl StrokesState {
 
     /// Returns all strokes below the y_pos
     pub fn strokes_below_y_pos(&self, y_pos: f64) -> Vec<StrokeKey> {
-        self.strokes
-            .iter()
-            .filter_map(|(key, stroke)| {
-                if stroke.bounds().mins[1] > y_pos {
-                    Some(key)
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<StrokeKey>>()
+        self.key_tree.keys_below_y_pos(y_pos)
     }
 
     pub fn drag_strokes_proximity(
@@ -23,6 +23,9 @@ pub struct RenderComponent {
     pub images: Vec<render::Image>,
     #[serde(skip)]
     pub rendernode: Option<gsk::RenderNode>,
+    /// the render tick when the stroke was last in the viewport, used for evicting the least recently used images
+    #[serde(skip)]
+    pub last_used: u64,
 }
 
 impl Default for RenderComponent {
@@ -32,10 +35,57 @@ impl Default for RenderComponent {
             regenerate_flag: true,
             images: vec![],
             rendernode: None,
+            last_used: 0,
         }
     }
 }
 
+impl RenderComponent {
+    /// The default memory budget for the cached images of all strokes, in bytes
+    pub const RENDER_CACHE_BUDGET_DEFAULT: usize = 512 * 1024 * 1024;
+    /// The number of detail levels per doubling of the zoom
+    pub const LOD_LEVELS_PER_OCTAVE: f64 = 4.0;
+    /// Below this zoom the strokes are rendered with a reduced resolution
+    pub const LOD_LOW_ZOOM_THRESHOLD: f64 = 0.5;
+    /// The resolution of the images below the low zoom threshold, relative to the zoom
+    pub const LOD_LOW_ZOOM_RESOLUTION: f64 = 0.5;
+
+    /// The zoom at which the images are rendered for the given zoom of the canvas.
+    /// It is quantized into detail levels, so that zooming only within a level does not regenerate the images
+    pub fn lod_zoom(zoom: f64) -> f64 {
+        let level =
+            (zoom.log2() * Self::LOD_LEVELS_PER_OCTAVE).ceil() / Self::LOD_LEVELS_PER_OCTAVE;
+        let level_zoom = 2.0_f64.powf(level);
+
+        if zoom < Self::LOD_LOW_ZOOM_THRESHOLD {
+            level_zoom * Self::LOD_LOW_ZOOM_RESOLUTION
+        } else {
+            level_zoom
+        }
+    }
+
+    /// The size of the cached images in bytes
+    pub fn images_size(&self) -> usize {
+        self.images.iter().map(|image| image.data.len()).sum()
+    }
+
+    /// Whether the images were rendered at a different detail level than needed for the zoom
+    pub fn lod_outdated(&self, zoom: f64) -> bool {
+        let lod_zoom = Self::lod_zoom(zoom);
+
+        self.images
+            .iter()
+            .any(|image| (image.zoom - lod_zoom).abs() > f64::EPSILON)
+    }
+
+    /// Drops the cached images and rendernode. They are regenerated the next time the stroke is in the viewport
+    pub fn evict(&mut self) {
+        self.images.clear();
+        self.rendernode = None;
+        self.regenerate_flag = true;
+    }
+}
+
 impl StrokesState {
     /// Returns false if rendering is not supported
     pub fn can_render(&self, key: StrokeKey) -> bool {
@@ -106,13 +156,14 @@ impl StrokesState {
         if let (Some(stroke), Some(render_comp)) =
             (self.strokes.get(key), self.render_components.get_mut(key))
         {
-            match stroke.gen_image(zoom, renderer) {
+            match stroke.gen_image(RenderComponent::lod_zoom(zoom), renderer) {
                 Ok(Some(image)) => {
                     match render::image_to_rendernode(&image, zoom) {
                         Ok(rendernode) => {
                             render_comp.rendernode = Some(rendernode);
                             render_comp.regenerate_flag = false;
                             render_comp.images = vec![image];
+                            render_comp.last_used = self.render_tick;
                         }
                         Err(e) => log::error!("image_to_rendernode() failed in regenerate_rendering_for_stroke() with Err {}", e),
                     }
@@ -157,7 +208,7 @@ impl StrokesState {
 
             // Spawn a new thread for image rendering
             self.threadpool.spawn(move || {
-                match stroke.gen_image(zoom, renderer) {
+                match stroke.gen_image(RenderComponent::lod_zoom(zoom), renderer) {
                     Ok(Some(image)) => {
                         tasks_tx.send(StateTask::UpdateStrokeWithImages {
                             key,
@@ -195,7 +246,13 @@ impl StrokesState {
         renderer: Arc<RwLock<Renderer>>,
         zoom: f64,
     ) {
-        let keys = self.render_components.keys().collect::<Vec<StrokeKey>>();
+        let keys = if let Some(viewport) = viewport {
+            self.key_tree.keys_intersecting_bounds(viewport)
+        } else {
+            self.render_components.keys().collect::<Vec<StrokeKey>>()
+        };
+
+        self.render_tick += 1;
 
         keys.iter().for_each(|&key| {
             self.update_geometry_for_stroke(key);
@@ -209,11 +266,15 @@ impl StrokesState {
                         return;
                     }
                 }
-                if !force_regenerate && !render_comp.regenerate_flag {
+                render_comp.last_used = self.render_tick;
+                if !force_regenerate
+                    && !render_comp.regenerate_flag
+                    && !render_comp.lod_outdated(zoom)
+                {
                     return;
                 }
 
-                match stroke.gen_image(zoom, Arc::clone(&renderer)) {
+                match stroke.gen_image(RenderComponent::lod_zoom(zoom), Arc::clone(&renderer)) {
                     Ok(Some(image)) => {
                         let images = vec![image];
 
@@ -242,7 +303,11 @@ impl StrokesState {
                     key
                 );
             }
-        })
+        });
+
+        if let Some(viewport) = viewport {
+            self.evict_render_cache(viewport);
+        }
     }
 
     pub fn regenerate_rendering_current_view_threaded(
@@ -252,7 +317,13 @@ impl StrokesState {
         renderer: Arc<RwLock<Renderer>>,
         zoom: f64,
     ) {
-        let keys = self.render_components.keys().collect::<Vec<StrokeKey>>();
+        let keys = if let Some(viewport) = viewport {
+            self.key_tree.keys_intersecting_bounds(viewport)
+        } else {
+            self.render_components.keys().collect::<Vec<StrokeKey>>()
+        };
+
+        self.render_tick += 1;
 
         keys.iter().for_each(|&key| {
             if let (Some(stroke), Some(render_comp)) =
@@ -264,7 +335,11 @@ impl StrokesState {
                         return;
                     }
                 }
-                if !force_regenerate && !render_comp.regenerate_flag {
+                render_comp.last_used = self.render_tick;
+                if !force_regenerate
+                    && !render_comp.regenerate_flag
+                    && !render_comp.lod_outdated(zoom)
+                {
                     return;
                 }
 
@@ -276,7 +351,56 @@ impl StrokesState {
                     key
                 );
             }
-        })
+        });
+
+        if let Some(viewport) = viewport {
+            self.evict_render_cache(viewport);
+        }
+    }
+
+    /// The size of all cached images in bytes
+    pub fn render_cache_size(&self) -> usize {
+        self.render_components
+            .values()
+            .map(|render_comp| render_comp.images_size())
+            .sum()
+    }
+
+    /// Evicts the images of the least recently used strokes outside of the viewport, until the cache fits into the budget.
+    /// Strokes in the viewport are never evicted, so the budget might still be exceeded when they alone need more memory
+    pub fn evict_render_cache(&mut self, viewport: AABB) {
+        let mut cache_size = self.render_cache_size();
+        if cache_size <= self.render_cache_budget {
+            return;
+        }
+
+        let mut evictable = self
+            .render_components
+            .iter()
+            .filter_map(|(key, render_comp)| {
+                let in_viewport = self
+                    .strokes
+                    .get(key)
+                    .map_or(false, |stroke| viewport.intersects(&stroke.bounds()));
+
+                if in_viewport || render_comp.images.is_empty() {
+                    None
+                } else {
+                    Some((key, render_comp.last_used))
+                }
+            })
+            .collect::<Vec<(StrokeKey, u64)>>();
+        evictable.sort_unstable_by_key(|&(_key, last_used)| last_used);
+
+        for (key, _last_used) in evictable {
+            if cache_size <= self.render_cache_budget {
+                break;
+            }
+            if let Some(render_comp) = self.render_components.get_mut(key) {
+                cache_size = cache_size.saturating_sub(render_comp.images_size());
+                render_comp.evict();
+            }
+        }
     }
 
     /// Append the last elements to the render_comp of the stroke. The rendering for strokes that don't support generating rendering for only the last elements are regenerated completely
@@ -311,7 +435,7 @@ impl StrokesState {
                         {
                             let bounds = last_elems_svg.bounds;
                             match renderer.read().unwrap().gen_image(
-                                zoom,
+                                RenderComponent::lod_zoom(zoom),
                                 &[last_elems_svg],
                                 bounds,
                             ) {
@@ -339,7 +463,8 @@ impl StrokesState {
                         }
                     }
                 }
-                StrokeStyle::MarkerStroke(markerstroke) => {
+                // Highlighters need to be regenerated completely, else the appended segments would darken where they overlap
+                StrokeStyle::MarkerStroke(markerstroke) if !markerstroke.highlighter => {
                     let elems_len = markerstroke.elements.len();
 
                     let elements = if elems_len >= 4 {
@@ -363,7 +488,7 @@ impl StrokesState {
                         ) {
                             let bounds = last_elems_svg.bounds;
                             match renderer.read().unwrap().gen_image(
-                                zoom,
+                                RenderComponent::lod_zoom(zoom),
                                 &[last_elems_svg],
                                 bounds,
                             ) {
@@ -392,10 +517,11 @@ impl StrokesState {
                     }
                 }
                 // regenerate everything for strokes that don't support generating svgs for the last added elements
-                StrokeStyle::ShapeStroke(_)
+                StrokeStyle::MarkerStroke(_)
+                | StrokeStyle::ShapeStroke(_)
                 | StrokeStyle::VectorImage(_)
                 | StrokeStyle::BitmapImage(_) => {
-                    match stroke.gen_image(zoom, renderer) {
+                    match stroke.gen_image(RenderComponent::lod_zoom(zoom), renderer) {
                         Ok(Some(image)) => {
                             match render::image_to_rendernode(&image, zoom) {
                                 Ok(rendernode) => {
@@ -443,7 +569,8 @@ impl StrokesState {
 
             self.threadpool.spawn_fifo(move || {
                 match stroke {
-                    StrokeStyle::MarkerStroke(markerstroke) => {
+                    // Highlighters need to be regenerated completely, else the appended segments would darken where they overlap
+                    StrokeStyle::MarkerStroke(markerstroke) if !markerstroke.highlighter => {
                         let elems_len = markerstroke.elements.len();
 
                         let elements = if elems_len >= 4 {
@@ -464,7 +591,7 @@ impl StrokesState {
                             {
                             let bounds = last_elems_svg.bounds;
                                 match renderer.read().unwrap().gen_image(
-                                    zoom,
+                                    RenderComponent::lod_zoom(zoom),
                                     &[last_elems_svg],
                                     bounds,
                                 ) {
@@ -507,7 +634,7 @@ impl StrokesState {
                             {
                                 let bounds = last_elems_svg.bounds;
                                 match renderer.read().unwrap().gen_image(
-                                    zoom,
+                                    RenderComponent::lod_zoom(zoom),
                                     &[last_elems_svg],
                                     bounds,
                                 ) {
@@ -530,10 +657,11 @@ impl StrokesState {
                         }
                     }
                     // regenerate everything for strokes that don't support generating svgs for the last added elements
-                    StrokeStyle::ShapeStroke(_)
+                    StrokeStyle::MarkerStroke(_)
+                    | StrokeStyle::ShapeStroke(_)
                     | StrokeStyle::VectorImage(_)
                     | StrokeStyle::BitmapImage(_) => {
-                        match stroke.gen_image(zoom, renderer) {
+                        match stroke.gen_image(RenderComponent::lod_zoom(zoom), renderer) {
                             Ok(Some(image)) => {
                                 tasks_tx.send(StateTask::UpdateStrokeWithImages {
                                     key,
@@ -574,6 +702,7 @@ impl StrokesState {
                     render_comp.rendernode = Some(rendernode);
                     render_comp.regenerate_flag = false;
                     render_comp.images = images;
+                    render_comp.last_used = self.render_tick;
                 }
                 Ok(None) => {}
                 Err(e) => log::error!(
@@ -601,6 +730,7 @@ impl StrokesState {
                     render_comp.rendernode = rendernode;
                     render_comp.regenerate_flag = false;
                     render_comp.images.append(&mut images);
+                    render_comp.last_used = self.render_tick;
                 }
                 Err(e) => log::error!(
                     "append_images_to_rendernode() failed in append_images_to_rendering() with Err {}",
@@ -630,24 +760,28 @@ impl StrokesState {
 
     /// Draws the strokes without the selection
     pub fn draw_strokes(&self, snapshot: &Snapshot, viewport: Option<AABB>) {
-        self.stroke_keys_in_order_rendered()
-            .iter()
-            .for_each(|&key| {
-                if let (Some(stroke), Some(render_comp)) =
-                    (self.strokes.get(key), self.render_components.get(key))
-                {
-                    // skip if stroke is not in viewport
-                    if let Some(viewport) = viewport {
-                        if !viewport.intersects(&stroke.bounds()) {
-                            return;
-                        }
-                    }
+        let keys = if let Some(viewport) = viewport {
+            self.stroke_keys_intersect_bounds(viewport)
+        } else {
+            self.stroke_keys_in_order_rendered()
+        };
 
-                    if let Some(rendernode) = render_comp.rendernode.as_ref() {
-                        snapshot.append_node(rendernode);
+        keys.iter().for_each(|&key| {
+            if let (Some(stroke), Some(render_comp)) =
+                (self.strokes.get(key), self.render_components.get(key))
+            {
+                // skip if stroke is not in viewport
+                if let Some(viewport) = viewport {
+                    if !viewport.intersects(&stroke.bounds()) {
+                        return;
                     }
                 }
-            });
+
+                if let Some(rendernode) = render_comp.rendernode.as_ref() {
+                    snapshot.append_node(rendernode);
+                }
+            }
+        });
     }
 
     /// Draws the selection
@@ -730,6 +864,14 @@ impl StrokesState {
                     );
                 }
             }
+            for hitbox in stroke.hitshape().hitboxes() {
+                canvas::debug::draw_bounds(
+                    hitbox,
+                    canvas::debug::COLOR_STROKE_HITBOX,
+                    zoom,
+                    snapshot,
+                );
+            }
             match stroke {
                 StrokeStyle::MarkerStroke(markerstroke) => {
                     for element in markerstroke.elements.iter() {
@@ -740,14 +882,6 @@ impl StrokesState {
                             snapshot,
                         )
                     }
-                    for &hitbox_elem in markerstroke.hitbox.iter() {
-                        canvas::debug::draw_bounds(
-                            hitbox_elem,
-                            canvas::debug::COLOR_STROKE_HITBOX,
-                            zoom,
-                            snapshot,
-                        );
-                    }
                     canvas::debug::draw_bounds(
                         markerstroke.bounds,
                         canvas::debug::COLOR_STROKE_BOUNDS,
@@ -764,14 +898,6 @@ impl StrokesState {
                             snapshot,
                         )
                     }
-                    for &hitbox_elem in brushstroke.hitboxes.iter() {
-                        canvas::debug::draw_bounds(
-                            hitbox_elem,
-                            canvas::debug::COLOR_STROKE_HITBOX,
-                            zoom,
-                            snapshot,
-                        );
-                    }
                     canvas::debug::draw_bounds(
                         brushstroke.bounds,
                         canvas::debug::COLOR_STROKE_BOUNDS,
@@ -1,16 +1,83 @@
-use super::{StrokeKey, StrokeStyle, StrokesState};
+use std::sync::{Arc, RwLock};
+
+use super::{StrokeKey, StrokesState};
 use crate::compose::geometry;
 use crate::drawbehaviour::DrawBehaviour;
 use crate::pens::selector::{self, Selector};
+use crate::render::Renderer;
+use crate::strokes::strokestyle::StyleChange;
 use crate::{compose, render};
 
 use geo::line_string;
 use geo::prelude::*;
 use gtk4::{gio, glib, prelude::*};
 use p2d::bounding_volume::{BoundingVolume, AABB};
+use p2d::query::PointQuery;
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 
+/// The edge or center of the reference bounds to which strokes are aligned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
+    Left,
+    HorizontalCenter,
+    Right,
+    Top,
+    VerticalCenter,
+    Bottom,
+}
+
+impl Alignment {
+    pub fn from_nick(nick: &str) -> Option<Self> {
+        match nick {
+            "left" => Some(Self::Left),
+            "horizontal-center" => Some(Self::HorizontalCenter),
+            "right" => Some(Self::Right),
+            "top" => Some(Self::Top),
+            "vertical-center" => Some(Self::VerticalCenter),
+            "bottom" => Some(Self::Bottom),
+            _ => None,
+        }
+    }
+
+    /// The offset which aligns the bounds to the reference
+    pub fn offset(&self, bounds: AABB, reference: AABB) -> na::Vector2<f64> {
+        match self {
+            Self::Left => na::vector![reference.mins[0] - bounds.mins[0], 0.0],
+            Self::HorizontalCenter => na::vector![reference.center()[0] - bounds.center()[0], 0.0],
+            Self::Right => na::vector![reference.maxs[0] - bounds.maxs[0], 0.0],
+            Self::Top => na::vector![0.0, reference.mins[1] - bounds.mins[1]],
+            Self::VerticalCenter => na::vector![0.0, reference.center()[1] - bounds.center()[1]],
+            Self::Bottom => na::vector![0.0, reference.maxs[1] - bounds.maxs[1]],
+        }
+    }
+}
+
+/// The direction in which strokes are distributed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Distribution {
+    Horizontal,
+    Vertical,
+}
+
+impl Distribution {
+    pub fn from_nick(nick: &str) -> Option<Self> {
+        match nick {
+            "horizontal" => Some(Self::Horizontal),
+            "vertical" => Some(Self::Vertical),
+            _ => None,
+        }
+    }
+
+    /// The index of the axis along which is distributed
+    fn axis(&self) -> usize {
+        match self {
+            Self::Horizontal => 0,
+            Self::Vertical => 1,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 #[serde(default, rename = "selection_component")]
 pub struct SelectionComponent {
@@ -51,9 +118,12 @@ impl StrokesState {
         }
     }
 
-    /// Sets if the stroke is currently selected
+    /// Sets if the stroke is currently selected. The rotation of the selection starts over when the selected strokes change
     pub fn set_selected(&mut self, key: StrokeKey, selected: bool) {
         if let Some(selection_comp) = self.selection_components.get_mut(key) {
+            if selection_comp.selected != selected {
+                self.selection_rotation = 0.0;
+            }
             selection_comp.selected = selected;
 
             if let Some(chrono_comp) = self.chrono_components.get_mut(key) {
@@ -76,9 +146,9 @@ impl StrokesState {
 
     /// Returns the selection keys in the order that they should be rendered. Does not return the stroke keys!
     pub fn selection_keys_in_order_rendered(&self) -> Vec<StrokeKey> {
-        let keys_sorted_chrono = self.keys_sorted_chrono();
+        let keys_sorted_zorder = self.keys_sorted_zorder();
 
-        keys_sorted_chrono
+        keys_sorted_zorder
             .iter()
             .filter_map(|&key| {
                 if self.does_render(key).unwrap_or(false)
@@ -93,6 +163,25 @@ impl StrokesState {
             .collect::<Vec<StrokeKey>>()
     }
 
+    /// The angle (in radians) the selection was rotated by since its strokes were selected
+    pub fn selection_rotation(&self) -> f64 {
+        self.selection_rotation
+    }
+
+    /// Rotates the selection around the center and adds the angle to the rotation of the selection
+    pub fn rotate_selection(
+        &mut self,
+        angle: f64,
+        center: na::Point2<f64>,
+        renderer: Arc<RwLock<Renderer>>,
+        zoom: f64,
+    ) {
+        let selection_keys = self.selection_keys_in_order_rendered();
+        self.rotate_strokes(&selection_keys, angle, center, renderer, zoom);
+
+        self.selection_rotation += angle;
+    }
+
     pub fn selection_len(&self) -> usize {
         self.selection_keys_in_order_rendered().len()
     }
@@ -123,7 +212,81 @@ impl StrokesState {
         self.translate_strokes(&new_selected, offset, zoom);
     }
 
-    /// Returns true if selection has changed
+    /// Aligns each selected stroke to the reference bounds. The selection bounds are used as reference if it is None
+    pub fn align_selection(&mut self, alignment: Alignment, reference: Option<AABB>, zoom: f64) {
+        let reference = match reference.or_else(|| self.gen_selection_bounds()) {
+            Some(reference) => reference,
+            None => return,
+        };
+
+        for key in self.selection_keys_in_order_rendered() {
+            let offset = match self.strokes.get(key) {
+                Some(stroke) => alignment.offset(stroke.bounds(), reference),
+                None => continue,
+            };
+
+            self.translate_strokes(&[key], offset, zoom);
+        }
+    }
+
+    /// Distributes the selected strokes so that the gaps between them are equal.
+    /// The first and the last stroke in the direction stay in place
+    pub fn distribute_selection(&mut self, distribution: Distribution, zoom: f64) {
+        let axis = distribution.axis();
+
+        let mut strokes_bounds = self
+            .selection_keys_in_order_rendered()
+            .into_iter()
+            .filter_map(|key| Some((key, self.strokes.get(key)?.bounds())))
+            .collect::<Vec<(StrokeKey, AABB)>>();
+        if strokes_bounds.len() < 3 {
+            return;
+        }
+
+        strokes_bounds.sort_by(|(_, first), (_, second)| {
+            first.center()[axis]
+                .partial_cmp(&second.center()[axis])
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        let start = strokes_bounds
+            .iter()
+            .map(|(_, bounds)| bounds.mins[axis])
+            .fold(f64::INFINITY, f64::min);
+        let end = strokes_bounds
+            .iter()
+            .map(|(_, bounds)| bounds.maxs[axis])
+            .fold(f64::NEG_INFINITY, f64::max);
+        let extents_sum = strokes_bounds
+            .iter()
+            .map(|(_, bounds)| bounds.extents()[axis])
+            .sum::<f64>();
+        let gap = (end - start - extents_sum) / (strokes_bounds.len() - 1) as f64;
+
+        let mut pos = start;
+        for (key, bounds) in strokes_bounds {
+            let mut offset = na::Vector2::zeros();
+            offset[axis] = pos - bounds.mins[axis];
+
+            self.translate_strokes(&[key], offset, zoom);
+            pos += bounds.extents()[axis] + gap;
+        }
+    }
+
+    /// Applies the style change to all selected strokes and updates their geometry. Their rendering needs to be regenerated afterwards
+    pub fn restyle_selection(&mut self, change: StyleChange) {
+        let selection_keys = self.selection_keys_in_order_rendered();
+
+        selection_keys.iter().for_each(|&key| {
+            if let Some(stroke) = self.strokes.get_mut(key) {
+                stroke.restyle(change);
+            }
+        });
+
+        self.update_geometry_selection_strokes();
+    }
+
+    /// Selects the strokes inside the selector path, depending on its containment mode. Does not deselect any strokes
     pub fn update_selection_for_selector(&mut self, selector: &Selector, viewport: Option<AABB>) {
         let selector_polygon = match selector.style {
             selector::SelectorStyle::Polygon => {
@@ -155,120 +318,84 @@ impl StrokesState {
             }
         };
 
-        self.keys_sorted_chrono().iter().for_each(|&key| {
-            let stroke = if let Some(stroke) = self.strokes.get(key) {
-                stroke
-            } else {
-                return;
-            };
-            // skip if stroke is trashed
-            if let Some(trash_comp) = self.trash_components.get(key) {
-                if trash_comp.trashed {
-                    return;
-                }
-            }
-            // skip if stroke is not in viewport
-            if let Some(viewport) = viewport {
-                if !viewport.intersects(&stroke.bounds()) {
-                    return;
+        let selector_polygon_contains =
+            |bounds: AABB| selector_polygon.contains(&geometry::p2d_aabb_to_geo_polygon(bounds));
+        let selector_polygon_intersects =
+            |bounds: AABB| selector_polygon.intersects(&geometry::p2d_aabb_to_geo_polygon(bounds));
+
+        let selector_bounds = match selector.path.iter().map(|inputdata| inputdata.pos()).fold(
+            None,
+            |acc: Option<AABB>, pos| {
+                let pos_bounds = AABB::new(na::Point2::from(pos), na::Point2::from(pos));
+                Some(acc.map_or(pos_bounds, |acc| acc.merged(&pos_bounds)))
+            },
+        ) {
+            Some(selector_bounds) => selector_bounds,
+            None => return,
+        };
+
+        let selected_keys = self
+            .key_tree
+            .keys_intersecting_bounds(selector_bounds)
+            .into_iter()
+            .filter(|&key| {
+                let stroke = match self.strokes.get(key) {
+                    Some(stroke) => stroke,
+                    None => return false,
+                };
+                // skip if stroke is trashed
+                if self.trashed(key).unwrap_or(false) {
+                    return false;
                 }
-            }
-            if let Some(selection_comp) = self.selection_components.get_mut(key) {
-                // default to not selected, check for if selected
-                selection_comp.selected = false;
-
-                match stroke {
-                    StrokeStyle::MarkerStroke(markerstroke) => {
-                        if selector_polygon
-                            .contains(&geometry::p2d_aabb_to_geo_polygon(markerstroke.bounds))
-                        {
-                            selection_comp.selected = true;
-                        } else if selector_polygon
-                            .intersects(&geometry::p2d_aabb_to_geo_polygon(markerstroke.bounds))
-                        {
-                            for &hitbox_elem in markerstroke.hitbox.iter() {
-                                if !selector_polygon
-                                    .contains(&geometry::p2d_aabb_to_geo_polygon(hitbox_elem))
-                                {
-                                    return;
-                                }
-                            }
-                            selection_comp.selected = true;
-
-                            if let Some(chrono_comp) = self.chrono_components.get_mut(key) {
-                                self.chrono_counter += 1;
-                                chrono_comp.t = self.chrono_counter;
-                            }
-                        }
-                    }
-                    StrokeStyle::BrushStroke(brushstroke) => {
-                        if selector_polygon
-                            .contains(&geometry::p2d_aabb_to_geo_polygon(brushstroke.bounds))
-                        {
-                            selection_comp.selected = true;
-                        } else if selector_polygon
-                            .intersects(&geometry::p2d_aabb_to_geo_polygon(brushstroke.bounds))
-                        {
-                            for &hitbox_elem in brushstroke.hitboxes.iter() {
-                                if !selector_polygon
-                                    .contains(&geometry::p2d_aabb_to_geo_polygon(hitbox_elem))
-                                {
-                                    return;
-                                }
-                            }
-                            selection_comp.selected = true;
-
-                            if let Some(chrono_comp) = self.chrono_components.get_mut(key) {
-                                self.chrono_counter += 1;
-                                chrono_comp.t = self.chrono_counter;
-                            }
-                        }
+                let bounds = stroke.bounds();
+                // skip if stroke is not in viewport
+                if let Some(viewport) = viewport {
+                    if !viewport.intersects(&bounds) {
+                        return false;
                     }
-                    StrokeStyle::ShapeStroke(shapestroke) => {
-                        if selector_polygon
-                            .contains(&geometry::p2d_aabb_to_geo_polygon(shapestroke.bounds))
-                        {
-                            selection_comp.selected = true;
-
-                            if let Some(chrono_comp) = self.chrono_components.get_mut(key) {
-                                self.chrono_counter += 1;
-                                chrono_comp.t = self.chrono_counter;
-                            }
-                        }
-                    }
-                    StrokeStyle::VectorImage(vectorimage) => {
-                        if selector_polygon
-                            .contains(&geometry::p2d_aabb_to_geo_polygon(vectorimage.bounds))
-                        {
-                            selection_comp.selected = true;
-
-                            if let Some(chrono_comp) = self.chrono_components.get_mut(key) {
-                                self.chrono_counter += 1;
-                                chrono_comp.t = self.chrono_counter;
-                            }
-                        }
+                }
+
+                match selector.containment {
+                    selector::SelectorContainment::Touching => {
+                        selector_polygon_intersects(bounds)
+                            && stroke.hitshape().intersects_polygon(&selector_polygon)
                     }
-                    StrokeStyle::BitmapImage(bitmapimage) => {
-                        if selector_polygon
-                            .contains(&geometry::p2d_aabb_to_geo_polygon(bitmapimage.bounds))
-                        {
-                            selection_comp.selected = true;
-
-                            if let Some(chrono_comp) = self.chrono_components.get_mut(key) {
-                                self.chrono_counter += 1;
-                                chrono_comp.t = self.chrono_counter;
-                            }
-                        }
+                    selector::SelectorContainment::Contained => {
+                        selector_polygon_contains(bounds)
+                            || (selector_polygon_intersects(bounds)
+                                && stroke.hitshape().contained_in_polygon(&selector_polygon))
                     }
                 }
-            }
-        });
+            })
+            .collect::<Vec<StrokeKey>>();
+
+        self.set_selected_keys(&selected_keys, true);
+    }
+
+    /// The topmost stroke at the position, including the selected strokes. The tolerance widens the hit geometry
+    pub fn stroke_key_at_pos(&self, pos: na::Vector2<f64>, tolerance: f64) -> Option<StrokeKey> {
+        let pos = na::Point2::from(pos);
+        let pos_bounds = AABB::new(pos, pos).loosened(tolerance);
+
+        self.stroke_keys_intersect_bounds(pos_bounds)
+            .into_iter()
+            .chain(self.selection_keys_intersect_bounds(pos_bounds))
+            .rev()
+            .find(|&key| {
+                self.strokes.get(key).map_or(false, |stroke| {
+                    stroke
+                        .bounds()
+                        .loosened(tolerance)
+                        .contains_local_point(&pos)
+                        && stroke.hitshape().contains_point(pos.coords, tolerance)
+                })
+            })
     }
 
     /// the svgs of the current selection, without xml header or svg root
     pub fn gen_svgs_selection(&self) -> Result<Vec<render::Svg>, anyhow::Error> {
         Ok(self
-            .keys_sorted_chrono()
+            .keys_sorted_zorder()
             .iter()
             .filter(|&&key| {
                 self.does_render(key).unwrap_or(false)
@@ -113,57 +113,26 @@ impl StrokesState {
                 ),
             );
 
-            self.strokes.iter_mut().for_each(|(key, stroke)| {
+            let keys = self.key_tree.keys_intersecting_bounds(eraser_bounds);
+
+            keys.into_iter().for_each(|key| {
+                let stroke = match self.strokes.get(key) {
+                    Some(stroke) => stroke,
+                    None => return,
+                };
                 if let Some(viewport) = viewport {
                     if !viewport.intersects(&stroke.bounds()) {
                         return;
                     }
                 }
                 match stroke {
-                    StrokeStyle::MarkerStroke(markerstroke) => {
-                        // First check markerstroke bounds, then conditionally check hitbox
-                        if eraser_bounds.intersects(&markerstroke.bounds) {
-                            for hitbox_elem in markerstroke.hitbox.iter() {
-                                if eraser_bounds.intersects(hitbox_elem) {
-                                    if let Some(trash_comp) = self.trash_components.get_mut(key) {
-                                        trash_comp.trashed = true;
-
-                                        if let Some(chrono_comp) =
-                                            self.chrono_components.get_mut(key)
-                                        {
-                                            self.chrono_counter += 1;
-                                            chrono_comp.t = self.chrono_counter;
-                                        }
-                                    }
-
-                                    return;
-                                }
-                            }
-                        }
-                    }
-                    StrokeStyle::BrushStroke(brushstroke) => {
-                        // First check markerstroke bounds, then conditionally check hitbox
-                        if eraser_bounds.intersects(&brushstroke.bounds) {
-                            for hitbox_elem in brushstroke.hitboxes.iter() {
-                                if eraser_bounds.intersects(hitbox_elem) {
-                                    if let Some(trash_comp) = self.trash_components.get_mut(key) {
-                                        trash_comp.trashed = true;
-
-                                        if let Some(chrono_comp) =
-                                            self.chrono_components.get_mut(key)
-                                        {
-                                            self.chrono_counter += 1;
-                                            chrono_comp.t = self.chrono_counter;
-                                        }
-                                    }
-
-                                    return;
-                                }
-                            }
-                        }
-                    }
-                    StrokeStyle::ShapeStroke(shapestroke) => {
-                        if eraser_bounds.intersects(&shapestroke.bounds) {
+                    StrokeStyle::MarkerStroke(_)
+                    | StrokeStyle::BrushStroke(_)
+                    | StrokeStyle::ShapeStroke(_) => {
+                        // First check the bounds, then conditionally the exact geometry
+                        if eraser_bounds.intersects(&stroke.bounds())
+                            && stroke.hitshape().intersects_aabb(eraser_bounds)
+                        {
                             if let Some(trash_comp) = self.trash_components.get_mut(key) {
                                 trash_comp.trashed = true;
 
@@ -174,11 +143,8 @@ impl StrokesState {
                             }
                         }
                     }
-                    StrokeStyle::VectorImage(_vectorimage) => {
-                        // Ignore VectorImage when trashing with the Eraser
-                    }
-                    StrokeStyle::BitmapImage(_bitmapimage) => {
-                        // Ignore BitmapImage when trashing with the Eraser
+                    StrokeStyle::VectorImage(_) | StrokeStyle::BitmapImage(_) => {
+                        // Ignore images when trashing with the Eraser
                     }
                 }
             });
@@ -0,0 +1,228 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+use rayon::iter::{ParallelBridge, ParallelIterator};
+use rayon::slice::ParallelSliceMut;
+use serde::{Deserialize, Serialize};
+
+use super::{StrokeKey, StrokesState};
+
+/// The z value is fractional, so that strokes can be inserted in between others without renumbering them
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, PartialOrd)]
+#[serde(default, rename = "zorder_component")]
+pub struct ZOrderComponent {
+    #[serde(rename = "z")]
+    pub z: f64,
+}
+
+impl Default for ZOrderComponent {
+    fn default() -> Self {
+        Self { z: 0.0 }
+    }
+}
+
+impl ZOrderComponent {
+    pub fn new(z: f64) -> Self {
+        Self { z }
+    }
+}
+
+/// A change of the z-order of strokes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZOrderChange {
+    BringToFront,
+    BringForward,
+    SendBackward,
+    SendToBack,
+}
+
+impl ZOrderChange {
+    pub fn from_nick(nick: &str) -> Option<Self> {
+        match nick {
+            "bring-to-front" => Some(Self::BringToFront),
+            "bring-forward" => Some(Self::BringForward),
+            "send-backward" => Some(Self::SendBackward),
+            "send-to-back" => Some(Self::SendToBack),
+            _ => None,
+        }
+    }
+}
+
+/// Systems that are related to the z-order, the order in which the strokes are stacked on top of each other.
+/// It is independent of the chronological order, which changes with every modification of a stroke.
+impl StrokesState {
+    /// Returns the keys in z-order, as in first: gets drawn first, last: gets drawn last
+    pub fn keys_sorted_zorder(&self) -> Vec<StrokeKey> {
+        let zorder_components = &self.zorder_components;
+
+        let mut sorted = zorder_components
+            .iter()
+            .par_bridge()
+            .map(|(key, zorder_comp)| (key, zorder_comp.z))
+            .collect::<Vec<(StrokeKey, f64)>>();
+        sorted.par_sort_unstable_by(|first, second| {
+            first.1.partial_cmp(&second.1).unwrap_or(Ordering::Equal)
+        });
+
+        let (keys, _zs): (Vec<StrokeKey>, Vec<f64>) = sorted.into_iter().unzip();
+        keys
+    }
+
+    /// Inserts the z-order component for a new stroke on top of all others.
+    /// Strokes which render under the ink (highlighters and fills) are then moved below the ink they overlap
+    pub(super) fn insert_zorder_component(&mut self, key: StrokeKey) {
+        self.zorder_counter += 1.0;
+        self.zorder_components
+            .insert(key, ZOrderComponent::new(self.zorder_counter));
+
+        self.place_under_overlapping_ink(key);
+    }
+
+    /// Moves a stroke which renders under the ink below the lowest ink stroke it overlaps, but above the other overlapping strokes that are already below it.
+    /// It gets a z in between, so only the overlapping strokes are looked up in the key tree and no other stroke is renumbered
+    pub fn place_under_overlapping_ink(&mut self, key: StrokeKey) {
+        if !self
+            .strokes
+            .get(key)
+            .map_or(false, |stroke| stroke.renders_under_ink())
+        {
+            return;
+        }
+        let z = match self.zorder_components.get(key) {
+            Some(zorder_comp) => zorder_comp.z,
+            None => return,
+        };
+
+        let overlapping_zs = self
+            .overlapping_keys(key)
+            .into_iter()
+            .filter_map(|other| {
+                let renders_under_ink = self.strokes.get(other)?.renders_under_ink();
+                Some((self.zorder_components.get(other)?.z, renders_under_ink))
+            })
+            .collect::<Vec<(f64, bool)>>();
+
+        let lowest_ink_z = match overlapping_zs
+            .iter()
+            .filter(|(_, renders_under_ink)| !renders_under_ink)
+            .map(|&(other_z, _)| other_z)
+            .reduce(f64::min)
+        {
+            Some(lowest_ink_z) if lowest_ink_z < z => lowest_ink_z,
+            _ => return,
+        };
+        let below_z = overlapping_zs
+            .iter()
+            .map(|&(other_z, _)| other_z)
+            .filter(|&other_z| other_z < lowest_ink_z)
+            .fold(lowest_ink_z - 1.0, f64::max);
+
+        let new_z = (below_z + lowest_ink_z) * 0.5;
+        if new_z <= below_z || new_z >= lowest_ink_z {
+            // The gap is exhausted, so the z-order is renumbered to integral values before trying again
+            let order = self.keys_sorted_zorder();
+            self.renumber_zorder(order);
+            self.place_under_overlapping_ink(key);
+            return;
+        }
+
+        if let Some(zorder_comp) = self.zorder_components.get_mut(key) {
+            zorder_comp.z = new_z;
+        }
+    }
+
+    /// Gives the strokes integral z values in the given order
+    fn renumber_zorder(&mut self, order: Vec<StrokeKey>) {
+        self.zorder_counter = 0.0;
+        for key in order {
+            if let Some(zorder_comp) = self.zorder_components.get_mut(key) {
+                self.zorder_counter += 1.0;
+                zorder_comp.z = self.zorder_counter;
+            }
+        }
+    }
+
+    /// Strokes from files which were saved without a z-order get one from their chronological order. Needs the key tree to be built
+    pub fn ensure_zorder_components(&mut self) {
+        for key in self.keys_sorted_chrono() {
+            if !self.zorder_components.contains_key(key) {
+                self.insert_zorder_component(key);
+            }
+        }
+    }
+
+    /// The keys of the strokes which overlap the given stroke, which are the ones it can be moved forward or backward past
+    fn overlapping_keys(&self, key: StrokeKey) -> HashSet<StrokeKey> {
+        self.strokes
+            .get(key)
+            .map(|stroke| {
+                self.key_tree
+                    .keys_intersecting_bounds(stroke.bounds())
+                    .into_iter()
+                    .filter(|&other| other != key)
+                    .collect::<HashSet<StrokeKey>>()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Changes the z-order of the given strokes, keeping their order relative to each other.
+    /// Moving forward or backward passes the next stroke which overlaps the moved stroke, skipping trashed strokes
+    pub fn change_zorder(&mut self, keys: &[StrokeKey], change: ZOrderChange) {
+        let mut order = self.keys_sorted_zorder();
+        let moved = |key: &StrokeKey| keys.contains(key);
+
+        match change {
+            ZOrderChange::BringToFront => {
+                let (mut moved_keys, others): (Vec<StrokeKey>, Vec<StrokeKey>) =
+                    order.into_iter().partition(moved);
+                order = others;
+                order.append(&mut moved_keys);
+            }
+            ZOrderChange::SendToBack => {
+                let (mut moved_keys, mut others): (Vec<StrokeKey>, Vec<StrokeKey>) =
+                    order.into_iter().partition(moved);
+                moved_keys.append(&mut others);
+                order = moved_keys;
+            }
+            ZOrderChange::BringForward => {
+                // Starting from the top, so that moved strokes don't pass each other
+                for i in (0..order.len()).rev() {
+                    if !moved(&order[i]) {
+                        continue;
+                    }
+                    let overlapping = self.overlapping_keys(order[i]);
+                    let above = (i + 1..order.len()).find(|&j| {
+                        !moved(&order[j])
+                            && overlapping.contains(&order[j])
+                            && !self.trashed(order[j]).unwrap_or(false)
+                    });
+
+                    if let Some(above) = above {
+                        let key = order.remove(i);
+                        order.insert(above, key);
+                    }
+                }
+            }
+            ZOrderChange::SendBackward => {
+                for i in 0..order.len() {
+                    if !moved(&order[i]) {
+                        continue;
+                    }
+                    let overlapping = self.overlapping_keys(order[i]);
+                    let below = (0..i).rev().find(|&j| {
+                        !moved(&order[j])
+                            && overlapping.contains(&order[j])
+                            && !self.trashed(order[j]).unwrap_or(false)
+                    });
+
+                    if let Some(below) = below {
+                        let key = order.remove(i);
+                        order.insert(below, key);
+                    }
+                }
+            }
+        }
+
+        self.renumber_zorder(order);
+    }
+}
@@ -66,6 +66,34 @@ impl RnoteAppWindow {
             .bind("touch-drawing", &self.canvas(), "touch-drawing")
             .build();
 
+        // touch rotation
+        self.app_settings()
+            .bind("touch-rotation", &self.canvas(), "touch-rotation")
+            .build();
+
+        // palm rejection
+        self.app_settings()
+            .bind("palm-rejection", &self.canvas(), "palm-rejection")
+            .build();
+
+        // palm rejection timeout
+        self.app_settings()
+            .bind(
+                "palm-rejection-timeout",
+                &self.canvas(),
+                "palm-rejection-timeout",
+            )
+            .build();
+
+        // palm rejection contact threshold
+        self.app_settings()
+            .bind(
+                "palm-rejection-contact-threshold",
+                &self.canvas(),
+                "palm-rejection-contact-threshold",
+            )
+            .build();
+
         // endless sheet
         self.app_settings()
             .bind("endless-sheet", &self.canvas(), "endless-sheet")
@@ -129,6 +157,15 @@ impl RnoteAppWindow {
             )
             .build();
 
+        // Tools page
+        self.app_settings()
+            .bind(
+                "toolspage-selected-fill",
+                &self.penssidebar().tools_page().fill_colorpicker(),
+                "selected",
+            )
+            .build();
+
         // lock resize aspectratio
         self.app_settings()
             .bind("resize-lock-aspectratio", &self.canvas().selection_modifier(), "resize-lock-aspectratio")
@@ -200,6 +237,18 @@ impl RnoteAppWindow {
                 .shaper_page()
                 .fill_colorpicker()
                 .load_colors(&fill_colors);
+
+            // Tools page fills
+
+            let fill_colors = self.app_settings().get::<(u32, u32)>("toolspage-fills");
+            let fill_colors = [fill_colors.0, fill_colors.1]
+                .into_iter()
+                .map(|color| Color::from(color))
+                .collect::<Vec<Color>>();
+            self.penssidebar()
+                .tools_page()
+                .fill_colorpicker()
+                .load_colors(&fill_colors);
         }
 
         {
@@ -304,6 +353,19 @@ impl RnoteAppWindow {
             self.app_settings()
                 .set_value("shaperpage-fills", &fills.to_variant())?;
 
+            // Tools page fills
+            let fills = self
+                .penssidebar()
+                .tools_page()
+                .fill_colorpicker()
+                .fetch_all_colors()
+                .into_iter()
+                .map(|color| color.to_u32())
+                .collect::<Vec<u32>>();
+            let fills = (fills[0], fills[1]);
+            self.app_settings()
+                .set_value("toolspage-fills", &fills.to_variant())?;
+
             // Save format
             let format_string = serde_json::to_string(&self.canvas().sheet().borrow().format)?;
             self.app_settings()
@@ -1,9 +1,14 @@
 use super::RnoteAppWindow;
 use crate::pens::brush::BrushStyle;
-use crate::pens::selector::SelectorStyle;
+use crate::pens::presets::PenPreset;
+use crate::pens::selector::{SelectorContainment, SelectorStyle};
 use crate::pens::shaper::{ShaperDrawStyle, ShaperStyle};
-use crate::pens::tools::ToolStyle;
+use crate::pens::tools::{RulerShape, ToolStyle};
 use crate::render::{self, RendererBackend};
+use crate::sheet::format::MeasureUnit;
+use crate::strokes::strokestyle::StyleChange;
+use crate::strokesstate::selection_comp::{Alignment, Distribution};
+use crate::strokesstate::zorder_comp::ZOrderChange;
 use crate::{
     app::RnoteApp,
     compose,
@@ -12,14 +17,20 @@ use crate::{
     utils,
 };
 
+use gettextrs::gettext;
 use gtk4::{
     gdk, gio, glib, glib::clone, prelude::*, ArrowType, CornerType, PackType, PositionType,
     PrintOperation, PrintOperationAction, Unit,
 };
 use p2d::bounding_volume::AABB;
-use std::{cell::Cell, rc::Rc};
+use p2d::query::PointQuery;
+use std::cell::Cell;
+use std::rc::Rc;
 
 impl RnoteAppWindow {
+    /// The accelerators for deselecting all strokes. The presentation mode takes them over while presenting
+    pub const SELECTION_DESELECT_ALL_ACCELS: &'static [&'static str] = &["Escape"];
+
     /// Appwindow actions only have state in the lifetime of the application. Actions that are saved to the settings should be app actions
     pub fn setup_actions(&self) {
         let action_close_active = gio::SimpleAction::new("close-active", None);
@@ -65,6 +76,9 @@ impl RnoteAppWindow {
         let action_touch_drawing =
             gio::PropertyAction::new("touch-drawing", &self.canvas(), "touch-drawing");
         self.add_action(&action_touch_drawing);
+        let action_touch_rotation =
+            gio::PropertyAction::new("touch-rotation", &self.canvas(), "touch-rotation");
+        self.add_action(&action_touch_rotation);
         let action_endless_sheet =
             gio::PropertyAction::new("endless-sheet", &self.canvas(), "endless-sheet");
         self.add_action(&action_endless_sheet);
@@ -80,6 +94,19 @@ impl RnoteAppWindow {
         self.add_action(&action_zoom_reset);
         let action_zoom_fit_width = gio::SimpleAction::new("zoom-fit-width", None);
         self.add_action(&action_zoom_fit_width);
+        let action_rotation_reset = gio::SimpleAction::new("rotation-reset", None);
+        self.add_action(&action_rotation_reset);
+        let action_presentation_mode =
+            gio::SimpleAction::new_stateful("presentation-mode", None, &false.to_variant());
+        self.add_action(&action_presentation_mode);
+        let action_presentation_next_page = gio::SimpleAction::new("presentation-next-page", None);
+        self.add_action(&action_presentation_next_page);
+        let action_presentation_prev_page = gio::SimpleAction::new("presentation-prev-page", None);
+        self.add_action(&action_presentation_prev_page);
+        let action_presentation_exit = gio::SimpleAction::new("presentation-exit", None);
+        self.add_action(&action_presentation_exit);
+        let action_page_overview = gio::SimpleAction::new("page-overview", None);
+        self.add_action(&action_page_overview);
         let action_zoomin = gio::SimpleAction::new("zoom-in", None);
         self.add_action(&action_zoomin);
         let action_zoomout = gio::SimpleAction::new("zoom-out", None);
@@ -92,6 +119,53 @@ impl RnoteAppWindow {
         self.add_action(&action_selection_select_all);
         let action_selection_deselect_all = gio::SimpleAction::new("selection-deselect-all", None);
         self.add_action(&action_selection_deselect_all);
+        let action_selection_flip_horizontal =
+            gio::SimpleAction::new("selection-flip-horizontal", None);
+        self.add_action(&action_selection_flip_horizontal);
+        let action_selection_flip_vertical =
+            gio::SimpleAction::new("selection-flip-vertical", None);
+        self.add_action(&action_selection_flip_vertical);
+        let action_selection_skew = gio::SimpleAction::new(
+            "selection-skew",
+            Some(&glib::VariantType::new("(dd)").unwrap()),
+        );
+        self.add_action(&action_selection_skew);
+        let action_selection_transform = gio::SimpleAction::new(
+            "selection-transform",
+            Some(&glib::VariantType::new("(ddddd)").unwrap()),
+        );
+        self.add_action(&action_selection_transform);
+        let action_selection_align = gio::SimpleAction::new(
+            "selection-align",
+            Some(&glib::VariantType::new("s").unwrap()),
+        );
+        self.add_action(&action_selection_align);
+        let action_selection_align_to_page =
+            gio::SimpleAction::new_stateful("selection-align-to-page", None, &false.to_variant());
+        self.add_action(&action_selection_align_to_page);
+        let action_selection_distribute = gio::SimpleAction::new(
+            "selection-distribute",
+            Some(&glib::VariantType::new("s").unwrap()),
+        );
+        self.add_action(&action_selection_distribute);
+        let action_selection_zorder = gio::SimpleAction::new(
+            "selection-zorder",
+            Some(&glib::VariantType::new("s").unwrap()),
+        );
+        self.add_action(&action_selection_zorder);
+        let action_selection_restyle_brushstyle = gio::SimpleAction::new(
+            "selection-restyle-brushstyle",
+            Some(&glib::VariantType::new("s").unwrap()),
+        );
+        self.add_action(&action_selection_restyle_brushstyle);
+        let action_selection_restyle_shapedrawstyle = gio::SimpleAction::new(
+            "selection-restyle-shapedrawstyle",
+            Some(&glib::VariantType::new("s").unwrap()),
+        );
+        self.add_action(&action_selection_restyle_shapedrawstyle);
+        let action_selection_restyle_remove_fill =
+            gio::SimpleAction::new("selection-restyle-remove-fill", None);
+        self.add_action(&action_selection_restyle_remove_fill);
         let action_clear_sheet = gio::SimpleAction::new("clear-sheet", None);
         self.add_action(&action_clear_sheet);
         let action_new_sheet = gio::SimpleAction::new("new-sheet", None);
@@ -148,9 +222,38 @@ impl RnoteAppWindow {
             Some(&glib::VariantType::new("s").unwrap()),
         );
         self.add_action(&action_selector_style);
+        let action_selector_containment = gio::SimpleAction::new(
+            "selector-containment",
+            Some(&glib::VariantType::new("s").unwrap()),
+        );
+        self.add_action(&action_selector_containment);
         let action_tool_style =
             gio::SimpleAction::new("tool-style", Some(&glib::VariantType::new("s").unwrap()));
         self.add_action(&action_tool_style);
+        let action_tool_ruler_hide = gio::SimpleAction::new("tool-ruler-hide", None);
+        self.add_action(&action_tool_ruler_hide);
+        let action_tool_ruler_unit =
+            gio::SimpleAction::new("tool-ruler-unit", Some(&glib::VariantType::new("s").unwrap()));
+        self.add_action(&action_tool_ruler_unit);
+        let action_pen_preset_apply = gio::SimpleAction::new(
+            "pen-preset-apply",
+            Some(&glib::VariantType::new("s").unwrap()),
+        );
+        self.add_action(&action_pen_preset_apply);
+        let action_pen_preset_save_current = gio::SimpleAction::new(
+            "pen-preset-save-current",
+            Some(&glib::VariantType::new("s").unwrap()),
+        );
+        self.add_action(&action_pen_preset_save_current);
+        let action_pen_preset_remove = gio::SimpleAction::new(
+            "pen-preset-remove",
+            Some(&glib::VariantType::new("s").unwrap()),
+        );
+        self.add_action(&action_pen_preset_remove);
+        let action_import_pen_presets = gio::SimpleAction::new("import-pen-presets", None);
+        self.add_action(&action_import_pen_presets);
+        let action_export_pen_presets = gio::SimpleAction::new("export-pen-presets", None);
+        self.add_action(&action_export_pen_presets);
         let action_refresh_ui_for_sheet = gio::SimpleAction::new("refresh-ui-for-sheet", None);
         self.add_action(&action_refresh_ui_for_sheet);
 
@@ -408,29 +511,26 @@ impl RnoteAppWindow {
         // Current Pen
         action_current_pen.connect_activate(
             clone!(@weak self as appwindow => move |_action_current_pen, target| {
-                let current_pen = target.unwrap().str().unwrap();
+                let current_pen = match target.unwrap().str().unwrap() {
+                    "marker_style" => PenStyle::MarkerStyle,
+                    "brush_style" => PenStyle::BrushStyle,
+                    "shaper_style" => PenStyle::ShaperStyle,
+                    "eraser_style" => PenStyle::EraserStyle,
+                    "selector_style" => PenStyle::SelectorStyle,
+                    "tools_style" => PenStyle::ToolsStyle,
+                    _ => {
+                        log::error!("set invalid state of action `current-pen`");
+                        return;
+                    }
+                };
+                // The pen toggles and the sidebar pages activate this action as well when they are refreshed to the current pen
+                if appwindow.canvas().pens().borrow().current_pen == current_pen {
+                    return;
+                }
 
-                match current_pen {
-                    "marker_style" => {
-                        appwindow.canvas().pens().borrow_mut().current_pen = PenStyle::MarkerStyle;
-                        appwindow.canvas().pens().borrow_mut().marker.options.width = appwindow.penssidebar().marker_page().width_spinbutton().value();
-                    },
-                    "brush_style" => {
-                        appwindow.canvas().pens().borrow_mut().current_pen = PenStyle::BrushStyle;
-                    },
-                    "shaper_style" => {
-                        appwindow.canvas().pens().borrow_mut().current_pen = PenStyle::ShaperStyle;
-                    },
-                    "eraser_style" => {
-                        appwindow.canvas().pens().borrow_mut().current_pen = PenStyle::EraserStyle;
-                    },
-                    "selector_style" => {
-                        appwindow.canvas().pens().borrow_mut().current_pen = PenStyle::SelectorStyle;
-                    },
-                    "tools_style" => {
-                        appwindow.canvas().pens().borrow_mut().current_pen = PenStyle::ToolsStyle;
-                    },
-                    _ => { log::error!("set invalid state of action `current-pen`")}
+                appwindow.canvas().pens().borrow_mut().current_pen = current_pen;
+                if current_pen == PenStyle::MarkerStyle {
+                    appwindow.canvas().pens().borrow_mut().marker.options.width = appwindow.penssidebar().marker_page().width_spinbutton().value();
                 }
 
                 adw::prelude::ActionGroupExt::activate_action(&appwindow, "refresh-ui-for-sheet", None);
@@ -453,6 +553,16 @@ impl RnoteAppWindow {
                     appwindow.canvas().pens().borrow_mut().brush.textured_options.width = appwindow.penssidebar().brush_page().width_spinbutton().value();
                     appwindow.canvas().pens().borrow_mut().brush.textured_options.stroke_color = Some(appwindow.penssidebar().brush_page().colorpicker().current_color());
                 },
+                "calligraphy" => {
+                    appwindow.canvas().pens().borrow_mut().brush.style = brush::BrushStyle::Calligraphy;
+                    appwindow.canvas().pens().borrow_mut().brush.calligraphy_options.width = appwindow.penssidebar().brush_page().width_spinbutton().value();
+                    appwindow.canvas().pens().borrow_mut().brush.calligraphy_options.stroke_color = Some(appwindow.penssidebar().brush_page().colorpicker().current_color());
+                },
+                "pencil" => {
+                    appwindow.canvas().pens().borrow_mut().brush.style = brush::BrushStyle::Pencil;
+                    appwindow.canvas().pens().borrow_mut().brush.pencil_options.width = appwindow.penssidebar().brush_page().width_spinbutton().value();
+                    appwindow.canvas().pens().borrow_mut().brush.pencil_options.stroke_color = Some(appwindow.penssidebar().brush_page().colorpicker().current_color());
+                },
                 _ => { log::error!("set invalid state of action `brush-style`")}
             }
 
@@ -527,6 +637,25 @@ impl RnoteAppWindow {
         }),
         );
 
+        // Selector Containment
+        action_selector_containment.connect_activate(
+        clone!(@weak self as appwindow => move |_action_selector_containment, target| {
+            let selector_containment = target.unwrap().str().unwrap();
+
+            match selector_containment {
+                "contained" => {
+                    appwindow.canvas().pens().borrow_mut().selector.containment = selector::SelectorContainment::Contained;
+                },
+                "touching" => {
+                    appwindow.canvas().pens().borrow_mut().selector.containment = selector::SelectorContainment::Touching;
+                },
+                _ => { log::error!("set invalid state of action `selector-containment`")}
+            }
+
+            adw::prelude::ActionGroupExt::activate_action(&appwindow, "refresh-ui-for-sheet", None);
+        }),
+        );
+
         // Tool Style
         action_tool_style.connect_activate(
         clone!(@weak self as appwindow => move |_action_tool_style, target| {
@@ -539,13 +668,117 @@ impl RnoteAppWindow {
                 "dragproximity" => {
                     appwindow.canvas().pens().borrow_mut().tools.style = tools::ToolStyle::DragProximity;
                 },
+                "ruler" => {
+                    let viewport_center = appwindow.canvas().viewport_in_sheet_coords().center().coords;
+                    appwindow.canvas().pens().borrow_mut().tools.style = tools::ToolStyle::Ruler;
+                    appwindow.canvas().pens().borrow_mut().tools.ruler_tool.show(RulerShape::Straightedge, viewport_center);
+                },
+                "protractor" => {
+                    let viewport_center = appwindow.canvas().viewport_in_sheet_coords().center().coords;
+                    appwindow.canvas().pens().borrow_mut().tools.style = tools::ToolStyle::Protractor;
+                    appwindow.canvas().pens().borrow_mut().tools.ruler_tool.show(RulerShape::Protractor, viewport_center);
+                },
+                "laser" => {
+                    appwindow.canvas().pens().borrow_mut().tools.style = tools::ToolStyle::Laser;
+                },
+                "vanishingink" => {
+                    appwindow.canvas().pens().borrow_mut().tools.style = tools::ToolStyle::VanishingInk;
+                },
+                "fill" => {
+                    appwindow.canvas().pens().borrow_mut().tools.style = tools::ToolStyle::Fill;
+                    appwindow.canvas().pens().borrow_mut().tools.fill_tool.color = appwindow.penssidebar().tools_page().fill_colorpicker().current_color();
+                },
                 _ => { log::error!("set invalid state of action `tool-style`")}
             }
 
+            appwindow.canvas().queue_draw();
             adw::prelude::ActionGroupExt::activate_action(&appwindow, "refresh-ui-for-sheet", None);
         }),
         );
 
+        // Hide the ruler
+        action_tool_ruler_hide.connect_activate(
+        clone!(@weak self as appwindow => move |_action_tool_ruler_hide, _| {
+            appwindow.canvas().pens().borrow_mut().tools.ruler_tool.hide();
+            appwindow.canvas().queue_draw();
+        }),
+        );
+
+        // Ruler unit
+        action_tool_ruler_unit.connect_activate(
+        clone!(@weak self as appwindow => move |_action_tool_ruler_unit, target| {
+            let unit = target.unwrap().str().unwrap();
+
+            match unit {
+                "px" => {
+                    appwindow.canvas().pens().borrow_mut().tools.ruler_tool.unit = MeasureUnit::Px;
+                },
+                "mm" => {
+                    appwindow.canvas().pens().borrow_mut().tools.ruler_tool.unit = MeasureUnit::Mm;
+                },
+                "cm" => {
+                    appwindow.canvas().pens().borrow_mut().tools.ruler_tool.unit = MeasureUnit::Cm;
+                },
+                _ => { log::error!("set invalid state of action `tool-ruler-unit`")}
+            }
+
+            appwindow.canvas().queue_draw();
+        }),
+        );
+
+        // Apply pen preset
+        action_pen_preset_apply.connect_activate(
+            clone!(@weak self as appwindow => move |_action_pen_preset_apply, target| {
+                let name = target.unwrap().get::<String>().unwrap();
+
+                let preset = appwindow.canvas().pens().borrow().presets.get(&name).cloned();
+                if let Some(preset) = preset {
+                    preset.apply(&mut appwindow.canvas().pens().borrow_mut());
+                } else {
+                    log::error!("pen preset `{}` does not exist", name);
+                }
+
+                adw::prelude::ActionGroupExt::activate_action(&appwindow, "refresh-ui-for-sheet", None);
+            }),
+        );
+
+        // Save the current pen as preset
+        action_pen_preset_save_current.connect_activate(
+            clone!(@weak self as appwindow => move |_action_pen_preset_save_current, target| {
+                let name = target.unwrap().get::<String>().unwrap();
+
+                let preset = PenPreset::from_current_pen(name, &appwindow.canvas().pens().borrow());
+                if let Some(preset) = preset {
+                    appwindow.canvas().pens().borrow_mut().presets.insert(preset);
+                } else {
+                    adw::prelude::ActionGroupExt::activate_action(&appwindow, "text-notify", Some(&gettext("Only marker, brush and shaper can be saved as presets").to_variant()));
+                }
+
+                adw::prelude::ActionGroupExt::activate_action(&appwindow, "refresh-ui-for-sheet", None);
+            }),
+        );
+
+        // Remove pen preset
+        action_pen_preset_remove.connect_activate(
+            clone!(@weak self as appwindow => move |_action_pen_preset_remove, target| {
+                let name = target.unwrap().get::<String>().unwrap();
+
+                appwindow.canvas().pens().borrow_mut().presets.remove(&name);
+
+                adw::prelude::ActionGroupExt::activate_action(&appwindow, "refresh-ui-for-sheet", None);
+            }),
+        );
+
+        // Import pen presets
+        action_import_pen_presets.connect_activate(clone!(@weak self as appwindow => move |_,_| {
+            dialogs::dialog_import_pen_presets(&appwindow);
+        }));
+
+        // Export pen presets
+        action_export_pen_presets.connect_activate(clone!(@weak self as appwindow => move |_,_| {
+            dialogs::dialog_export_pen_presets(&appwindow);
+        }));
+
         // Refresh UI state
         action_refresh_ui_for_sheet.connect_activate(
             clone!(@weak self as appwindow => move |_action_refresh_ui_for_sheet, _| {
@@ -553,37 +786,12 @@ impl RnoteAppWindow {
                 let pens = appwindow.canvas().pens().borrow().clone();
 
                 // Current pen
-                match pens.current_pen {
-                    PenStyle::MarkerStyle => {
-                        appwindow.mainheader().marker_toggle().set_active(true);
-                        appwindow.penssidebar().sidebar_stack().set_visible_child_name("marker_page");
-                    }
-                    PenStyle::BrushStyle => {
-                        appwindow.mainheader().brush_toggle().set_active(true);
-                        appwindow.penssidebar().sidebar_stack().set_visible_child_name("brush_page");
-                    }
-                    PenStyle::ShaperStyle => {
-                        // Avoids borrow errors
-                        appwindow.mainheader().shaper_toggle().set_active(true);
-                        appwindow.penssidebar().sidebar_stack().set_visible_child_name("shaper_page");
-                    }
-                    PenStyle::EraserStyle => {
-                        appwindow.mainheader().eraser_toggle().set_active(true);
-                        appwindow.penssidebar().sidebar_stack().set_visible_child_name("eraser_page");
-                    }
-                    PenStyle::SelectorStyle => {
-                        appwindow.mainheader().selector_toggle().set_active(true);
-                        appwindow.penssidebar().sidebar_stack().set_visible_child_name("selector_page");
-                    }
-                    PenStyle::ToolsStyle => {
-                        appwindow.mainheader().tools_toggle().set_active(true);
-                        appwindow.penssidebar().sidebar_stack().set_visible_child_name("tools_page");
-                    }
-                }
+                appwindow.refresh_current_pen_ui();
 
                 // Marker
                 appwindow.penssidebar().marker_page().width_spinbutton().set_value(pens.marker.options.width);
                 appwindow.penssidebar().marker_page().colorpicker().set_current_color(pens.marker.options.stroke_color);
+                appwindow.penssidebar().marker_page().highlighter_togglebutton().set_active(pens.marker.highlighter);
 
                 // Brush
                 appwindow.penssidebar().brush_page().texturedstyle_density_spinbutton()
@@ -593,6 +801,16 @@ impl RnoteAppWindow {
                 appwindow.penssidebar().brush_page().texturedstyle_radius_y_spinbutton()
                     .set_value(pens.brush.textured_options.radii[1]);
                 appwindow.penssidebar().brush_page().set_texturedstyle_distribution_variant(pens.brush.textured_options.distribution);
+                appwindow.penssidebar().brush_page().calligraphystyle_nib_angle_spinbutton()
+                    .set_value(pens.brush.calligraphy_options.nib_angle.to_degrees());
+                appwindow.penssidebar().brush_page().calligraphystyle_nib_thickness_spinbutton()
+                    .set_value(pens.brush.calligraphy_options.nib_thickness);
+                appwindow.penssidebar().brush_page().calligraphystyle_tilt_sensitivity_spinbutton()
+                    .set_value(pens.brush.calligraphy_options.tilt_sensitivity);
+                appwindow.penssidebar().brush_page().pencilstyle_grain_density_spinbutton()
+                    .set_value(pens.brush.pencil_options.grain_density);
+                appwindow.penssidebar().brush_page().pencilstyle_grain_radius_spinbutton()
+                    .set_value(pens.brush.pencil_options.grain_radius);
                 match pens.brush.style {
                     BrushStyle::Solid => {
                         appwindow.penssidebar().brush_page().brushstyle_listbox().select_row(Some(&appwindow.penssidebar().brush_page().brushstyle_solid_row()));
@@ -604,6 +822,16 @@ impl RnoteAppWindow {
                         appwindow.penssidebar().brush_page().width_spinbutton().set_value(pens.brush.textured_options.width);
                         appwindow.penssidebar().brush_page().colorpicker().set_current_color(pens.brush.textured_options.stroke_color);
                     },
+                    BrushStyle::Calligraphy => {
+                        appwindow.penssidebar().brush_page().brushstyle_listbox().select_row(Some(&appwindow.penssidebar().brush_page().brushstyle_calligraphy_row()));
+                        appwindow.penssidebar().brush_page().width_spinbutton().set_value(pens.brush.calligraphy_options.width);
+                        appwindow.penssidebar().brush_page().colorpicker().set_current_color(pens.brush.calligraphy_options.stroke_color);
+                    },
+                    BrushStyle::Pencil => {
+                        appwindow.penssidebar().brush_page().brushstyle_listbox().select_row(Some(&appwindow.penssidebar().brush_page().brushstyle_pencil_row()));
+                        appwindow.penssidebar().brush_page().width_spinbutton().set_value(pens.brush.pencil_options.width);
+                        appwindow.penssidebar().brush_page().colorpicker().set_current_color(pens.brush.pencil_options.stroke_color);
+                    },
                 }
 
                 // Shaper
@@ -654,13 +882,29 @@ impl RnoteAppWindow {
                     SelectorStyle::Polygon => appwindow.penssidebar().selector_page().selectorstyle_polygon_toggle().set_active(true),
                     SelectorStyle::Rectangle => appwindow.penssidebar().selector_page().selectorstyle_rect_toggle().set_active(true),
                 }
+                let selector_containment = appwindow.canvas().pens().borrow().selector.containment;
+                match selector_containment {
+                    SelectorContainment::Contained => appwindow.penssidebar().selector_page().selectorcontainment_contained_toggle().set_active(true),
+                    SelectorContainment::Touching => appwindow.penssidebar().selector_page().selectorcontainment_touching_toggle().set_active(true),
+                }
 
                 // Tools
                 let tool_style = appwindow.canvas().pens().borrow().tools.style;
                 match tool_style {
                     ToolStyle::ExpandSheet => appwindow.penssidebar().tools_page().toolstyle_expandsheet_toggle().set_active(true),
                     ToolStyle::DragProximity => appwindow.penssidebar().tools_page().toolstyle_dragproximity_toggle().set_active(true),
+                    ToolStyle::Ruler => appwindow.penssidebar().tools_page().toolstyle_ruler_toggle().set_active(true),
+                    ToolStyle::Protractor => appwindow.penssidebar().tools_page().toolstyle_protractor_toggle().set_active(true),
+                    ToolStyle::Laser => appwindow.penssidebar().tools_page().toolstyle_laser_toggle().set_active(true),
+                    ToolStyle::VanishingInk => appwindow.penssidebar().tools_page().toolstyle_vanishingink_toggle().set_active(true),
+                    ToolStyle::Fill => appwindow.penssidebar().tools_page().toolstyle_fill_toggle().set_active(true),
                 }
+                appwindow.penssidebar().tools_page().fill_colorpicker().set_current_color(Some(pens.tools.fill_tool.color));
+                appwindow.penssidebar().tools_page().vanishingink_timeout_spinbutton().set_value(pens.tools.vanishing_ink_tool.timeout);
+                appwindow.penssidebar().tools_page().set_ruler_unit(pens.tools.ruler_tool.unit);
+
+                // Presets
+                appwindow.penssidebar().refresh_presets(&pens.presets, &appwindow);
 
                 // Settings panel
                 appwindow.settings_panel().refresh_for_sheet(&appwindow);
@@ -711,6 +955,255 @@ impl RnoteAppWindow {
             }),
         );
 
+        // Flip the selection horizontally
+        action_selection_flip_horizontal.connect_activate(
+            clone!(@weak self as appwindow => move |_action_selection_flip_horizontal, _| {
+                let selection_bounds = appwindow.canvas().sheet().borrow().strokes_state.gen_selection_bounds();
+                if let Some(selection_bounds) = selection_bounds {
+                    let selection_keys = appwindow.canvas().sheet().borrow().strokes_state.selection_keys_in_order_rendered();
+                    appwindow.canvas().sheet().borrow_mut().strokes_state.flip_strokes_horizontal(
+                        &selection_keys,
+                        selection_bounds.center(),
+                        appwindow.canvas().renderer(),
+                        appwindow.canvas().zoom(),
+                    );
+
+                    appwindow.canvas().set_unsaved_changes(true);
+                    appwindow.canvas().selection_modifier().update_state(&appwindow.canvas());
+                    appwindow.canvas().queue_draw();
+                }
+            }),
+        );
+
+        // Flip the selection vertically
+        action_selection_flip_vertical.connect_activate(
+            clone!(@weak self as appwindow => move |_action_selection_flip_vertical, _| {
+                let selection_bounds = appwindow.canvas().sheet().borrow().strokes_state.gen_selection_bounds();
+                if let Some(selection_bounds) = selection_bounds {
+                    let selection_keys = appwindow.canvas().sheet().borrow().strokes_state.selection_keys_in_order_rendered();
+                    appwindow.canvas().sheet().borrow_mut().strokes_state.flip_strokes_vertical(
+                        &selection_keys,
+                        selection_bounds.center(),
+                        appwindow.canvas().renderer(),
+                        appwindow.canvas().zoom(),
+                    );
+
+                    appwindow.canvas().set_unsaved_changes(true);
+                    appwindow.canvas().selection_modifier().update_state(&appwindow.canvas());
+                    appwindow.canvas().queue_draw();
+                }
+            }),
+        );
+
+        // Skew the selection. The target are the horizontal and vertical skew angles in degrees
+        action_selection_skew.connect_activate(
+            clone!(@weak self as appwindow => move |_action_selection_skew, target| {
+                let (angle_x, angle_y) = target.unwrap().get::<(f64, f64)>().unwrap();
+                let angles = na::vector![angle_x.to_radians(), angle_y.to_radians()];
+
+                let selection_bounds = appwindow.canvas().sheet().borrow().strokes_state.gen_selection_bounds();
+                if let Some(selection_bounds) = selection_bounds {
+                    let selection_keys = appwindow.canvas().sheet().borrow().strokes_state.selection_keys_in_order_rendered();
+                    appwindow.canvas().sheet().borrow_mut().strokes_state.skew_strokes(
+                        &selection_keys,
+                        angles,
+                        selection_bounds.center(),
+                        appwindow.canvas().renderer(),
+                        appwindow.canvas().zoom(),
+                    );
+
+                    appwindow.canvas().set_unsaved_changes(true);
+                    appwindow.canvas().selection_modifier().update_state(&appwindow.canvas());
+                    appwindow.canvas().queue_draw();
+                }
+            }),
+        );
+
+        // Transform the selection numerically. The target are the position and size in px and the rotation since the strokes were selected in degrees
+        action_selection_transform.connect_activate(
+            clone!(@weak self as appwindow => move |_action_selection_transform, target| {
+                let (x, y, width, height, rotation) = target.unwrap().get::<(f64, f64, f64, f64, f64)>().unwrap();
+
+                let selection_bounds = appwindow.canvas().sheet().borrow().strokes_state.gen_selection_bounds();
+                if let Some(selection_bounds) = selection_bounds {
+                    let selection_keys = appwindow.canvas().sheet().borrow().strokes_state.selection_keys_in_order_rendered();
+                    let new_extents = na::vector![width.max(1.0), height.max(1.0)];
+
+                    if (new_extents - selection_bounds.extents()).abs().max() > f64::EPSILON {
+                        appwindow.canvas().sheet().borrow_mut().strokes_state.resize_strokes(
+                            &selection_keys,
+                            selection_bounds,
+                            AABB::new(selection_bounds.mins, selection_bounds.mins + new_extents),
+                            appwindow.canvas().renderer(),
+                            appwindow.canvas().zoom(),
+                        );
+                    }
+
+                    let offset = na::vector![x, y] - selection_bounds.mins.coords;
+                    if offset.abs().max() > f64::EPSILON {
+                        appwindow.canvas().sheet().borrow_mut().strokes_state.translate_strokes(
+                            &selection_keys,
+                            offset,
+                            appwindow.canvas().zoom(),
+                        );
+                    }
+
+                    let angle = rotation.to_radians() - appwindow.canvas().sheet().borrow().strokes_state.selection_rotation();
+                    // The shortest rotation, whole turns are not applied
+                    let angle = angle - (angle / std::f64::consts::TAU).round() * std::f64::consts::TAU;
+                    if angle.abs() > f64::EPSILON {
+                        let center = na::Point2::from(na::vector![x, y] + new_extents * 0.5);
+                        appwindow.canvas().sheet().borrow_mut().strokes_state.rotate_selection(
+                            angle,
+                            center,
+                            appwindow.canvas().renderer(),
+                            appwindow.canvas().zoom(),
+                        );
+                    }
+
+                    appwindow.canvas().set_unsaved_changes(true);
+                    appwindow.canvas().selection_modifier().update_state(&appwindow.canvas());
+                    appwindow.canvas().queue_draw();
+                }
+            }),
+        );
+
+        // Toggle aligning the selection relative to the page instead of the selection bounds
+        action_selection_align_to_page.connect_activate(
+            move |action_selection_align_to_page, _target| {
+                let state = action_selection_align_to_page.state().unwrap().get::<bool>().unwrap();
+                action_selection_align_to_page.change_state(&(!state).to_variant());
+            },
+        );
+
+        // Align the selected strokes
+        action_selection_align.connect_activate(
+            clone!(@weak self as appwindow, @weak action_selection_align_to_page => move |_action_selection_align, target| {
+                let alignment = match Alignment::from_nick(target.unwrap().str().unwrap()) {
+                    Some(alignment) => alignment,
+                    None => {
+                        log::error!("invalid target for action `selection-align`");
+                        return;
+                    }
+                };
+                let selection_bounds = match appwindow.canvas().sheet().borrow().strokes_state.gen_selection_bounds() {
+                    Some(selection_bounds) => selection_bounds,
+                    None => return,
+                };
+
+                let align_to_page = action_selection_align_to_page.state().unwrap().get::<bool>().unwrap();
+                let reference = if align_to_page {
+                    // The page which contains the center of the selection
+                    let sheet_bounds = appwindow.canvas().sheet().borrow().bounds();
+                    let page_bounds = appwindow.canvas().sheet().borrow().gen_pages_bounds()
+                        .into_iter()
+                        .find(|page_bounds| {
+                            page_bounds.contains_local_point(&selection_bounds.center())
+                        })
+                        .unwrap_or(sheet_bounds);
+
+                    Some(page_bounds)
+                } else {
+                    None
+                };
+
+                appwindow.canvas().sheet().borrow_mut().strokes_state.align_selection(
+                    alignment,
+                    reference,
+                    appwindow.canvas().zoom(),
+                );
+
+                appwindow.canvas().set_unsaved_changes(true);
+                appwindow.canvas().selection_modifier().update_state(&appwindow.canvas());
+                appwindow.canvas().queue_draw();
+            }),
+        );
+
+        // Distribute the selected strokes evenly
+        action_selection_distribute.connect_activate(
+            clone!(@weak self as appwindow => move |_action_selection_distribute, target| {
+                let distribution = match Distribution::from_nick(target.unwrap().str().unwrap()) {
+                    Some(distribution) => distribution,
+                    None => {
+                        log::error!("invalid target for action `selection-distribute`");
+                        return;
+                    }
+                };
+
+                appwindow.canvas().sheet().borrow_mut().strokes_state.distribute_selection(
+                    distribution,
+                    appwindow.canvas().zoom(),
+                );
+
+                appwindow.canvas().set_unsaved_changes(true);
+                appwindow.canvas().selection_modifier().update_state(&appwindow.canvas());
+                appwindow.canvas().queue_draw();
+            }),
+        );
+
+        // Change the z-order of the selected strokes
+        action_selection_zorder.connect_activate(
+            clone!(@weak self as appwindow => move |_action_selection_zorder, target| {
+                let zorder_change = match ZOrderChange::from_nick(target.unwrap().str().unwrap()) {
+                    Some(zorder_change) => zorder_change,
+                    None => {
+                        log::error!("invalid target for action `selection-zorder`");
+                        return;
+                    }
+                };
+
+                let selection_keys = appwindow.canvas().sheet().borrow().strokes_state.selection_keys_in_order_rendered();
+                if selection_keys.is_empty() {
+                    return;
+                }
+                appwindow.canvas().sheet().borrow_mut().strokes_state.change_zorder(&selection_keys, zorder_change);
+
+                appwindow.canvas().set_unsaved_changes(true);
+                appwindow.canvas().queue_draw();
+            }),
+        );
+
+        // Restyle the brush style of the selected strokes
+        action_selection_restyle_brushstyle.connect_activate(
+            clone!(@weak self as appwindow => move |_action_selection_restyle_brushstyle, target| {
+                let brush_style = match target.unwrap().str().unwrap() {
+                    "solid" => BrushStyle::Solid,
+                    "textured" => BrushStyle::Textured,
+                    "calligraphy" => BrushStyle::Calligraphy,
+                    "pencil" => BrushStyle::Pencil,
+                    _ => {
+                        log::error!("invalid target for action `selection-restyle-brushstyle`");
+                        return;
+                    }
+                };
+
+                appwindow.canvas().restyle_selection(StyleChange::BrushStyle(brush_style));
+            }),
+        );
+
+        // Restyle the draw style of the selected shapes
+        action_selection_restyle_shapedrawstyle.connect_activate(
+            clone!(@weak self as appwindow => move |_action_selection_restyle_shapedrawstyle, target| {
+                let drawstyle = match target.unwrap().str().unwrap() {
+                    "smooth" => ShaperDrawStyle::Smooth,
+                    "rough" => ShaperDrawStyle::Rough,
+                    _ => {
+                        log::error!("invalid target for action `selection-restyle-shapedrawstyle`");
+                        return;
+                    }
+                };
+
+                appwindow.canvas().restyle_selection(StyleChange::ShapeDrawStyle(drawstyle));
+            }),
+        );
+
+        // Remove the fill of the selected shapes
+        action_selection_restyle_remove_fill.connect_activate(
+            clone!(@weak self as appwindow => move |_action_selection_restyle_remove_fill, _| {
+                appwindow.canvas().restyle_selection(StyleChange::FillColor(None));
+            }),
+        );
+
         // Clear sheet
         action_clear_sheet.connect_activate(clone!(@weak self as appwindow => move |_, _| {
             dialogs::dialog_clear_sheet(&appwindow);
@@ -735,6 +1228,14 @@ impl RnoteAppWindow {
             appwindow.canvas().zoom_to(Canvas::ZOOM_DEFAULT);
         }));
 
+        // Rotation reset
+        action_rotation_reset.connect_activate(clone!(@weak self as appwindow => move |_,_| {
+            let sheet_center_pos = appwindow.canvas().transform_canvas_coords_to_sheet_coords(appwindow.canvas().view_center().coords);
+
+            appwindow.canvas().rotate_to(0.0);
+            appwindow.canvas().center_around_coord_on_sheet(sheet_center_pos);
+        }));
+
         // Zoom fit to width
         action_zoom_fit_width.connect_activate(clone!(@weak self as appwindow => move |_,_| {
             let mut new_zoom = appwindow.canvas().zoom();
@@ -745,6 +1246,82 @@ impl RnoteAppWindow {
             appwindow.canvas().zoom_to(new_zoom);
         }));
 
+        // Presentation mode
+        action_presentation_next_page.set_enabled(false);
+        action_presentation_prev_page.set_enabled(false);
+        action_presentation_exit.set_enabled(false);
+        // The flap is hidden while presenting and revealed again afterwards if it was before
+        let flap_revealed_before_presentation = Rc::new(Cell::new(false));
+
+        action_presentation_mode.connect_activate(
+            clone!(@strong flap_revealed_before_presentation, @weak self as appwindow, @weak action_presentation_next_page, @weak action_presentation_prev_page, @weak action_presentation_exit => move |action_presentation_mode, _target| {
+                let state = action_presentation_mode.state().unwrap().get::<bool>().unwrap();
+                let app = appwindow.application().unwrap();
+
+                action_presentation_next_page.set_enabled(!state);
+                action_presentation_prev_page.set_enabled(!state);
+                action_presentation_exit.set_enabled(!state);
+
+                if !state {
+                    // Start presenting the page which is currently in the center of the view
+                    let format_height = f64::from(appwindow.canvas().sheet().borrow().format.height);
+                    let view_center = appwindow.canvas().viewport_in_sheet_coords().center();
+                    let page = if format_height > 0.0 {
+                        (view_center[1] / format_height).floor().max(0.0) as u32
+                    } else {
+                        0
+                    };
+
+                    // Escape leaves the presentation instead of deselecting
+                    app.set_accels_for_action("win.selection-deselect-all", &[]);
+                    app.set_accels_for_action("win.presentation-exit", Self::SELECTION_DESELECT_ALL_ACCELS);
+
+                    flap_revealed_before_presentation.set(appwindow.flapreveal_toggle().is_active());
+                    appwindow.flapreveal_toggle().set_active(false);
+                    appwindow.mainheader().set_visible(false);
+                    appwindow.sidebar_sep().set_visible(false);
+                    appwindow.sidebar_grid().set_visible(false);
+                    appwindow.fullscreen();
+
+                    appwindow.canvas().rotate_to(0.0);
+                    appwindow.present_page(page);
+                } else {
+                    app.set_accels_for_action("win.presentation-exit", &[]);
+                    app.set_accels_for_action("win.selection-deselect-all", Self::SELECTION_DESELECT_ALL_ACCELS);
+
+                    appwindow.unfullscreen();
+                    appwindow.mainheader().set_visible(true);
+                    appwindow.sidebar_sep().set_visible(true);
+                    appwindow.sidebar_grid().set_visible(true);
+                    appwindow.flapreveal_toggle().set_active(flap_revealed_before_presentation.get());
+
+                    appwindow.canvas().set_presented_page(None);
+                    appwindow.canvas().regenerate_content(false, true);
+                }
+
+                action_presentation_mode.change_state(&(!state).to_variant());
+            }),
+        );
+
+        action_presentation_next_page.connect_activate(clone!(@weak self as appwindow => move |_,_| {
+            appwindow.present_page(appwindow.presentation_page().saturating_add(1));
+        }));
+
+        action_presentation_prev_page.connect_activate(clone!(@weak self as appwindow => move |_,_| {
+            appwindow.present_page(appwindow.presentation_page().saturating_sub(1));
+        }));
+
+        action_presentation_exit.connect_activate(clone!(@weak action_presentation_mode => move |_,_| {
+            if action_presentation_mode.state().unwrap().get::<bool>().unwrap() {
+                action_presentation_mode.activate(None);
+            }
+        }));
+
+        // Page overview
+        action_page_overview.connect_activate(clone!(@weak self as appwindow => move |_,_| {
+            dialogs::dialog_page_overview(&appwindow);
+        }));
+
         // Zoom in
         action_zoomin.connect_activate(clone!(@weak self as appwindow => move |_,_| {
             let new_zoom = ((appwindow.canvas().total_zoom() + Canvas::ZOOM_ACTION_DELTA) * 10.0).floor() / 10.0;
@@ -757,30 +1334,18 @@ impl RnoteAppWindow {
             appwindow.canvas().zoom_temporarily_then_scale_to_after_timeout(new_zoom, Canvas::ZOOM_TIMEOUT_TIME);
         }));
 
-        // Temporary Eraser
-        let pen_tmp = Rc::new(Cell::new(PenStyle::default()));
-
+        // Temporary Eraser. Shares the temporary pen of the canvas with the stylus eraser end and side buttons
         action_tmperaser.connect_activate(
-            clone!(@strong pen_tmp, @weak self as appwindow => move |action_tmperaser, target| {
+            clone!(@weak self as appwindow => move |action_tmperaser, target| {
                 let state = action_tmperaser.state().unwrap().get::<bool>().unwrap();
                 let target = target.unwrap().get::<bool>().unwrap();
 
                 // Only change if has changed
                 if target != state {
                     if target {
-                        pen_tmp.set(appwindow.canvas().pens().borrow().current_pen);
-
-                        appwindow
-                            .canvas()
-                            .pens()
-                            .borrow_mut()
-                            .current_pen = PenStyle::EraserStyle;
+                        appwindow.canvas().set_temporary_pen(PenStyle::EraserStyle, &appwindow);
                     } else {
-                        appwindow
-                            .canvas()
-                            .pens()
-                            .borrow_mut()
-                            .current_pen = pen_tmp.get();
+                        appwindow.canvas().reset_temporary_pen(&appwindow);
                     };
                     action_tmperaser.set_state(&target.to_variant());
                 }
@@ -1024,10 +1589,35 @@ impl RnoteAppWindow {
         app.set_accels_for_action("win.redo-stroke", &["<Ctrl><Shift>z"]);
         app.set_accels_for_action("win.zoomin", &["plus"]);
         app.set_accels_for_action("win.zoomout", &["minus"]);
+        app.set_accels_for_action("win.presentation-mode", &["F5"]);
+        app.set_accels_for_action(
+            "win.presentation-next-page",
+            &["Page_Down", "Right", "space"],
+        );
+        app.set_accels_for_action(
+            "win.presentation-prev-page",
+            &["Page_Up", "Left", "BackSpace"],
+        );
+        app.set_accels_for_action("win.page-overview", &["<Ctrl>g"]);
         app.set_accels_for_action("win.selection-trash", &["Delete"]);
         app.set_accels_for_action("win.selection-duplicate", &["<Ctrl>d"]);
         app.set_accels_for_action("win.selection-select-all", &["<Ctrl>a"]);
-        app.set_accels_for_action("win.selection-deselect-all", &["Escape"]);
+        app.set_accels_for_action("win.selection-flip-horizontal", &["<Ctrl><Shift>h"]);
+        app.set_accels_for_action("win.selection-flip-vertical", &["<Ctrl><Shift>v"]);
+        app.set_accels_for_action(
+            "win.selection-zorder('bring-to-front')",
+            &["<Ctrl><Shift>Page_Up"],
+        );
+        app.set_accels_for_action("win.selection-zorder('bring-forward')", &["<Ctrl>Page_Up"]);
+        app.set_accels_for_action("win.selection-zorder('send-backward')", &["<Ctrl>Page_Down"]);
+        app.set_accels_for_action(
+            "win.selection-zorder('send-to-back')",
+            &["<Ctrl><Shift>Page_Down"],
+        );
+        app.set_accels_for_action(
+            "win.selection-deselect-all",
+            Self::SELECTION_DESELECT_ALL_ACCELS,
+        );
         app.set_accels_for_action("win.tmperaser(true)", &["d"]);
         app.set_accels_for_action("win.clipboard-copy-selection", &["<Ctrl>c"]);
         app.set_accels_for_action("win.clipboard-paste-selection", &["<Ctrl>v"]);
@@ -29,6 +29,7 @@ mod imp {
 
         pub righthanded: Cell<bool>,
         pub pen_sounds: Cell<bool>,
+        pub presentation_page: Cell<u32>,
 
         #[template_child]
         pub toast_overlay: TemplateChild<adw::ToastOverlay>,
@@ -77,6 +78,7 @@ mod imp {
 
                 righthanded: Cell::new(true),
                 pen_sounds: Cell::new(true),
+                presentation_page: Cell::new(0),
 
                 toast_overlay: TemplateChild::<adw::ToastOverlay>::default(),
                 main_grid: TemplateChild::<Grid>::default(),
@@ -367,14 +369,16 @@ use std::{
 use adw::prelude::*;
 use gtk4::{
     gdk, gio, glib, glib::clone, subclass::prelude::*, Application, Box, EventControllerScroll,
-    EventControllerScrollFlags, EventSequenceState, FileChooserNative, GestureDrag, GestureZoom,
-    Grid, IconTheme, Inhibit, PropagationPhase, ScrolledWindow, Separator, ToggleButton,
+    EventControllerScrollFlags, EventSequenceState, FileChooserNative, GestureDrag, GestureRotate,
+    GestureZoom, Grid, IconTheme, Inhibit, PropagationPhase, ScrolledWindow, Separator,
+    ToggleButton,
 };
 
 use crate::{
     app::RnoteApp,
     audioplayer::RnoteAudioPlayer,
     config,
+    pens::PenStyle,
     strokes::{bitmapimage::BitmapImage, vectorimage::VectorImage},
     strokesstate::{StateTask, StrokesState},
     ui::canvas::Canvas,
@@ -619,13 +623,19 @@ impl RnoteAppWindow {
         let touch_drag_start_x = Rc::new(Cell::new(0.0));
         let touch_drag_start_y = Rc::new(Cell::new(0.0));
 
-        canvas_touch_drag_gesture.connect_drag_begin(clone!(@strong touch_drag_start_x, @strong touch_drag_start_y, @weak self as appwindow => move |_canvas_touch_drag_gesture, _x, _y| {
+        canvas_touch_drag_gesture.connect_drag_begin(clone!(@strong touch_drag_start_x, @strong touch_drag_start_y, @weak self as appwindow => move |canvas_touch_drag_gesture, _x, _y| {
+            if appwindow.canvas().palm_rejection_rejects_touch(canvas_touch_drag_gesture.current_event().as_ref()) {
+                canvas_touch_drag_gesture.set_state(EventSequenceState::Denied);
+                return;
+            }
+
             touch_drag_start_x.set(appwindow.canvas().hadjustment().unwrap().value());
             touch_drag_start_y.set(appwindow.canvas().vadjustment().unwrap().value());
         }));
         canvas_touch_drag_gesture.connect_drag_update(clone!(@strong touch_drag_start_x, @strong touch_drag_start_y, @weak self as appwindow => move |_canvas_touch_drag_gesture, x, y| {
-            appwindow.canvas().hadjustment().unwrap().set_value(touch_drag_start_x.get() - x);
-            appwindow.canvas().vadjustment().unwrap().set_value(touch_drag_start_y.get() - y);
+            let delta = appwindow.canvas().transform_view_delta_to_canvas_delta(na::vector![x, y]);
+            appwindow.canvas().hadjustment().unwrap().set_value(touch_drag_start_x.get() - delta[0]);
+            appwindow.canvas().vadjustment().unwrap().set_value(touch_drag_start_y.get() - delta[1]);
         }));
         self.canvas_scroller()
             .add_controller(&canvas_touch_drag_gesture);
@@ -647,8 +657,9 @@ impl RnoteAppWindow {
             mouse_drag_start_y.set(appwindow.canvas().vadjustment().unwrap().value());
         }));
         canvas_mouse_middle_gesture.connect_drag_update(clone!(@strong mouse_drag_start_x, @strong mouse_drag_start_y, @weak self as appwindow => move |_canvas_mouse_drag_gesture, x, y| {
-            appwindow.canvas().hadjustment().unwrap().set_value(mouse_drag_start_x.get() - x);
-            appwindow.canvas().vadjustment().unwrap().set_value(mouse_drag_start_y.get() - y);
+            let delta = appwindow.canvas().transform_view_delta_to_canvas_delta(na::vector![x, y]);
+            appwindow.canvas().hadjustment().unwrap().set_value(mouse_drag_start_x.get() - delta[0]);
+            appwindow.canvas().vadjustment().unwrap().set_value(mouse_drag_start_y.get() - delta[1]);
         }));
 
         // Move Canvas by dragging in empty area
@@ -668,8 +679,9 @@ impl RnoteAppWindow {
             mouse_drag_start_y.set(appwindow.canvas().vadjustment().unwrap().value());
         }));
         canvas_mouse_drag_empty_area_gesture.connect_drag_update(clone!(@strong mouse_drag_start_x, @strong mouse_drag_start_y, @weak self as appwindow => move |_canvas_mouse_drag_gesture, x, y| {
-            appwindow.canvas().hadjustment().unwrap().set_value(mouse_drag_start_x.get() - x);
-            appwindow.canvas().vadjustment().unwrap().set_value(mouse_drag_start_y.get() - y);
+            let delta = appwindow.canvas().transform_view_delta_to_canvas_delta(na::vector![x, y]);
+            appwindow.canvas().hadjustment().unwrap().set_value(mouse_drag_start_x.get() - delta[0]);
+            appwindow.canvas().vadjustment().unwrap().set_value(mouse_drag_start_y.get() - delta[1]);
         }));
 
         // Canvas gesture zooming with preview and dragging
@@ -692,6 +704,10 @@ impl RnoteAppWindow {
             @strong zoomgesture_canvasscroller_start_pos,
             @strong zoomgesture_bbcenter_start,
             @weak self as appwindow => move |canvas_zoom_gesture, _eventsequence| {
+                if appwindow.canvas().palm_rejection_rejects_touch(canvas_zoom_gesture.current_event().as_ref()) {
+                    canvas_zoom_gesture.set_state(EventSequenceState::Denied);
+                    return;
+                }
                 canvas_zoom_gesture.set_state(EventSequenceState::Claimed);
 
                 zoom_begin.set(appwindow.canvas().zoom());
@@ -706,7 +722,7 @@ impl RnoteAppWindow {
                 );
                 if let Some(bbcenter) = canvas_zoom_gesture.bounding_box_center() {
                     zoomgesture_bbcenter_start.set(Some(
-                        bbcenter
+                        appwindow.canvas_scroller_coords_to_unrotated_canvas_coords(bbcenter)
                     ));
                 }
         }));
@@ -725,6 +741,8 @@ impl RnoteAppWindow {
                 };
 
                 if let Some(bbcenter) = canvas_zoom_gesture.bounding_box_center() {
+                    let bbcenter = appwindow.canvas_scroller_coords_to_unrotated_canvas_coords(bbcenter);
+
                     if let Some(bbcenter_start) = zoomgesture_bbcenter_start.get() {
                         let bbcenter_delta = (
                             bbcenter.0 - bbcenter_start.0 * new_zoom,
@@ -764,10 +782,63 @@ impl RnoteAppWindow {
             }),
         );
 
+        // Canvas rotation with a two finger touch gesture
+        let canvas_rotate_gesture = GestureRotate::builder()
+            .name("canvas_rotate_gesture")
+            .propagation_phase(PropagationPhase::Capture)
+            .build();
+        self.canvas_scroller().add_controller(&canvas_rotate_gesture);
+
+        let rotation_begin: Rc<Cell<Option<f64>>> = Rc::new(Cell::new(None));
+        let rotategesture_sheet_center = Rc::new(Cell::new(na::vector![0.0, 0.0]));
+
+        // The sequences are claimed and denied by the grouped zoom gesture
+        canvas_rotate_gesture.connect_begin(clone!(@strong rotation_begin, @strong rotategesture_sheet_center, @weak self as appwindow => move |canvas_rotate_gesture, _eventsequence| {
+            if !appwindow.canvas().touch_rotation()
+                || appwindow.canvas().palm_rejection_rejects_touch(canvas_rotate_gesture.current_event().as_ref()) {
+                rotation_begin.set(None);
+                return;
+            }
+
+            rotation_begin.set(Some(appwindow.canvas().rotation()));
+            // the sheet position in the center of the view, which is kept in place while rotating
+            rotategesture_sheet_center.set(appwindow.canvas().transform_canvas_coords_to_sheet_coords(appwindow.canvas().view_center().coords));
+        }));
+
+        canvas_rotate_gesture.connect_angle_changed(clone!(@strong rotation_begin, @strong rotategesture_sheet_center, @weak self as appwindow => move |_canvas_rotate_gesture, _angle, angle_delta| {
+            if let Some(rotation_begin) = rotation_begin.get() {
+                appwindow.canvas().set_rotation(Canvas::snap_rotation(rotation_begin + angle_delta));
+                appwindow.canvas().center_around_coord_on_sheet(rotategesture_sheet_center.get());
+            }
+        }));
+
+        canvas_rotate_gesture.connect_end(clone!(@strong rotation_begin, @weak self as appwindow => move |_canvas_rotate_gesture, _eventsequence| {
+            if rotation_begin.take().is_some() {
+                appwindow.canvas().rotate_to(appwindow.canvas().rotation());
+            }
+        }));
+
         // Gesture Grouping
         canvas_mouse_middle_gesture.group_with(&canvas_touch_drag_gesture);
         canvas_mouse_drag_empty_area_gesture.group_with(&canvas_touch_drag_gesture);
         canvas_zoom_gesture.group_with(&canvas_touch_drag_gesture);
+        canvas_rotate_gesture.group_with(&canvas_touch_drag_gesture);
+
+        // Fit the presented page again when the view size changes, e.g. when going fullscreen
+        self.canvas_scroller().hadjustment().connect_page_size_notify(
+            clone!(@weak self as appwindow => move |_| {
+                if appwindow.canvas().presented_page().is_some() {
+                    appwindow.present_page(appwindow.presentation_page());
+                }
+            }),
+        );
+        self.canvas_scroller().vadjustment().connect_page_size_notify(
+            clone!(@weak self as appwindow => move |_| {
+                if appwindow.canvas().presented_page().is_some() {
+                    appwindow.present_page(appwindow.presentation_page());
+                }
+            }),
+        );
 
         // actions and settings AFTER widget callback declarations
         self.setup_actions();
@@ -800,6 +871,94 @@ impl RnoteAppWindow {
         }
     }
 
+    /// Updates the pen toggles and the visible sidebar page to the current pen
+    pub fn refresh_current_pen_ui(&self) {
+        // Avoids borrow errors, the toggles activate the `current-pen` action
+        let current_pen = self.canvas().pens().borrow().current_pen;
+
+        match current_pen {
+            PenStyle::MarkerStyle => {
+                self.mainheader().marker_toggle().set_active(true);
+                self.penssidebar()
+                    .sidebar_stack()
+                    .set_visible_child_name("marker_page");
+            }
+            PenStyle::BrushStyle => {
+                self.mainheader().brush_toggle().set_active(true);
+                self.penssidebar()
+                    .sidebar_stack()
+                    .set_visible_child_name("brush_page");
+            }
+            PenStyle::ShaperStyle => {
+                self.mainheader().shaper_toggle().set_active(true);
+                self.penssidebar()
+                    .sidebar_stack()
+                    .set_visible_child_name("shaper_page");
+            }
+            PenStyle::EraserStyle => {
+                self.mainheader().eraser_toggle().set_active(true);
+                self.penssidebar()
+                    .sidebar_stack()
+                    .set_visible_child_name("eraser_page");
+            }
+            PenStyle::SelectorStyle => {
+                self.mainheader().selector_toggle().set_active(true);
+                self.penssidebar()
+                    .sidebar_stack()
+                    .set_visible_child_name("selector_page");
+            }
+            PenStyle::ToolsStyle => {
+                self.mainheader().tools_toggle().set_active(true);
+                self.penssidebar()
+                    .sidebar_stack()
+                    .set_visible_child_name("tools_page");
+            }
+        }
+    }
+
+    /// The index of the page which is currently presented in presentation mode
+    pub fn presentation_page(&self) -> u32 {
+        self.imp().presentation_page.get()
+    }
+
+    /// Presents the page with the given index, zooming the canvas so that it fits into the view
+    pub fn present_page(&self, page: u32) {
+        let pages_bounds = self.canvas().sheet().borrow().gen_pages_bounds();
+        let page = page.min(pages_bounds.len().saturating_sub(1) as u32);
+        let page_bounds = match pages_bounds.get(page as usize) {
+            Some(page_bounds) => *page_bounds,
+            None => return,
+        };
+        self.imp().presentation_page.set(page);
+
+        let page_extents = page_bounds.extents();
+        let new_zoom = (f64::from(self.canvas_scroller().width()) / page_extents[0])
+            .min(f64::from(self.canvas_scroller().height()) / page_extents[1])
+            .clamp(Canvas::ZOOM_MIN, Canvas::ZOOM_MAX);
+
+        self.canvas().set_presented_page(Some(page_bounds));
+        self.canvas().zoom_to(new_zoom);
+
+        // Center after the canvas was resized to the new zoom
+        glib::idle_add_local_once(clone!(@weak self as appwindow => move || {
+            appwindow.canvas().center_around_coord_on_sheet(page_bounds.center().coords);
+        }));
+    }
+
+    /// transforming coordinates in the canvas scroller (e.g. of gestures) into the unrotated canvas coordinate space
+    fn canvas_scroller_coords_to_unrotated_canvas_coords(&self, coords: (f64, f64)) -> (f64, f64) {
+        let coords = self
+            .canvas_scroller()
+            .translate_coordinates(&self.canvas(), coords.0, coords.1)
+            .unwrap_or(coords);
+        let coords = self
+            .canvas()
+            .view_rotation_isometry()
+            .inverse_transform_point(&na::point![coords.0, coords.1]);
+
+        (coords[0], coords[1])
+    }
+
     pub fn open_file_w_dialogs(&self, file: &gio::File, target_pos: Option<na::Vector2<f64>>) {
         let app = self.application().unwrap().downcast::<RnoteApp>().unwrap();
         match utils::FileType::lookup_file_type(file) {
@@ -934,7 +1093,7 @@ impl RnoteAppWindow {
         self.canvas()
             .sheet()
             .borrow_mut()
-            .open_sheet_from_rnote_bytes(bytes)?;
+            .open_sheet_from_rnote_bytes(&bytes)?;
 
         // Loading the sheet properties into the format settings panel
         self.settings_panel().refresh_for_sheet(self);
@@ -972,7 +1131,7 @@ impl RnoteAppWindow {
         self.canvas()
             .sheet()
             .borrow_mut()
-            .open_from_xopp_bytes(bytes)?;
+            .open_from_xopp_bytes(&bytes)?;
 
         // Loading the sheet properties into the format settings panel
         self.settings_panel().refresh_for_sheet(self);
@@ -1,6 +1,7 @@
 mod imp {
-    use gtk4::gdk;
     use gtk4::glib;
+    use gtk4::graphene;
+    use gtk4::gsk;
     use gtk4::prelude::*;
     use gtk4::subclass::prelude::*;
     use gtk4::LayoutManager;
@@ -127,14 +128,24 @@ mod imp {
                     (0, 0)
                 };
 
-                canvas_priv.selection_modifier.size_allocate(
-                    &gdk::Rectangle::new(
-                        selection_modifier_x,
-                        selection_modifier_y,
-                        selection_modifier_width,
-                        selection_modifier_height,
-                    ),
+                // The modifier is positioned in the unrotated canvas coordinate space and rotated around the view center like the sheet
+                let view_center = canvas.view_center();
+                let transform = gsk::Transform::new()
+                    .translate(&graphene::Point::new(
+                        view_center[0] as f32,
+                        view_center[1] as f32,
+                    ))
+                    .rotate(canvas.rotation().to_degrees() as f32)
+                    .translate(&graphene::Point::new(
+                        (f64::from(selection_modifier_x) - view_center[0]) as f32,
+                        (f64::from(selection_modifier_y) - view_center[1]) as f32,
+                    ));
+
+                canvas_priv.selection_modifier.allocate(
+                    selection_modifier_width,
+                    selection_modifier_height,
                     -1,
+                    Some(&transform),
                 );
             }
         }
@@ -4,6 +4,7 @@ mod imp {
     use std::cell::{Cell, RefCell};
     use std::rc::Rc;
     use std::sync::{Arc, RwLock};
+    use std::time;
 
     use super::canvaslayout::CanvasLayout;
     use super::debug;
@@ -13,6 +14,7 @@ mod imp {
     use crate::pens::{PenStyle, Pens};
     use crate::render::Renderer;
     use crate::sheet::Sheet;
+    use crate::ui::colorpicker::ColorPicker;
     use crate::ui::selectionmodifier::SelectionModifier;
 
     use gtk4::{
@@ -38,15 +40,22 @@ mod imp {
         pub cursor: gdk::Cursor,
         pub motion_cursor: gdk::Cursor,
         pub stylus_drawing_gesture: GestureStylus,
+        pub stylus_button_gesture: GestureStylus,
         pub mouse_drawing_gesture: GestureDrag,
         pub touch_drawing_gesture: GestureDrag,
         pub selection_modifier: SelectionModifier,
 
         pub pens: Rc<RefCell<Pens>>,
         pub pen_shown: Cell<bool>,
+        pub temporary_pen_prev: Cell<Option<PenStyle>>,
+        pub stylus_last_activity: Cell<Option<time::Instant>>,
+        pub touch_stroke_active: Cell<bool>,
         pub sheet: Rc<RefCell<Sheet>>,
         pub zoom: Cell<f64>,
         pub temporary_zoom: Cell<f64>,
+        pub rotation: Cell<f64>,
+        pub presented_page: Cell<Option<AABB>>,
+        pub eyedropper: RefCell<Option<ColorPicker>>,
         pub visual_debug: Cell<bool>,
         pub unsaved_changes: Cell<bool>,
         pub empty: Cell<bool>,
@@ -54,6 +63,10 @@ mod imp {
         // State that is saved in settings
         pub sheet_margin: Cell<i32>,
         pub touch_drawing: Cell<bool>,
+        pub touch_rotation: Cell<bool>,
+        pub palm_rejection: Cell<bool>,
+        pub palm_rejection_timeout: Cell<f64>,
+        pub palm_rejection_contact_threshold: Cell<f64>,
         pub endless_sheet: Cell<bool>,
         pub format_borders: Cell<bool>,
         pub pdf_import_width: Cell<f64>,
@@ -67,6 +80,13 @@ mod imp {
                 .propagation_phase(PropagationPhase::Target)
                 .build();
 
+            // Handles the stylus side buttons. It does not claim its sequences, so the drawing gesture still receives the tip presses
+            let stylus_button_gesture = GestureStylus::builder()
+                .name("stylus_button_gesture")
+                .button(0)
+                .propagation_phase(PropagationPhase::Target)
+                .build();
+
             // mouse gesture handlers have a guard to not handle emulated pointer events ( e.g. coming from touch input )
             // matching different input methods with gdk4::InputSource or gdk4::DeviceToolType did NOT WORK unfortunately, dont know why
             let mouse_drawing_gesture = GestureDrag::builder()
@@ -116,6 +136,7 @@ mod imp {
                 cursor,
                 motion_cursor,
                 stylus_drawing_gesture,
+                stylus_button_gesture,
                 mouse_drawing_gesture,
                 touch_drawing_gesture,
                 zoom_timeout_id: RefCell::new(None),
@@ -124,16 +145,28 @@ mod imp {
 
                 pens: Rc::new(RefCell::new(Pens::default())),
                 pen_shown: Cell::new(false),
+                temporary_pen_prev: Cell::new(None),
+                stylus_last_activity: Cell::new(None),
+                touch_stroke_active: Cell::new(false),
                 sheet: Rc::new(RefCell::new(Sheet::default())),
 
                 zoom: Cell::new(super::Canvas::ZOOM_DEFAULT),
                 temporary_zoom: Cell::new(1.0),
+                rotation: Cell::new(0.0),
+                presented_page: Cell::new(None),
+                eyedropper: RefCell::new(None),
                 visual_debug: Cell::new(false),
                 unsaved_changes: Cell::new(false),
                 empty: Cell::new(true),
 
                 sheet_margin: Cell::new(super::Canvas::SHEET_MARGIN_DEFAULT),
                 touch_drawing: Cell::new(false),
+                touch_rotation: Cell::new(false),
+                palm_rejection: Cell::new(true),
+                palm_rejection_timeout: Cell::new(super::Canvas::PALM_REJECTION_TIMEOUT_DEFAULT),
+                palm_rejection_contact_threshold: Cell::new(
+                    super::Canvas::PALM_REJECTION_CONTACT_THRESHOLD_DEFAULT,
+                ),
                 endless_sheet: Cell::new(true),
                 format_borders: Cell::new(true),
                 pdf_import_width: Cell::new(super::Canvas::PDF_IMPORT_WIDTH_DEFAULT),
@@ -172,6 +205,7 @@ mod imp {
             obj.set_cursor(Some(&self.cursor));
 
             obj.add_controller(&self.stylus_drawing_gesture);
+            obj.add_controller(&self.stylus_button_gesture);
             obj.add_controller(&self.mouse_drawing_gesture);
             obj.add_controller(&self.touch_drawing_gesture);
         }
@@ -212,6 +246,16 @@ mod imp {
                         1.0,
                         glib::ParamFlags::READWRITE,
                     ),
+                    // The rotation of the view in radians, around the center of the view
+                    glib::ParamSpecDouble::new(
+                        "rotation",
+                        "rotation",
+                        "rotation",
+                        f64::MIN,
+                        f64::MAX,
+                        0.0,
+                        glib::ParamFlags::READWRITE,
+                    ),
                     // Visual debugging, which shows bounding boxes, hitboxes, ... (enable in developer action menu)
                     glib::ParamSpecBoolean::new(
                         "visual-debug",
@@ -270,6 +314,42 @@ mod imp {
                         false,
                         glib::ParamFlags::READWRITE,
                     ),
+                    // Wether the view can be rotated with a two finger touch gesture
+                    glib::ParamSpecBoolean::new(
+                        "touch-rotation",
+                        "touch-rotation",
+                        "touch-rotation",
+                        false,
+                        glib::ParamFlags::READWRITE,
+                    ),
+                    // Wether touches are ignored while the stylus is in use
+                    glib::ParamSpecBoolean::new(
+                        "palm-rejection",
+                        "palm-rejection",
+                        "palm-rejection",
+                        true,
+                        glib::ParamFlags::READWRITE,
+                    ),
+                    // How long (ms) touches are ignored after the last stylus activity
+                    glib::ParamSpecDouble::new(
+                        "palm-rejection-timeout",
+                        "palm-rejection-timeout",
+                        "palm-rejection-timeout",
+                        0.0,
+                        5000.0,
+                        super::Canvas::PALM_REJECTION_TIMEOUT_DEFAULT,
+                        glib::ParamFlags::READWRITE,
+                    ),
+                    // Touches with a larger contact are ignored. 1.0 disables it
+                    glib::ParamSpecDouble::new(
+                        "palm-rejection-contact-threshold",
+                        "palm-rejection-contact-threshold",
+                        "palm-rejection-contact-threshold",
+                        0.0,
+                        1.0,
+                        super::Canvas::PALM_REJECTION_CONTACT_THRESHOLD_DEFAULT,
+                        glib::ParamFlags::READWRITE,
+                    ),
                     // import PDFs with with in percentage to sheet width
                     glib::ParamSpecDouble::new(
                         "pdf-import-width",
@@ -303,6 +383,7 @@ mod imp {
                 "pen-shown" => self.sheet_margin.get().to_value(),
                 "zoom" => self.zoom.get().to_value(),
                 "temporary-zoom" => self.temporary_zoom.get().to_value(),
+                "rotation" => self.rotation.get().to_value(),
                 "visual-debug" => self.visual_debug.get().to_value(),
                 "unsaved-changes" => self.unsaved_changes.get().to_value(),
                 "empty" => self.empty.get().to_value(),
@@ -314,6 +395,12 @@ mod imp {
                 "endless-sheet" => self.endless_sheet.get().to_value(),
                 "format-borders" => self.format_borders.get().to_value(),
                 "touch-drawing" => self.touch_drawing.get().to_value(),
+                "touch-rotation" => self.touch_rotation.get().to_value(),
+                "palm-rejection" => self.palm_rejection.get().to_value(),
+                "palm-rejection-timeout" => self.palm_rejection_timeout.get().to_value(),
+                "palm-rejection-contact-threshold" => {
+                    self.palm_rejection_contact_threshold.get().to_value()
+                }
                 "pdf-import-width" => self.pdf_import_width.get().to_value(),
                 "pdf-import-as-vector" => self.pdf_import_as_vector.get().to_value(),
                 _ => unimplemented!(),
@@ -359,6 +446,20 @@ mod imp {
                     obj.queue_resize();
                     obj.queue_draw();
                 }
+                "rotation" => {
+                    let rotation = value
+                        .get::<f64>()
+                        .expect("The value needs to be of type `f64`.");
+                    // Normalized to the range -PI..PI
+                    let rotation = (rotation + std::f64::consts::PI)
+                        .rem_euclid(2.0 * std::f64::consts::PI)
+                        - std::f64::consts::PI;
+                    self.rotation.replace(rotation);
+
+                    self.selection_modifier.update_state(obj);
+                    obj.queue_resize();
+                    obj.queue_draw();
+                }
                 "visual-debug" => {
                     let visual_debug: bool =
                         value.get().expect("The value needs to be of type `bool`.");
@@ -432,6 +533,33 @@ mod imp {
                             .set_propagation_phase(PropagationPhase::None);
                     }
                 }
+                "touch-rotation" => {
+                    let touch_rotation: bool =
+                        value.get().expect("The value needs to be of type `bool`.");
+                    self.touch_rotation.replace(touch_rotation);
+                }
+                "palm-rejection" => {
+                    let palm_rejection: bool =
+                        value.get().expect("The value needs to be of type `bool`.");
+                    self.palm_rejection.replace(palm_rejection);
+                }
+                "palm-rejection-timeout" => {
+                    let palm_rejection_timeout = value
+                        .get::<f64>()
+                        .expect("The value needs to be of type `f64`.")
+                        .clamp(0.0, 5000.0);
+
+                    self.palm_rejection_timeout.replace(palm_rejection_timeout);
+                }
+                "palm-rejection-contact-threshold" => {
+                    let palm_rejection_contact_threshold = value
+                        .get::<f64>()
+                        .expect("The value needs to be of type `f64`.")
+                        .clamp(0.0, 1.0);
+
+                    self.palm_rejection_contact_threshold
+                        .replace(palm_rejection_contact_threshold);
+                }
                 "pdf-import-width" => {
                     let pdf_import_width = value
                         .get::<f64>()
@@ -484,6 +612,21 @@ mod imp {
 
             snapshot.save();
 
+            // Rotate the view around its center
+            let rotation = widget.rotation();
+            if rotation != 0.0 {
+                let view_center = widget.view_center();
+                snapshot.translate(&graphene::Point::new(
+                    view_center[0] as f32,
+                    view_center[1] as f32,
+                ));
+                snapshot.rotate(rotation.to_degrees() as f32);
+                snapshot.translate(&graphene::Point::new(
+                    -view_center[0] as f32,
+                    -view_center[1] as f32,
+                ));
+            }
+
             snapshot.translate(&graphene::Point::new(
                 (-hadj.value()) as f32,
                 (-vadj.value()) as f32,
@@ -497,15 +640,21 @@ mod imp {
                 (f64::from(sheet_margin) * zoom) as f32,
             ));
 
+            // When presenting, only the presented page is visible
+            let visible_bounds = self
+                .presented_page
+                .get()
+                .unwrap_or_else(|| widget.sheet().borrow().bounds());
+
             self.draw_shadow(
-                geometry::aabb_scale(widget.sheet().borrow().bounds(), zoom),
+                geometry::aabb_scale(visible_bounds, zoom),
                 f64::from(sheet_margin) * zoom,
                 snapshot,
             );
 
-            // Clip sheet and stroke drawing to sheet bounds
+            // Clip sheet and stroke drawing to the visible bounds
             snapshot.push_clip(&geometry::aabb_to_graphene_rect(geometry::aabb_scale(
-                widget.sheet().borrow().bounds(),
+                visible_bounds,
                 zoom,
             )));
 
@@ -525,6 +674,34 @@ mod imp {
                 .strokes_state
                 .draw_selection(zoom, snapshot);
 
+            self.draw_snap_guides(snapshot, zoom);
+
+            // The ruler stays visible while other pens are used
+            if let Err(e) = self.pens.borrow_mut().tools.ruler_tool.draw(
+                self.sheet.borrow().format.dpi,
+                zoom,
+                snapshot,
+                widget.renderer(),
+            ) {
+                log::debug!(
+                    "ruler_tool draw() failed in canvas snapshot() with Err {}",
+                    e
+                );
+            }
+
+            // Laser trails and vanishing ink fade out while other pens are used
+            if let Err(e) = self
+                .pens
+                .borrow_mut()
+                .tools
+                .draw_fading(zoom, snapshot, widget.renderer())
+            {
+                log::debug!(
+                    "tools draw_fading() failed in canvas snapshot() with Err {}",
+                    e
+                );
+            }
+
             if self.pen_shown.get() {
                 if let Err(e) = self.pens.borrow().current_pen.draw(widget, snapshot) {
                     log::debug!("pens draw() failed in canvas snapshot() with Err {}", e);
@@ -576,6 +753,22 @@ mod imp {
             );
         }
 
+        /// Draws the guides of the lines the selection is snapped to while it is translated
+        fn draw_snap_guides(&self, snapshot: &Snapshot, zoom: f64) {
+            for guide in self.selection_modifier.snap_guides() {
+                let bounds = geometry::aabb_new_positive(
+                    na::Point2::from(guide.start * zoom),
+                    na::Point2::from(guide.end * zoom),
+                )
+                .loosened(SelectionModifier::SNAP_GUIDE_WIDTH * 0.5);
+
+                snapshot.append_color(
+                    &SelectionModifier::SNAP_GUIDE_COLOR.to_gdk(),
+                    &geometry::aabb_to_graphene_rect(bounds),
+                );
+            }
+        }
+
         // Draw bounds, positions, .. for visual debugging purposes
         fn draw_debug(&self, widget: &super::Canvas, snapshot: &Snapshot, zoom: f64) {
             if self.pen_shown.get() {
@@ -624,12 +817,20 @@ mod imp {
     }
 }
 
+use crate::compose::color::Color;
 use crate::compose::geometry;
 use crate::input;
 use crate::render::Renderer;
-use crate::strokes::strokestyle::InputData;
+use crate::strokes::strokestyle::{InputData, StyleChange};
+use crate::ui::colorpicker::ColorPicker;
 use crate::ui::selectionmodifier::SelectionModifier;
-use crate::{app::RnoteApp, pens::Pens, render, sheet::Sheet, ui::appwindow::RnoteAppWindow};
+use crate::{
+    app::RnoteApp,
+    pens::{PenStyle, Pens},
+    render,
+    sheet::Sheet,
+    ui::appwindow::RnoteAppWindow,
+};
 
 use std::cell::RefCell;
 use std::collections::VecDeque;
@@ -663,6 +864,11 @@ impl Canvas {
     pub const SHEET_MARGIN_DEFAULT: i32 = 32;
     // The default width of imported PDF's in percentage to the sheet width
     pub const PDF_IMPORT_WIDTH_DEFAULT: f64 = 50.0;
+    /// ms
+    pub const PALM_REJECTION_TIMEOUT_DEFAULT: f64 = 500.0;
+    pub const PALM_REJECTION_CONTACT_THRESHOLD_DEFAULT: f64 = 0.8;
+    /// The rotation snaps to multiples of 90 degrees within this threshold (in radians)
+    pub const ROTATION_SNAP_THRESHOLD: f64 = 0.08;
 
     pub fn new() -> Self {
         let canvas: Canvas = glib::Object::new(&[]).expect("Failed to create Canvas");
@@ -695,6 +901,28 @@ impl Canvas {
         self.set_property("pen-shown", pen_shown);
     }
 
+    /// Switches to the pen until reset_temporary_pen() is called and refreshes the pen UI.
+    /// The previous pen is kept if a temporary pen is already set, e.g. by the stylus and the `tmperaser` action at the same time
+    pub fn set_temporary_pen(&self, pen_style: PenStyle, appwindow: &RnoteAppWindow) {
+        if self.imp().temporary_pen_prev.get().is_none() {
+            self.imp()
+                .temporary_pen_prev
+                .set(Some(self.pens().borrow().current_pen));
+        }
+        self.pens().borrow_mut().current_pen = pen_style;
+
+        appwindow.refresh_current_pen_ui();
+    }
+
+    /// Switches back to the pen before set_temporary_pen() was called and refreshes the pen UI
+    pub fn reset_temporary_pen(&self, appwindow: &RnoteAppWindow) {
+        if let Some(pen_style) = self.imp().temporary_pen_prev.take() {
+            self.pens().borrow_mut().current_pen = pen_style;
+
+            appwindow.refresh_current_pen_ui();
+        }
+    }
+
     /// Only change the sheet state in actions to avoid nested mutable borrows!
     pub fn sheet(&self) -> Rc<RefCell<Sheet>> {
         Rc::clone(&imp::Canvas::from_instance(self).sheet)
@@ -740,6 +968,65 @@ impl Canvas {
         self.set_property("temporary-zoom", temporary_zoom.to_value());
     }
 
+    pub fn rotation(&self) -> f64 {
+        self.property::<f64>("rotation")
+    }
+
+    pub fn set_rotation(&self, rotation: f64) {
+        self.set_property("rotation", rotation.to_value());
+    }
+
+    /// Rotates the view and regenerates the content in the now visible area
+    pub fn rotate_to(&self, rotation: f64) {
+        self.set_rotation(rotation);
+        self.regenerate_content(false, true);
+    }
+
+    /// Snaps the rotation to multiples of 90 degrees, if it is within the snap threshold
+    pub fn snap_rotation(rotation: f64) -> f64 {
+        let snapped =
+            (rotation / std::f64::consts::FRAC_PI_2).round() * std::f64::consts::FRAC_PI_2;
+
+        if (rotation - snapped).abs() < Self::ROTATION_SNAP_THRESHOLD {
+            snapped
+        } else {
+            rotation
+        }
+    }
+
+    pub fn touch_rotation(&self) -> bool {
+        self.property::<bool>("touch-rotation")
+    }
+
+    pub fn set_touch_rotation(&self, touch_rotation: bool) {
+        self.set_property("touch-rotation", touch_rotation.to_value());
+    }
+
+    /// The page which is presented in presentation mode, in sheet coordinates. Drawing is clipped to it
+    pub fn presented_page(&self) -> Option<AABB> {
+        self.imp().presented_page.get()
+    }
+
+    pub fn set_presented_page(&self, presented_page: Option<AABB>) {
+        self.imp().presented_page.set(presented_page);
+        self.queue_draw();
+    }
+
+    /// The colorpicker which receives the color picked with the eyedropper. While it is Some, pen input picks colors instead of drawing
+    pub fn eyedropper(&self) -> Option<ColorPicker> {
+        self.imp().eyedropper.borrow().clone()
+    }
+
+    pub fn set_eyedropper(&self, eyedropper: Option<ColorPicker>) {
+        if eyedropper.is_some() {
+            self.set_cursor(gdk::Cursor::from_name("crosshair", Some(&self.cursor())).as_ref());
+        } else {
+            self.set_cursor(Some(&self.cursor()));
+        }
+
+        self.imp().eyedropper.replace(eyedropper);
+    }
+
     pub fn pdf_import_width(&self) -> f64 {
         self.property::<f64>("pdf-import-width")
     }
@@ -780,6 +1067,64 @@ impl Canvas {
         self.set_property("touch-drawing", touch_drawing.to_value());
     }
 
+    pub fn palm_rejection(&self) -> bool {
+        self.property::<bool>("palm-rejection")
+    }
+
+    pub fn set_palm_rejection(&self, palm_rejection: bool) {
+        self.set_property("palm-rejection", palm_rejection.to_value());
+    }
+
+    pub fn palm_rejection_timeout(&self) -> f64 {
+        self.property::<f64>("palm-rejection-timeout")
+    }
+
+    pub fn set_palm_rejection_timeout(&self, palm_rejection_timeout: f64) {
+        self.set_property("palm-rejection-timeout", palm_rejection_timeout.to_value());
+    }
+
+    pub fn palm_rejection_contact_threshold(&self) -> f64 {
+        self.property::<f64>("palm-rejection-contact-threshold")
+    }
+
+    pub fn set_palm_rejection_contact_threshold(&self, palm_rejection_contact_threshold: f64) {
+        self.set_property(
+            "palm-rejection-contact-threshold",
+            palm_rejection_contact_threshold.to_value(),
+        );
+    }
+
+    /// Records stylus activity (proximity or input), which is used for palm rejection
+    pub fn register_stylus_activity(&self) {
+        self.imp()
+            .stylus_last_activity
+            .set(Some(time::Instant::now()));
+    }
+
+    /// Wether the touch of the event should be ignored, because the stylus is in use or the contact is too large.
+    /// Touchscreens usually report the size of the contact on the pressure axis
+    pub fn palm_rejection_rejects_touch(&self, event: Option<&gdk::Event>) -> bool {
+        if !self.palm_rejection() {
+            return false;
+        }
+
+        let timeout = time::Duration::from_secs_f64(self.palm_rejection_timeout() / 1000.0);
+        let stylus_active = self.imp().stylus_drawing_gesture.is_active()
+            || self
+                .imp()
+                .stylus_last_activity
+                .get()
+                .map_or(false, |last_activity| last_activity.elapsed() < timeout);
+
+        let large_contact = event
+            .and_then(|event| event.axis(gdk::AxisUse::Pressure))
+            .map_or(false, |pressure| {
+                pressure > self.palm_rejection_contact_threshold()
+            });
+
+        stylus_active || large_contact
+    }
+
     pub fn visual_debug(&self) -> bool {
         self.property::<bool>("visual-debug")
     }
@@ -872,25 +1217,61 @@ impl Canvas {
         )
         .build();
 
+        // Eyedroppers of the colorpickers. Only one can be active at a time
+        for colorpicker in [
+            appwindow.penssidebar().marker_page().colorpicker(),
+            appwindow.penssidebar().brush_page().colorpicker(),
+            appwindow.penssidebar().shaper_page().stroke_colorpicker(),
+            appwindow.penssidebar().shaper_page().fill_colorpicker(),
+            appwindow.penssidebar().tools_page().fill_colorpicker(),
+        ] {
+            colorpicker.eyedropper_button().connect_toggled(
+                clone!(@weak self as canvas, @weak colorpicker => move |eyedropper_button| {
+                    let prev_eyedropper = canvas.eyedropper();
+
+                    if eyedropper_button.is_active() {
+                        canvas.set_eyedropper(Some(colorpicker.clone()));
+
+                        if let Some(prev_eyedropper) = prev_eyedropper {
+                            if prev_eyedropper != colorpicker {
+                                prev_eyedropper.eyedropper_button().set_active(false);
+                            }
+                        }
+                    } else if prev_eyedropper.as_ref() == Some(&colorpicker) {
+                        canvas.set_eyedropper(None);
+                    }
+                }),
+            );
+        }
+
         // Stylus Drawing
+        self.imp().stylus_drawing_gesture.connect_proximity(
+            clone!(@weak self as canvas => move |_stylus_drawing_gesture, _x, _y| {
+                canvas.register_stylus_activity();
+            }),
+        );
+
         self.imp().stylus_drawing_gesture.connect_down(clone!(@weak self as canvas, @weak appwindow => move |stylus_drawing_gesture,x,y| {
             if let Some(device_tool) = stylus_drawing_gesture.device_tool() {
                 stylus_drawing_gesture.set_state(EventSequenceState::Claimed);
+                canvas.register_stylus_activity();
+
+                // A touch stroke in progress is most likely the palm resting on the screen
+                if canvas.palm_rejection() && canvas.imp().touch_stroke_active.replace(false) {
+                    input::process_peninput_cancel(&appwindow);
+                }
 
                 // Disable backlog, only allowed in motion signal handler
                 let mut data_entries = input::retreive_stylus_inputdata(stylus_drawing_gesture, false, x, y);
 
-                input::map_inputdata(canvas.zoom(), &mut data_entries, canvas.transform_canvas_coords_to_sheet_coords(na::vector![0.0, 0.0]));
+                input::map_inputdata(&canvas, &mut data_entries, na::vector![0.0, 0.0]);
 
                 match device_tool.tool_type() {
                     gdk::DeviceToolType::Pen => { },
                     gdk::DeviceToolType::Eraser => {
-
-                    gtk4::prelude::ActionGroupExt::activate_action(
-                        &appwindow,
-                        "tmperaser",
-                        Some(&true.to_variant())
-                    );
+                        if canvas.pens().borrow().stylus_config.eraser_auto_switch {
+                            canvas.set_temporary_pen(PenStyle::EraserStyle, &appwindow);
+                        }
                     }
                     _ => { return; },
                 }
@@ -901,17 +1282,49 @@ impl Canvas {
 
         self.imp().stylus_drawing_gesture.connect_motion(clone!(@weak self as canvas, @weak appwindow => move |stylus_drawing_gesture, x, y| {
             // backlog doesn't provide time equidistant inputdata and makes line look worse, so its disabled for now
+            canvas.register_stylus_activity();
+
             let mut data_entries: VecDeque<InputData> = input::retreive_stylus_inputdata(stylus_drawing_gesture, false, x, y);
-            input::map_inputdata(canvas.zoom(), &mut data_entries, canvas.transform_canvas_coords_to_sheet_coords(na::vector![0.0, 0.0]));
+            input::map_inputdata(&canvas, &mut data_entries, na::vector![0.0, 0.0]);
             input::process_peninput_motion(&appwindow, data_entries);
         }));
 
         self.imp().stylus_drawing_gesture.connect_up(
             clone!(@weak self as canvas, @weak appwindow => move |gesture_stylus,x,y| {
+                canvas.register_stylus_activity();
+
                 let mut data_entries = input::retreive_stylus_inputdata(gesture_stylus, false, x, y);
 
-                input::map_inputdata(canvas.zoom(), &mut data_entries, na::vector![0.0, 0.0]);
+                input::map_inputdata(&canvas, &mut data_entries, na::vector![0.0, 0.0]);
                 input::process_peninput_end(&appwindow, data_entries);
+
+                // Keep the temporary pen while a side button is still held down
+                let modifiers = gesture_stylus.current_event_state();
+                if !modifiers.intersects(gdk::ModifierType::BUTTON2_MASK | gdk::ModifierType::BUTTON3_MASK) {
+                    canvas.reset_temporary_pen(&appwindow);
+                }
+            }),
+        );
+
+        // Stylus side buttons
+        self.imp().stylus_button_gesture.connect_down(clone!(@weak self as canvas, @weak appwindow => move |stylus_button_gesture, _x, _y| {
+            let button_action = canvas.pens().borrow().stylus_config.button_action(stylus_button_gesture.current_button());
+
+            if let Some(button_action) = button_action {
+                if let Some(pen_style) = button_action.temporary_pen() {
+                    canvas.set_temporary_pen(pen_style, &appwindow);
+                } else if let Some(action_name) = button_action.action_name() {
+                    adw::prelude::ActionGroupExt::activate_action(&appwindow, action_name, None);
+                }
+            }
+        }));
+
+        self.imp().stylus_button_gesture.connect_up(
+            clone!(@weak self as canvas, @weak appwindow => move |_stylus_button_gesture, _x, _y| {
+                // If the tip is still down, the pen is reset when it is lifted
+                if !canvas.pen_shown() {
+                    canvas.reset_temporary_pen(&appwindow);
+                }
             }),
         );
 
@@ -927,7 +1340,7 @@ impl Canvas {
                     mouse_drawing_gesture.set_state(EventSequenceState::Claimed);
 
                     let mut data_entries = input::retreive_pointer_inputdata(x, y);
-                    input::map_inputdata(canvas.zoom(), &mut data_entries, canvas.transform_canvas_coords_to_sheet_coords(na::vector![0.0, 0.0]));
+                    input::map_inputdata(&canvas, &mut data_entries, na::vector![0.0, 0.0]);
                     input::process_peninput_start(&appwindow, data_entries);
                 }
             }),
@@ -942,7 +1355,7 @@ impl Canvas {
 
                 if let Some(start_point) = mouse_drawing_gesture.start_point() {
                     let mut data_entries = input::retreive_pointer_inputdata(x, y);
-                    input::map_inputdata(canvas.zoom(), &mut data_entries, canvas.transform_canvas_coords_to_sheet_coords(na::vector![start_point.0, start_point.1]));
+                    input::map_inputdata(&canvas, &mut data_entries, na::vector![start_point.0, start_point.1]);
                     input::process_peninput_motion(&appwindow, data_entries);
                 }
             }
@@ -959,7 +1372,7 @@ impl Canvas {
 
                     if let Some(start_point) = mouse_drawing_gesture.start_point() {
                         let mut data_entries = input::retreive_pointer_inputdata(x, y);
-                        input::map_inputdata(canvas.zoom(), &mut data_entries, canvas.transform_canvas_coords_to_sheet_coords(na::vector![start_point.0, start_point.1]));
+                        input::map_inputdata(&canvas, &mut data_entries, na::vector![start_point.0, start_point.1]);
                         input::process_peninput_end(&appwindow, data_entries);
                     }
                 }
@@ -969,28 +1382,42 @@ impl Canvas {
         // Touch drawing
         self.imp().touch_drawing_gesture.connect_drag_begin(
             clone!(@weak self as canvas, @weak appwindow => move |touch_drawing_gesture, x, y| {
+                if canvas.palm_rejection_rejects_touch(touch_drawing_gesture.current_event().as_ref()) {
+                    touch_drawing_gesture.set_state(EventSequenceState::Denied);
+                    return;
+                }
                 touch_drawing_gesture.set_state(EventSequenceState::Claimed);
+                canvas.imp().touch_stroke_active.set(true);
 
                 let mut data_entries = input::retreive_pointer_inputdata(x, y);
-                input::map_inputdata(canvas.zoom(), &mut data_entries, canvas.transform_canvas_coords_to_sheet_coords(na::vector![0.0, 0.0]));
+                input::map_inputdata(&canvas, &mut data_entries, na::vector![0.0, 0.0]);
 
                 input::process_peninput_start(&appwindow, data_entries);
             }),
         );
 
         self.imp().touch_drawing_gesture.connect_drag_update(clone!(@weak self as canvas, @weak appwindow => move |touch_drawing_gesture, x, y| {
+            // The stroke was rejected or canceled by palm rejection
+            if !canvas.imp().touch_stroke_active.get() {
+                return;
+            }
+
             if let Some(start_point) = touch_drawing_gesture.start_point() {
                 let mut data_entries = input::retreive_pointer_inputdata(x, y);
-                input::map_inputdata(canvas.zoom(), &mut data_entries, canvas.transform_canvas_coords_to_sheet_coords(na::vector![start_point.0, start_point.1]));
+                input::map_inputdata(&canvas, &mut data_entries, na::vector![start_point.0, start_point.1]);
                 input::process_peninput_motion(&appwindow, data_entries);
             }
         }));
 
         self.imp().touch_drawing_gesture.connect_drag_end(
             clone!(@weak self as canvas @weak appwindow => move |touch_drawing_gesture, x, y| {
+                if !canvas.imp().touch_stroke_active.replace(false) {
+                    return;
+                }
+
                 if let Some(start_point) = touch_drawing_gesture.start_point() {
                     let mut data_entries = input::retreive_pointer_inputdata(x, y);
-                    input::map_inputdata(canvas.zoom(), &mut data_entries, canvas.transform_canvas_coords_to_sheet_coords(na::vector![start_point.0, start_point.1]));
+                    input::map_inputdata(&canvas, &mut data_entries, na::vector![start_point.0, start_point.1]);
                     input::process_peninput_end(&appwindow, data_entries);
                 }
             }),
@@ -1049,11 +1476,52 @@ impl Canvas {
         )
     }
 
+    /// The center of the view in the coordinate space of the canvas. The view is rotated around it
+    pub fn view_center(&self) -> na::Point2<f64> {
+        if let Some(parent) = self.parent() {
+            let (parent_offset_x, parent_offset_y) = parent
+                .translate_coordinates(self, 0.0, 0.0)
+                .unwrap_or((0.0, 0.0));
+
+            na::point![
+                parent_offset_x + f64::from(parent.width()) * 0.5,
+                parent_offset_y + f64::from(parent.height()) * 0.5
+            ]
+        } else {
+            self.bounds().center()
+        }
+    }
+
+    /// The isometry which rotates the unrotated canvas coordinate space into the rotated view
+    pub fn view_rotation_isometry(&self) -> na::Isometry2<f64> {
+        geometry::rotation_around_center(self.rotation(), self.view_center())
+    }
+
+    /// transforming a movement in the (rotated) view into a movement in the unrotated canvas coordinate space
+    pub fn transform_view_delta_to_canvas_delta(
+        &self,
+        delta: na::Vector2<f64>,
+    ) -> na::Vector2<f64> {
+        na::Rotation2::new(-self.rotation()) * delta
+    }
+
     /// transforming a AABB in canvas coordinate space into sheet coordinate space
     pub fn transform_canvas_aabb_to_sheet(&self, aabb: AABB) -> AABB {
-        let mins = na::Point2::from(self.transform_canvas_coords_to_sheet_coords(aabb.mins.coords));
-        let maxs = na::Point2::from(self.transform_canvas_coords_to_sheet_coords(aabb.maxs.coords));
-        AABB::new(mins, maxs)
+        let aabb = aabb.transform_by(&self.view_rotation_isometry().inverse());
+
+        geometry::aabb_translate(
+            geometry::aabb_scale(
+                geometry::aabb_translate(
+                    aabb,
+                    na::vector![
+                        self.hadjustment().unwrap().value(),
+                        self.vadjustment().unwrap().value()
+                    ],
+                ),
+                1.0 / self.total_zoom(),
+            ),
+            -na::Vector2::from_element(f64::from(self.sheet_margin())),
+        )
     }
 
     /// transforming coordinates in canvas coordinate space into sheet coordinate space
@@ -1062,6 +1530,10 @@ impl Canvas {
         canvas_coords: na::Vector2<f64>,
     ) -> na::Vector2<f64> {
         let total_zoom = self.total_zoom();
+        let canvas_coords = self
+            .view_rotation_isometry()
+            .inverse_transform_point(&na::Point2::from(canvas_coords))
+            .coords;
 
         (canvas_coords
             + na::vector![
@@ -1074,9 +1546,20 @@ impl Canvas {
 
     /// transforming a AABB in sheet coordinate space into canvas coordinate space
     pub fn transform_sheet_aabb_to_canvas(&self, aabb: AABB) -> AABB {
-        let mins = na::Point2::from(self.transform_sheet_coords_to_canvas_coords(aabb.mins.coords));
-        let maxs = na::Point2::from(self.transform_sheet_coords_to_canvas_coords(aabb.maxs.coords));
-        AABB::new(mins, maxs)
+        geometry::aabb_translate(
+            geometry::aabb_scale(
+                geometry::aabb_translate(
+                    aabb,
+                    na::Vector2::from_element(f64::from(self.sheet_margin())),
+                ),
+                self.total_zoom(),
+            ),
+            -na::vector![
+                self.hadjustment().unwrap().value(),
+                self.vadjustment().unwrap().value()
+            ],
+        )
+        .transform_by(&self.view_rotation_isometry())
     }
 
     /// transforming coordinates in sheet coordinate space into canvas coordinate space
@@ -1086,11 +1569,16 @@ impl Canvas {
     ) -> na::Vector2<f64> {
         let total_zoom = self.total_zoom();
 
-        (sheet_coords + na::Vector2::from_element(f64::from(self.sheet_margin()))) * total_zoom
-            - na::vector![
-                self.hadjustment().unwrap().value(),
-                self.vadjustment().unwrap().value()
-            ]
+        let canvas_coords =
+            (sheet_coords + na::Vector2::from_element(f64::from(self.sheet_margin()))) * total_zoom
+                - na::vector![
+                    self.hadjustment().unwrap().value(),
+                    self.vadjustment().unwrap().value()
+                ];
+
+        self.view_rotation_isometry()
+            .transform_point(&na::Point2::from(canvas_coords))
+            .coords
     }
 
     /// The view of the parent scroller onto the Canvas
@@ -1112,12 +1600,17 @@ impl Canvas {
         )
     }
 
-    /// The viewport transformed to match the coordinate space of the sheet
+    /// The viewport transformed to match the coordinate space of the sheet. When the view is rotated, it is the bounds of the rotated viewport
     pub fn viewport_in_sheet_coords(&self) -> AABB {
         let mut viewport = self.viewport();
         let total_zoom = self.total_zoom();
         let sheet_margin = f64::from(self.sheet_margin());
 
+        viewport = viewport.transform_by(&geometry::rotation_around_center(
+            -self.rotation(),
+            viewport.center(),
+        ));
+
         viewport = geometry::aabb_translate(
             geometry::aabb_scale(viewport, 1.0 / total_zoom),
             -na::Vector2::from_element(sheet_margin),
@@ -1202,12 +1695,8 @@ impl Canvas {
         self.set_temporary_zoom(1.0);
         self.set_zoom(zoom);
 
-        self.sheet()
-            .borrow_mut()
-            .strokes_state
-            .reset_regenerate_flag_all_strokes();
-
-        // update rendernodes to new zoom until threaded regeneration is finished
+        // update rendernodes to new zoom until threaded regeneration is finished.
+        // Only the strokes in the viewport are upgraded to the new detail level, the others once they are scrolled into view
         self.update_content_rendernodes(false);
 
         self.regenerate_background(false);
@@ -1315,6 +1804,63 @@ impl Canvas {
         }
     }
 
+    /// Applies the style change to the selected strokes and regenerates their rendering
+    pub fn restyle_selection(&self, change: StyleChange) {
+        if self.sheet().borrow().strokes_state.selection_len() == 0 {
+            return;
+        }
+
+        self.sheet()
+            .borrow_mut()
+            .strokes_state
+            .restyle_selection(change);
+
+        self.set_unsaved_changes(true);
+        self.selection_modifier().update_state(self);
+        self.regenerate_content(false, true);
+    }
+
+    /// Samples the color of the rendered canvas at the given position in sheet coordinates. None if nothing is drawn there
+    pub fn sample_color_at(&self, pos: na::Vector2<f64>) -> Option<Color> {
+        let canvas_pos = na::Point2::from(self.transform_sheet_coords_to_canvas_coords(pos));
+        let snapshot = Snapshot::new();
+
+        self.selection_modifier().set_visible(false);
+        self.imp().snapshot(self, &snapshot);
+        self.selection_modifier().update_state(self);
+
+        let rendernode = snapshot.to_node()?;
+        let texture = render::rendernode_to_texture(
+            self.upcast_ref::<Widget>(),
+            &rendernode,
+            Some(AABB::new(canvas_pos, canvas_pos + na::vector![1.0, 1.0])),
+        )
+        .unwrap_or_else(|e| {
+            log::error!(
+                "rendernode_to_texture() in sample_color_at() failed with Err {}",
+                e
+            );
+            None
+        })?;
+
+        // Downloaded textures are premultiplied ARGB32 in native endianness
+        let mut data = [0_u8; 4];
+        texture.download(&mut data, 4);
+        let pixel = u32::from_ne_bytes(data);
+
+        let alpha = f64::from((pixel >> 24) & 0xff) / 255.0;
+        if alpha == 0.0 {
+            return None;
+        }
+
+        Some(Color {
+            r: (f64::from((pixel >> 16) & 0xff) / 255.0 / alpha).min(1.0),
+            g: (f64::from((pixel >> 8) & 0xff) / 255.0 / alpha).min(1.0),
+            b: (f64::from(pixel & 0xff) / 255.0 / alpha).min(1.0),
+            a: alpha,
+        })
+    }
+
     /// Captures the current view of the canvas as a gdk::Texture
     pub fn current_view_as_texture(&self) -> Option<gdk::Texture> {
         let snapshot = Snapshot::new();
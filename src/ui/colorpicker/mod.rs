@@ -9,7 +9,7 @@ mod imp {
     use gtk4::{
         gdk, glib, glib::clone, glib::translate::IntoGlib, prelude::*, subclass::prelude::*, Box,
         Button, ColorChooserWidget, CompositeTemplate, MenuButton, Orientation, Popover,
-        PositionType, Widget,
+        PositionType, ToggleButton, Widget,
     };
     use gtk4::{Align, BoxLayout};
 
@@ -25,6 +25,8 @@ mod imp {
         #[template_child]
         pub colorpicker_button: TemplateChild<MenuButton>,
         #[template_child]
+        pub eyedropper_button: TemplateChild<ToggleButton>,
+        #[template_child]
         pub colorpicker_popover: TemplateChild<Popover>,
         #[template_child]
         pub colorchooser: TemplateChild<ColorChooserWidget>,
@@ -46,6 +48,7 @@ mod imp {
                 setterbox: TemplateChild::<Box>::default(),
                 currentcolor_setter1: TemplateChild::<ColorSetter>::default(),
                 colorpicker_button: TemplateChild::<MenuButton>::default(),
+                eyedropper_button: TemplateChild::<ToggleButton>::default(),
                 colorpicker_popover: TemplateChild::<Popover>::default(),
                 colorchooser: TemplateChild::<ColorChooserWidget>::default(),
                 colorchooser_editor_gobackbutton: TemplateChild::<Button>::default(),
@@ -229,6 +232,10 @@ mod imp {
                             self.colorpicker_button.set_margin_end(0);
                             self.colorpicker_button.set_margin_top(6);
                             self.colorpicker_button.set_margin_bottom(0);
+                            self.eyedropper_button.set_margin_start(0);
+                            self.eyedropper_button.set_margin_end(0);
+                            self.eyedropper_button.set_margin_top(6);
+                            self.eyedropper_button.set_margin_bottom(0);
                         }
                         PositionType::Right => {
                             self.colorpicker_popover.set_position(PositionType::Left);
@@ -238,6 +245,10 @@ mod imp {
                             self.colorpicker_button.set_margin_end(0);
                             self.colorpicker_button.set_margin_top(6);
                             self.colorpicker_button.set_margin_bottom(0);
+                            self.eyedropper_button.set_margin_start(0);
+                            self.eyedropper_button.set_margin_end(0);
+                            self.eyedropper_button.set_margin_top(6);
+                            self.eyedropper_button.set_margin_bottom(0);
                         }
                         PositionType::Top => {
                             self.colorpicker_popover.set_position(PositionType::Bottom);
@@ -247,6 +258,10 @@ mod imp {
                             self.colorpicker_button.set_margin_end(0);
                             self.colorpicker_button.set_margin_top(0);
                             self.colorpicker_button.set_margin_bottom(0);
+                            self.eyedropper_button.set_margin_start(6);
+                            self.eyedropper_button.set_margin_end(0);
+                            self.eyedropper_button.set_margin_top(0);
+                            self.eyedropper_button.set_margin_bottom(0);
                         }
                         PositionType::Bottom => {
                             self.colorpicker_popover.set_position(PositionType::Top);
@@ -256,6 +271,10 @@ mod imp {
                             self.colorpicker_button.set_margin_end(0);
                             self.colorpicker_button.set_margin_top(0);
                             self.colorpicker_button.set_margin_bottom(0);
+                            self.eyedropper_button.set_margin_start(6);
+                            self.eyedropper_button.set_margin_end(0);
+                            self.eyedropper_button.set_margin_top(0);
+                            self.eyedropper_button.set_margin_bottom(0);
                         }
                         _ => {}
                     }
@@ -368,7 +387,9 @@ mod imp {
     }
 }
 
-use gtk4::{gdk, glib, prelude::*, subclass::prelude::*, Orientable, PositionType, Widget};
+use gtk4::{
+    gdk, glib, prelude::*, subclass::prelude::*, Orientable, PositionType, ToggleButton, Widget,
+};
 
 use crate::compose::color::Color;
 
@@ -412,6 +433,10 @@ impl ColorPicker {
         self.set_property("current-color", color.to_gdk().to_value());
     }
 
+    pub fn eyedropper_button(&self) -> ToggleButton {
+        self.imp().eyedropper_button.get()
+    }
+
     pub fn amount_colorbuttons(&self) -> u32 {
         self.property::<u32>("amount-colorbuttons")
     }
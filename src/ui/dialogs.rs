@@ -4,7 +4,10 @@ use gtk4::{
     AboutDialog, FileChooserAction, FileChooserNative, FileFilter, MessageDialog, ResponseType,
     ShortcutsWindow, gio,
 };
+use gtk4::{FlowBox, FlowBoxChild, Label, Orientation, Picture};
 
+use crate::pens::presets::PenPresets;
+use crate::render;
 use crate::ui::appwindow::RnoteAppWindow;
 use crate::utils;
 use crate::{app::RnoteApp, config};
@@ -171,6 +174,83 @@ pub fn dialog_open_overwrite(appwindow: &RnoteAppWindow) {
     dialog_open_input_file.show();
 }
 
+/// The width of the page thumbnails in the page overview
+const PAGE_OVERVIEW_THUMBNAIL_WIDTH: f64 = 200.0;
+
+pub fn dialog_page_overview(appwindow: &RnoteAppWindow) {
+    let builder =
+        Builder::from_resource((String::from(config::APP_IDPATH) + "ui/dialogs.ui").as_str());
+    let dialog_page_overview: adw::Window = builder.object("dialog_page_overview").unwrap();
+    let page_overview_flowbox: FlowBox = builder.object("page_overview_flowbox").unwrap();
+
+    dialog_page_overview.set_transient_for(Some(appwindow));
+
+    let (pages_bounds, sheet_svgs) = {
+        let sheet = appwindow.canvas().sheet();
+        let sheet = sheet.borrow();
+
+        let sheet_svgs = sheet.gen_svgs().unwrap_or_else(|e| {
+            log::error!("gen_svgs() failed in dialog_page_overview() with Err {}", e);
+            vec![]
+        });
+        (sheet.gen_pages_bounds(), sheet_svgs)
+    };
+
+    for (i, page_bounds) in pages_bounds.iter().enumerate() {
+        let page_box = gtk4::Box::new(Orientation::Vertical, 6);
+        let thumbnail = Picture::new();
+        thumbnail.set_can_shrink(false);
+
+        let zoom = PAGE_OVERVIEW_THUMBNAIL_WIDTH / page_bounds.extents()[0];
+        match appwindow
+            .canvas()
+            .renderer()
+            .read()
+            .unwrap()
+            .gen_image(zoom, &sheet_svgs, *page_bounds)
+        {
+            Ok(Some(image)) => match render::image_to_memtexture(&image) {
+                Ok(texture) => thumbnail.set_paintable(Some(&texture)),
+                Err(e) => log::error!(
+                    "image_to_memtexture() failed in dialog_page_overview() with Err {}",
+                    e
+                ),
+            },
+            Ok(None) => {}
+            Err(e) => {
+                log::error!("gen_image() failed in dialog_page_overview() with Err {}", e)
+            }
+        }
+
+        page_box.append(&thumbnail);
+        page_box.append(&Label::new(Some(&(i + 1).to_string())));
+
+        let page_child = FlowBoxChild::new();
+        page_child.set_child(Some(&page_box));
+        page_overview_flowbox.insert(&page_child, -1);
+
+        if i as u32 == appwindow.presentation_page() {
+            page_overview_flowbox.select_child(&page_child);
+        }
+    }
+
+    page_overview_flowbox.connect_child_activated(
+        clone!(@weak appwindow, @weak dialog_page_overview => move |_page_overview_flowbox, page_child| {
+            let page = page_child.index().max(0) as u32;
+
+            if appwindow.canvas().presented_page().is_some() {
+                appwindow.present_page(page);
+            } else if let Some(page_bounds) = appwindow.canvas().sheet().borrow().gen_pages_bounds().get(page as usize) {
+                appwindow.canvas().center_around_coord_on_sheet(page_bounds.center().coords);
+            }
+
+            dialog_page_overview.close();
+        }),
+    );
+
+    dialog_page_overview.show();
+}
+
 // FileChooserNative Dialogs
 
 pub fn dialog_open_sheet(appwindow: &RnoteAppWindow) {
@@ -560,3 +640,94 @@ pub fn dialog_export_sheet_as_xopp(appwindow: &RnoteAppWindow) {
     // keeping the filechooser around because otherwise GTK won't keep it alive
     *appwindow.filechoosernative().borrow_mut() = Some(dialog_export_sheet);
 }
+
+pub fn dialog_import_pen_presets(appwindow: &RnoteAppWindow) {
+    let filter = FileFilter::new();
+    filter.add_mime_type("application/json");
+    filter.add_pattern("*.json");
+    filter.set_name(Some(&gettext("JSON file")));
+
+    let dialog_import_pen_presets: FileChooserNative = FileChooserNative::builder()
+        .title(&gettext("Import Pen Presets"))
+        .modal(true)
+        .transient_for(appwindow)
+        .accept_label(&gettext("Import"))
+        .cancel_label(&gettext("Cancel"))
+        .action(FileChooserAction::Open)
+        .select_multiple(false)
+        .build();
+    dialog_import_pen_presets.add_filter(&filter);
+
+    dialog_import_pen_presets.connect_response(
+        clone!(@weak appwindow => move |dialog_import_pen_presets, responsetype| {
+            match responsetype {
+                ResponseType::Accept => {
+                    match dialog_import_pen_presets.file() {
+                        Some(file) => {
+                            match PenPresets::import_from_file(&file) {
+                                Ok(presets) => {
+                                    appwindow.canvas().pens().borrow_mut().presets.merge(presets);
+                                    adw::prelude::ActionGroupExt::activate_action(&appwindow, "refresh-ui-for-sheet", None);
+                                }
+                                Err(e) => {
+                                    log::error!("importing pen presets failed with error `{}`", e);
+                                    adw::prelude::ActionGroupExt::activate_action(&appwindow, "error", Some(&gettext("Importing pen presets failed").to_variant()));
+                                }
+                            }
+                        },
+                        None => { log::error!("Unable to import pen presets. No file selected.")},
+                    }
+                }
+                _ => {
+                }
+            }
+        }),
+    );
+
+    dialog_import_pen_presets.show();
+    // keeping the filechooser around because otherwise GTK won't keep it alive
+    *appwindow.filechoosernative().borrow_mut() = Some(dialog_import_pen_presets);
+}
+
+pub fn dialog_export_pen_presets(appwindow: &RnoteAppWindow) {
+    let filter = FileFilter::new();
+    filter.add_mime_type("application/json");
+    filter.add_pattern("*.json");
+    filter.set_name(Some(&gettext("JSON file")));
+
+    let dialog_export_pen_presets: FileChooserNative = FileChooserNative::builder()
+        .title(&gettext("Export Pen Presets"))
+        .modal(true)
+        .transient_for(appwindow)
+        .accept_label(&gettext("Export"))
+        .cancel_label(&gettext("Cancel"))
+        .action(FileChooserAction::Save)
+        .select_multiple(false)
+        .build();
+    dialog_export_pen_presets.add_filter(&filter);
+
+    dialog_export_pen_presets.set_current_name(format!("{}_pen_presets.json", utils::now_formatted_string()).as_str());
+
+    dialog_export_pen_presets.connect_response(
+        clone!(@weak appwindow => move |dialog_export_pen_presets, responsetype| {
+            match responsetype {
+                ResponseType::Accept => {
+                    match dialog_export_pen_presets.file() {
+                        Some(file) => {
+                            if let Err(e) = appwindow.canvas().pens().borrow().presets.export_to_file(&file) {
+                                log::error!("exporting pen presets failed with error `{}`", e);
+                            }
+                        },
+                        None => { log::error!("Can't export pen presets. No file selected.")},
+                    }
+                }
+                _ => {
+                }
+            }
+        }),
+    );
+
+    dialog_export_pen_presets.show();
+    // keeping the filechooser around because otherwise GTK won't keep it alive
+    *appwindow.filechoosernative().borrow_mut() = Some(dialog_export_pen_presets);
+}
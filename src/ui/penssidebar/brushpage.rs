@@ -21,10 +21,16 @@ mod imp {
         #[template_child]
         pub brushstyle_textured_row: TemplateChild<adw::ActionRow>,
         #[template_child]
+        pub brushstyle_calligraphy_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub brushstyle_pencil_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
         pub styleconfig_menubutton: TemplateChild<MenuButton>,
         #[template_child]
         pub styleconfig_popover: TemplateChild<Popover>,
         #[template_child]
+        pub texturedstyle_listbox: TemplateChild<ListBox>,
+        #[template_child]
         pub texturedstyle_density_spinbutton: TemplateChild<SpinButton>,
         #[template_child]
         pub texturedstyle_radius_x_spinbutton: TemplateChild<SpinButton>,
@@ -32,6 +38,20 @@ mod imp {
         pub texturedstyle_radius_y_spinbutton: TemplateChild<SpinButton>,
         #[template_child]
         pub texturedstyle_distribution_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub calligraphystyle_listbox: TemplateChild<ListBox>,
+        #[template_child]
+        pub calligraphystyle_nib_angle_spinbutton: TemplateChild<SpinButton>,
+        #[template_child]
+        pub calligraphystyle_nib_thickness_spinbutton: TemplateChild<SpinButton>,
+        #[template_child]
+        pub calligraphystyle_tilt_sensitivity_spinbutton: TemplateChild<SpinButton>,
+        #[template_child]
+        pub pencilstyle_listbox: TemplateChild<ListBox>,
+        #[template_child]
+        pub pencilstyle_grain_density_spinbutton: TemplateChild<SpinButton>,
+        #[template_child]
+        pub pencilstyle_grain_radius_spinbutton: TemplateChild<SpinButton>,
     }
 
     #[glib::object_subclass]
@@ -64,7 +84,9 @@ mod imp {
     impl WidgetImpl for BrushPage {}
 }
 
+use crate::compose::calligraphy::CalligraphyOptions;
 use crate::compose::color::Color;
+use crate::compose::pencil::PencilOptions;
 use crate::compose::textured::{TexturedDotsDistribution, TexturedOptions};
 use crate::pens::brush::BrushStyle;
 use crate::ui::{appwindow::RnoteAppWindow, colorpicker::ColorPicker};
@@ -132,6 +154,18 @@ impl BrushPage {
             .get()
     }
 
+    pub fn brushstyle_calligraphy_row(&self) -> adw::ActionRow {
+        imp::BrushPage::from_instance(self)
+            .brushstyle_calligraphy_row
+            .get()
+    }
+
+    pub fn brushstyle_pencil_row(&self) -> adw::ActionRow {
+        imp::BrushPage::from_instance(self)
+            .brushstyle_pencil_row
+            .get()
+    }
+
     pub fn styleconfig_menubutton(&self) -> MenuButton {
         imp::BrushPage::from_instance(self)
             .styleconfig_menubutton
@@ -144,6 +178,12 @@ impl BrushPage {
             .get()
     }
 
+    pub fn texturedstyle_listbox(&self) -> ListBox {
+        imp::BrushPage::from_instance(self)
+            .texturedstyle_listbox
+            .get()
+    }
+
     pub fn texturedstyle_distribution_row(&self) -> adw::ComboRow {
         imp::BrushPage::from_instance(self)
             .texturedstyle_distribution_row
@@ -168,6 +208,48 @@ impl BrushPage {
             .clone()
     }
 
+    pub fn calligraphystyle_listbox(&self) -> ListBox {
+        imp::BrushPage::from_instance(self)
+            .calligraphystyle_listbox
+            .get()
+    }
+
+    pub fn calligraphystyle_nib_angle_spinbutton(&self) -> SpinButton {
+        imp::BrushPage::from_instance(self)
+            .calligraphystyle_nib_angle_spinbutton
+            .get()
+    }
+
+    pub fn calligraphystyle_nib_thickness_spinbutton(&self) -> SpinButton {
+        imp::BrushPage::from_instance(self)
+            .calligraphystyle_nib_thickness_spinbutton
+            .get()
+    }
+
+    pub fn calligraphystyle_tilt_sensitivity_spinbutton(&self) -> SpinButton {
+        imp::BrushPage::from_instance(self)
+            .calligraphystyle_tilt_sensitivity_spinbutton
+            .get()
+    }
+
+    pub fn pencilstyle_listbox(&self) -> ListBox {
+        imp::BrushPage::from_instance(self)
+            .pencilstyle_listbox
+            .get()
+    }
+
+    pub fn pencilstyle_grain_density_spinbutton(&self) -> SpinButton {
+        imp::BrushPage::from_instance(self)
+            .pencilstyle_grain_density_spinbutton
+            .get()
+    }
+
+    pub fn pencilstyle_grain_radius_spinbutton(&self) -> SpinButton {
+        imp::BrushPage::from_instance(self)
+            .pencilstyle_grain_radius_spinbutton
+            .get()
+    }
+
     pub fn set_texturedstyle_distribution_variant(&self, distribution: TexturedDotsDistribution) {
         let texturedstyle_distribution_listmodel = self
             .imp()
@@ -199,6 +281,8 @@ impl BrushPage {
                 match brush_style {
                     BrushStyle::Solid => appwindow.canvas().pens().borrow_mut().brush.smooth_options.stroke_color = Some(color),
                     BrushStyle::Textured => appwindow.canvas().pens().borrow_mut().brush.textured_options.stroke_color = Some(color),
+                    BrushStyle::Calligraphy => appwindow.canvas().pens().borrow_mut().brush.calligraphy_options.stroke_color = Some(color),
+                    BrushStyle::Pencil => appwindow.canvas().pens().borrow_mut().brush.pencil_options.stroke_color = Some(color),
                 }
             }),
         );
@@ -210,6 +294,8 @@ impl BrushPage {
                 match brush_style {
                     BrushStyle::Solid => appwindow.canvas().pens().borrow_mut().brush.smooth_options.width = brush_widthscale_spinbutton.value(),
                     BrushStyle::Textured => appwindow.canvas().pens().borrow_mut().brush.textured_options.width = brush_widthscale_spinbutton.value(),
+                    BrushStyle::Calligraphy => appwindow.canvas().pens().borrow_mut().brush.calligraphy_options.width = brush_widthscale_spinbutton.value(),
+                    BrushStyle::Pencil => appwindow.canvas().pens().borrow_mut().brush.pencil_options.width = brush_widthscale_spinbutton.value(),
                 }
             }),
         );
@@ -229,6 +315,27 @@ impl BrushPage {
                             adw::prelude::ActionGroupExt::activate_action(&appwindow, "brush-style", Some(&"textured".to_variant()));
                             brushpage.brushstyle_image().set_icon_name(Some("pen-brush-style-textured-symbolic"));
                             brushpage.styleconfig_menubutton().set_sensitive(true);
+                            brushpage.texturedstyle_listbox().set_visible(true);
+                            brushpage.calligraphystyle_listbox().set_visible(false);
+                            brushpage.pencilstyle_listbox().set_visible(false);
+                        }
+                        // Calligraphy
+                        2 => {
+                            adw::prelude::ActionGroupExt::activate_action(&appwindow, "brush-style", Some(&"calligraphy".to_variant()));
+                            brushpage.brushstyle_image().set_icon_name(Some("pen-brush-style-calligraphy-symbolic"));
+                            brushpage.styleconfig_menubutton().set_sensitive(true);
+                            brushpage.texturedstyle_listbox().set_visible(false);
+                            brushpage.calligraphystyle_listbox().set_visible(true);
+                            brushpage.pencilstyle_listbox().set_visible(false);
+                        }
+                        // Pencil
+                        3 => {
+                            adw::prelude::ActionGroupExt::activate_action(&appwindow, "brush-style", Some(&"pencil".to_variant()));
+                            brushpage.brushstyle_image().set_icon_name(Some("pen-brush-style-pencil-symbolic"));
+                            brushpage.styleconfig_menubutton().set_sensitive(true);
+                            brushpage.texturedstyle_listbox().set_visible(false);
+                            brushpage.calligraphystyle_listbox().set_visible(false);
+                            brushpage.pencilstyle_listbox().set_visible(true);
                         }
                         _ => {}
                     }
@@ -348,5 +455,77 @@ impl BrushPage {
                 appwindow.canvas().regenerate_background(true);
             }
         }));
+
+        // Calligraphy style
+        // Nib angle, displayed in degrees
+        self.calligraphystyle_nib_angle_spinbutton()
+            .set_increments(1.0, 15.0);
+        self.calligraphystyle_nib_angle_spinbutton()
+            .set_range(0.0, 180.0);
+        self.calligraphystyle_nib_angle_spinbutton()
+            .set_value(CalligraphyOptions::NIB_ANGLE_DEFAULT.to_degrees());
+
+        self.calligraphystyle_nib_angle_spinbutton().connect_value_changed(
+            clone!(@weak appwindow => move |calligraphystyle_nib_angle_spinbutton| {
+                appwindow.canvas().pens().borrow_mut().brush.calligraphy_options.nib_angle = calligraphystyle_nib_angle_spinbutton.value().to_radians();
+            }),
+        );
+
+        // Nib thickness
+        self.calligraphystyle_nib_thickness_spinbutton()
+            .set_increments(0.01, 0.1);
+        self.calligraphystyle_nib_thickness_spinbutton()
+            .set_range(0.0, 1.0);
+        self.calligraphystyle_nib_thickness_spinbutton()
+            .set_value(CalligraphyOptions::NIB_THICKNESS_DEFAULT);
+
+        self.calligraphystyle_nib_thickness_spinbutton().connect_value_changed(
+            clone!(@weak appwindow => move |calligraphystyle_nib_thickness_spinbutton| {
+                appwindow.canvas().pens().borrow_mut().brush.calligraphy_options.nib_thickness = calligraphystyle_nib_thickness_spinbutton.value();
+            }),
+        );
+
+        // Tilt sensitivity
+        self.calligraphystyle_tilt_sensitivity_spinbutton()
+            .set_increments(0.05, 0.25);
+        self.calligraphystyle_tilt_sensitivity_spinbutton()
+            .set_range(0.0, 1.0);
+        self.calligraphystyle_tilt_sensitivity_spinbutton()
+            .set_value(CalligraphyOptions::TILT_SENSITIVITY_DEFAULT);
+
+        self.calligraphystyle_tilt_sensitivity_spinbutton().connect_value_changed(
+            clone!(@weak appwindow => move |calligraphystyle_tilt_sensitivity_spinbutton| {
+                appwindow.canvas().pens().borrow_mut().brush.calligraphy_options.tilt_sensitivity = calligraphystyle_tilt_sensitivity_spinbutton.value();
+            }),
+        );
+
+        // Pencil style
+        // Grain density
+        self.pencilstyle_grain_density_spinbutton()
+            .set_increments(1.0, 10.0);
+        self.pencilstyle_grain_density_spinbutton()
+            .set_range(1.0, 1000.0);
+        self.pencilstyle_grain_density_spinbutton()
+            .set_value(PencilOptions::GRAIN_DENSITY_DEFAULT);
+
+        self.pencilstyle_grain_density_spinbutton().connect_value_changed(
+            clone!(@weak appwindow => move |pencilstyle_grain_density_spinbutton| {
+                appwindow.canvas().pens().borrow_mut().brush.pencil_options.grain_density = pencilstyle_grain_density_spinbutton.value();
+            }),
+        );
+
+        // Grain radius
+        self.pencilstyle_grain_radius_spinbutton()
+            .set_increments(0.05, 0.25);
+        self.pencilstyle_grain_radius_spinbutton()
+            .set_range(0.05, 5.0);
+        self.pencilstyle_grain_radius_spinbutton()
+            .set_value(PencilOptions::GRAIN_RADIUS_DEFAULT);
+
+        self.pencilstyle_grain_radius_spinbutton().connect_value_changed(
+            clone!(@weak appwindow => move |pencilstyle_grain_radius_spinbutton| {
+                appwindow.canvas().pens().borrow_mut().brush.pencil_options.grain_radius = pencilstyle_grain_radius_spinbutton.value();
+            }),
+        );
     }
 }
@@ -1,6 +1,8 @@
 mod imp {
     use crate::ui::colorpicker::ColorPicker;
-    use gtk4::{glib, prelude::*, subclass::prelude::*, CompositeTemplate, SpinButton};
+    use gtk4::{
+        glib, prelude::*, subclass::prelude::*, CompositeTemplate, SpinButton, ToggleButton,
+    };
 
     #[derive(Default, Debug, CompositeTemplate)]
     #[template(resource = "/com/github/flxzt/rnote/ui/penssidebar/markerpage.ui")]
@@ -9,6 +11,8 @@ mod imp {
         pub width_spinbutton: TemplateChild<SpinButton>,
         #[template_child]
         pub colorpicker: TemplateChild<ColorPicker>,
+        #[template_child]
+        pub highlighter_togglebutton: TemplateChild<ToggleButton>,
     }
 
     #[glib::object_subclass]
@@ -44,7 +48,8 @@ mod imp {
 use crate::compose::color::Color;
 use crate::ui::{appwindow::RnoteAppWindow, colorpicker::ColorPicker};
 use gtk4::{
-    gdk, glib, glib::clone, prelude::*, subclass::prelude::*, Orientable, SpinButton, Widget,
+    gdk, glib, glib::clone, prelude::*, subclass::prelude::*, Orientable, SpinButton, ToggleButton,
+    Widget,
 };
 
 glib::wrapper! {
@@ -78,6 +83,12 @@ impl MarkerPage {
         imp::MarkerPage::from_instance(self).colorpicker.get()
     }
 
+    pub fn highlighter_togglebutton(&self) -> ToggleButton {
+        imp::MarkerPage::from_instance(self)
+            .highlighter_togglebutton
+            .get()
+    }
+
     pub fn init(&self, appwindow: &RnoteAppWindow) {
         self.width_spinbutton().set_increments(0.1, 2.0);
         self.width_spinbutton()
@@ -98,5 +109,11 @@ impl MarkerPage {
                 appwindow.canvas().pens().borrow_mut().marker.options.width = width_spinbutton.value();
             }),
         );
+
+        self.highlighter_togglebutton().connect_toggled(
+            clone!(@weak appwindow => move |highlighter_togglebutton| {
+                appwindow.canvas().pens().borrow_mut().marker.highlighter = highlighter_togglebutton.is_active();
+            }),
+        );
     }
 }
@@ -13,12 +13,23 @@ mod imp {
     };
 
     use gtk4::{
-        glib, prelude::*, subclass::prelude::*, CompositeTemplate, Stack, StackPage, Widget,
+        glib, prelude::*, subclass::prelude::*, Button, CompositeTemplate, Entry, ListBox,
+        MenuButton, Popover, Stack, StackPage, Widget,
     };
 
     #[derive(Default, Debug, CompositeTemplate)]
     #[template(resource = "/com/github/flxzt/rnote/ui/penssidebar/penssidebar.ui")]
     pub struct PensSideBar {
+        #[template_child]
+        pub presets_menubutton: TemplateChild<MenuButton>,
+        #[template_child]
+        pub presets_popover: TemplateChild<Popover>,
+        #[template_child]
+        pub presets_listbox: TemplateChild<ListBox>,
+        #[template_child]
+        pub presets_name_entry: TemplateChild<Entry>,
+        #[template_child]
+        pub presets_save_button: TemplateChild<Button>,
         #[template_child]
         pub sidebar_stack: TemplateChild<Stack>,
         #[template_child]
@@ -76,6 +87,7 @@ mod imp {
     impl WidgetImpl for PensSideBar {}
 }
 
+use crate::pens::presets::{PenConfig, PenPresets};
 use crate::ui::appwindow::RnoteAppWindow;
 
 use brushpage::BrushPage;
@@ -84,7 +96,11 @@ use markerpage::MarkerPage;
 use selectorpage::SelectorPage;
 use shaperpage::ShaperPage;
 
-use gtk4::{glib, glib::clone, prelude::*, subclass::prelude::*, Stack, StackPage, Widget};
+use gettextrs::gettext;
+use gtk4::{
+    glib, glib::clone, prelude::*, subclass::prelude::*, Align, Button, Entry, ListBox,
+    MenuButton, Popover, Stack, StackPage, Widget,
+};
 
 use self::toolspage::ToolsPage;
 
@@ -106,6 +122,32 @@ impl PensSideBar {
         penssidebar
     }
 
+    pub fn presets_menubutton(&self) -> MenuButton {
+        imp::PensSideBar::from_instance(self)
+            .presets_menubutton
+            .get()
+    }
+
+    pub fn presets_popover(&self) -> Popover {
+        imp::PensSideBar::from_instance(self).presets_popover.get()
+    }
+
+    pub fn presets_listbox(&self) -> ListBox {
+        imp::PensSideBar::from_instance(self).presets_listbox.get()
+    }
+
+    pub fn presets_name_entry(&self) -> Entry {
+        imp::PensSideBar::from_instance(self)
+            .presets_name_entry
+            .get()
+    }
+
+    pub fn presets_save_button(&self) -> Button {
+        imp::PensSideBar::from_instance(self)
+            .presets_save_button
+            .get()
+    }
+
     pub fn sidebar_stack(&self) -> Stack {
         imp::PensSideBar::from_instance(self).sidebar_stack.get()
     }
@@ -157,6 +199,24 @@ impl PensSideBar {
     }
 
     pub fn init(&self, appwindow: &RnoteAppWindow) {
+        self.presets_save_button().connect_clicked(
+            clone!(@weak self as penssidebar, @weak appwindow => move |_presets_save_button| {
+                let name = penssidebar.presets_name_entry().text().trim().to_string();
+                if name.is_empty() {
+                    return;
+                }
+
+                adw::prelude::ActionGroupExt::activate_action(&appwindow, "pen-preset-save-current", Some(&name.to_variant()));
+                penssidebar.presets_name_entry().set_text("");
+            }),
+        );
+
+        self.presets_name_entry().connect_activate(
+            clone!(@weak self as penssidebar => move |_presets_name_entry| {
+                penssidebar.presets_save_button().emit_clicked();
+            }),
+        );
+
         self.imp().sidebar_stack.get().connect_visible_child_name_notify(
             clone!(@weak appwindow => move |sidebar_stack| {
                 if let Some(child_name) = sidebar_stack.visible_child_name() {
@@ -185,4 +245,49 @@ impl PensSideBar {
             }),
         );
     }
+
+    /// Rebuilds the presets list from the given presets
+    pub fn refresh_presets(&self, presets: &PenPresets, appwindow: &RnoteAppWindow) {
+        let presets_listbox = self.presets_listbox();
+
+        while let Some(row) = presets_listbox.first_child() {
+            presets_listbox.remove(&row);
+        }
+
+        for preset in presets.presets() {
+            let (icon_name, subtitle) = match preset.config {
+                PenConfig::Marker(_) => ("pen-marker-symbolic", gettext("Marker")),
+                PenConfig::Brush(_) => ("pen-brush-symbolic", gettext("Brush")),
+                PenConfig::Shaper(_) => ("pen-shaper-symbolic", gettext("Shaper")),
+            };
+
+            let row = adw::ActionRow::builder()
+                .title(&preset.name)
+                .subtitle(&subtitle)
+                .icon_name(icon_name)
+                .activatable(true)
+                .build();
+
+            let remove_button = Button::builder()
+                .icon_name("edit-delete-symbolic")
+                .tooltip_text(&gettext("Remove preset"))
+                .valign(Align::Center)
+                .build();
+            remove_button.add_css_class("flat");
+            row.add_suffix(&remove_button);
+
+            let name = preset.name.clone();
+            row.connect_activated(
+                clone!(@weak self as penssidebar, @weak appwindow, @strong name => move |_row| {
+                    penssidebar.presets_popover().popdown();
+                    adw::prelude::ActionGroupExt::activate_action(&appwindow, "pen-preset-apply", Some(&name.to_variant()));
+                }),
+            );
+            remove_button.connect_clicked(clone!(@weak appwindow => move |_remove_button| {
+                adw::prelude::ActionGroupExt::activate_action(&appwindow, "pen-preset-remove", Some(&name.to_variant()));
+            }));
+
+            presets_listbox.append(&row);
+        }
+    }
 }
@@ -1,6 +1,10 @@
 mod imp {
-    use gtk4::ToggleButton;
+    use crate::strokes::strokestyle::StyleChange;
+    use crate::ui::colorpicker::ColorPicker;
+    use crate::ui::unitentry::UnitEntry;
     use gtk4::{glib, prelude::*, subclass::prelude::*, CompositeTemplate};
+    use gtk4::{Button, MenuButton, Popover, SpinButton, Switch, ToggleButton};
+    use std::cell::{Cell, RefCell};
 
     #[derive(Default, Debug, CompositeTemplate)]
     #[template(resource = "/com/github/flxzt/rnote/ui/penssidebar/selectorpage.ui")]
@@ -10,7 +14,56 @@ mod imp {
         #[template_child]
         pub selectorstyle_rect_toggle: TemplateChild<ToggleButton>,
         #[template_child]
+        pub selectorcontainment_contained_toggle: TemplateChild<ToggleButton>,
+        #[template_child]
+        pub selectorcontainment_touching_toggle: TemplateChild<ToggleButton>,
+        #[template_child]
         pub resize_lock_aspectratio_togglebutton: TemplateChild<ToggleButton>,
+        #[template_child]
+        pub transform_popover: TemplateChild<Popover>,
+        #[template_child]
+        pub transform_x_unitentry: TemplateChild<UnitEntry>,
+        #[template_child]
+        pub transform_y_unitentry: TemplateChild<UnitEntry>,
+        #[template_child]
+        pub transform_width_unitentry: TemplateChild<UnitEntry>,
+        #[template_child]
+        pub transform_height_unitentry: TemplateChild<UnitEntry>,
+        #[template_child]
+        pub transform_lock_aspectratio_switch: TemplateChild<Switch>,
+        #[template_child]
+        pub transform_rotation_spinbutton: TemplateChild<SpinButton>,
+        #[template_child]
+        pub transform_apply_button: TemplateChild<Button>,
+        /// Set while the transform entries are updated programmatically
+        pub transform_entries_updating: Cell<bool>,
+        #[template_child]
+        pub skew_popover: TemplateChild<Popover>,
+        #[template_child]
+        pub skew_horizontal_spinbutton: TemplateChild<SpinButton>,
+        #[template_child]
+        pub skew_vertical_spinbutton: TemplateChild<SpinButton>,
+        #[template_child]
+        pub skew_apply_button: TemplateChild<Button>,
+        #[template_child]
+        pub restyle_menubutton: TemplateChild<MenuButton>,
+        #[template_child]
+        pub restyle_popover: TemplateChild<Popover>,
+        #[template_child]
+        pub restyle_roughness_spinbutton: TemplateChild<SpinButton>,
+        #[template_child]
+        pub restyle_bowing_spinbutton: TemplateChild<SpinButton>,
+        #[template_child]
+        pub restyle_width_spinbutton: TemplateChild<SpinButton>,
+        #[template_child]
+        pub restyle_stroke_colorpicker: TemplateChild<ColorPicker>,
+        #[template_child]
+        pub restyle_fill_colorpicker: TemplateChild<ColorPicker>,
+        /// Set while the restyle values are loaded from the selection
+        pub restyle_values_updating: Cell<bool>,
+        /// The changes of the restyle spinbuttons, which are applied to the selection after a timeout
+        pub restyle_pending_changes: RefCell<Vec<StyleChange>>,
+        pub restyle_timeout_id: RefCell<Option<glib::SourceId>>,
     }
 
     #[glib::object_subclass]
@@ -43,9 +96,15 @@ mod imp {
     impl WidgetImpl for SelectorPage {}
 }
 
-use crate::ui::appwindow::RnoteAppWindow;
-use gtk4::ToggleButton;
+use crate::compose::color::Color;
+use crate::compose::rough::roughoptions::RoughOptions;
+use crate::compose::smooth::SmoothOptions;
+use crate::strokes::strokestyle::StyleChange;
+use crate::ui::unitentry::UnitEntry;
+use crate::ui::{appwindow::RnoteAppWindow, colorpicker::ColorPicker};
+use gtk4::{gdk, Button, MenuButton, Popover, SpinButton, Switch, ToggleButton};
 use gtk4::{glib, glib::clone, prelude::*, subclass::prelude::*, Orientable, Widget};
+use std::time;
 
 glib::wrapper! {
     pub struct SelectorPage(ObjectSubclass<imp::SelectorPage>)
@@ -59,6 +118,11 @@ impl Default for SelectorPage {
 }
 
 impl SelectorPage {
+    /// The max. skew angle in degrees. At 90 degrees the strokes would be skewed to infinity
+    pub const SKEW_ANGLE_MAX: f64 = 80.0;
+    /// The time after the last change of a restyle spinbutton, until the changes are applied to the selection
+    pub const RESTYLE_TIMEOUT_TIME: time::Duration = time::Duration::from_millis(300);
+
     pub fn new() -> Self {
         glib::Object::new(&[]).expect("Failed to create SelectorPage")
     }
@@ -75,12 +139,134 @@ impl SelectorPage {
             .get()
     }
 
+    pub fn selectorcontainment_contained_toggle(&self) -> ToggleButton {
+        imp::SelectorPage::from_instance(self)
+            .selectorcontainment_contained_toggle
+            .get()
+    }
+
+    pub fn selectorcontainment_touching_toggle(&self) -> ToggleButton {
+        imp::SelectorPage::from_instance(self)
+            .selectorcontainment_touching_toggle
+            .get()
+    }
+
     pub fn resize_lock_aspectratio_togglebutton(&self) -> ToggleButton {
         imp::SelectorPage::from_instance(self)
             .resize_lock_aspectratio_togglebutton
             .get()
     }
 
+    pub fn transform_popover(&self) -> Popover {
+        imp::SelectorPage::from_instance(self)
+            .transform_popover
+            .get()
+    }
+
+    pub fn transform_x_unitentry(&self) -> UnitEntry {
+        imp::SelectorPage::from_instance(self)
+            .transform_x_unitentry
+            .get()
+    }
+
+    pub fn transform_y_unitentry(&self) -> UnitEntry {
+        imp::SelectorPage::from_instance(self)
+            .transform_y_unitentry
+            .get()
+    }
+
+    pub fn transform_width_unitentry(&self) -> UnitEntry {
+        imp::SelectorPage::from_instance(self)
+            .transform_width_unitentry
+            .get()
+    }
+
+    pub fn transform_height_unitentry(&self) -> UnitEntry {
+        imp::SelectorPage::from_instance(self)
+            .transform_height_unitentry
+            .get()
+    }
+
+    pub fn transform_lock_aspectratio_switch(&self) -> Switch {
+        imp::SelectorPage::from_instance(self)
+            .transform_lock_aspectratio_switch
+            .get()
+    }
+
+    pub fn transform_rotation_spinbutton(&self) -> SpinButton {
+        imp::SelectorPage::from_instance(self)
+            .transform_rotation_spinbutton
+            .get()
+    }
+
+    pub fn transform_apply_button(&self) -> Button {
+        imp::SelectorPage::from_instance(self)
+            .transform_apply_button
+            .get()
+    }
+
+    pub fn skew_popover(&self) -> Popover {
+        imp::SelectorPage::from_instance(self).skew_popover.get()
+    }
+
+    pub fn skew_horizontal_spinbutton(&self) -> SpinButton {
+        imp::SelectorPage::from_instance(self)
+            .skew_horizontal_spinbutton
+            .get()
+    }
+
+    pub fn skew_vertical_spinbutton(&self) -> SpinButton {
+        imp::SelectorPage::from_instance(self)
+            .skew_vertical_spinbutton
+            .get()
+    }
+
+    pub fn skew_apply_button(&self) -> Button {
+        imp::SelectorPage::from_instance(self)
+            .skew_apply_button
+            .get()
+    }
+
+    pub fn restyle_menubutton(&self) -> MenuButton {
+        imp::SelectorPage::from_instance(self)
+            .restyle_menubutton
+            .get()
+    }
+
+    pub fn restyle_popover(&self) -> Popover {
+        imp::SelectorPage::from_instance(self).restyle_popover.get()
+    }
+
+    pub fn restyle_width_spinbutton(&self) -> SpinButton {
+        imp::SelectorPage::from_instance(self)
+            .restyle_width_spinbutton
+            .get()
+    }
+
+    pub fn restyle_roughness_spinbutton(&self) -> SpinButton {
+        imp::SelectorPage::from_instance(self)
+            .restyle_roughness_spinbutton
+            .get()
+    }
+
+    pub fn restyle_bowing_spinbutton(&self) -> SpinButton {
+        imp::SelectorPage::from_instance(self)
+            .restyle_bowing_spinbutton
+            .get()
+    }
+
+    pub fn restyle_stroke_colorpicker(&self) -> ColorPicker {
+        imp::SelectorPage::from_instance(self)
+            .restyle_stroke_colorpicker
+            .get()
+    }
+
+    pub fn restyle_fill_colorpicker(&self) -> ColorPicker {
+        imp::SelectorPage::from_instance(self)
+            .restyle_fill_colorpicker
+            .get()
+    }
+
     pub fn init(&self, appwindow: &RnoteAppWindow) {
         // selecting with Polygon / Rect toggles
         self.selectorstyle_polygon_toggle().connect_toggled(clone!(@weak appwindow => move |selectorstyle_polygon_toggle| {
@@ -95,6 +281,19 @@ impl SelectorPage {
             }
         }));
 
+        // selecting strokes fully contained in / touching the path
+        self.selectorcontainment_contained_toggle().connect_toggled(clone!(@weak appwindow => move |selectorcontainment_contained_toggle| {
+            if selectorcontainment_contained_toggle.is_active() {
+                adw::prelude::ActionGroupExt::activate_action(&appwindow, "selector-containment", Some(&"contained".to_variant()));
+            }
+        }));
+
+        self.selectorcontainment_touching_toggle().connect_toggled(clone!(@weak appwindow => move |selectorcontainment_touching_toggle| {
+            if selectorcontainment_touching_toggle.is_active() {
+                adw::prelude::ActionGroupExt::activate_action(&appwindow, "selector-containment", Some(&"touching".to_variant()));
+            }
+        }));
+
         self.resize_lock_aspectratio_togglebutton()
             .bind_property(
                 "active",
@@ -103,5 +302,338 @@ impl SelectorPage {
             )
             .flags(glib::BindingFlags::SYNC_CREATE | glib::BindingFlags::BIDIRECTIONAL)
             .build();
+
+        // Transform
+        for unitentry in [
+            self.transform_x_unitentry(),
+            self.transform_y_unitentry(),
+            self.transform_width_unitentry(),
+            self.transform_height_unitentry(),
+        ] {
+            unitentry.value_spinner().set_increments(1.0, 10.0);
+            unitentry.value_spinner().set_digits(2);
+
+            // Show the values in the newly selected unit
+            unitentry.connect_notify_local(
+                Some("unit"),
+                clone!(@weak self as selector_page, @weak appwindow => move |_unitentry, _pspec| {
+                    selector_page.refresh_transform_entries(&appwindow);
+                }),
+            );
+        }
+        for unitentry in [
+            self.transform_width_unitentry(),
+            self.transform_height_unitentry(),
+        ] {
+            unitentry.value_adj().set_lower(0.01);
+        }
+
+        self.transform_lock_aspectratio_switch()
+            .bind_property(
+                "active",
+                &appwindow.canvas().selection_modifier(),
+                "resize-lock-aspectratio",
+            )
+            .flags(glib::BindingFlags::SYNC_CREATE | glib::BindingFlags::BIDIRECTIONAL)
+            .build();
+
+        self.transform_width_unitentry().connect_local(
+            "measurement-changed",
+            false,
+            clone!(@weak self as selector_page, @weak appwindow => @default-return None, move |_args| {
+                selector_page.keep_transform_aspectratio(&appwindow, true);
+                None
+            }),
+        );
+
+        self.transform_height_unitentry().connect_local(
+            "measurement-changed",
+            false,
+            clone!(@weak self as selector_page, @weak appwindow => @default-return None, move |_args| {
+                selector_page.keep_transform_aspectratio(&appwindow, false);
+                None
+            }),
+        );
+
+        self.transform_rotation_spinbutton()
+            .set_increments(1.0, 15.0);
+        self.transform_rotation_spinbutton()
+            .set_range(-360.0, 360.0);
+        self.transform_rotation_spinbutton().set_value(0.0);
+
+        self.transform_popover().connect_show(
+            clone!(@weak self as selector_page, @weak appwindow => move |_transform_popover| {
+                selector_page.refresh_transform_entries(&appwindow);
+            }),
+        );
+
+        self.transform_apply_button().connect_clicked(
+            clone!(@weak self as selector_page, @weak appwindow => move |_transform_apply_button| {
+                let transform = (
+                    selector_page.transform_x_unitentry().value_in_px_f64(),
+                    selector_page.transform_y_unitentry().value_in_px_f64(),
+                    selector_page.transform_width_unitentry().value_in_px_f64(),
+                    selector_page.transform_height_unitentry().value_in_px_f64(),
+                    selector_page.transform_rotation_spinbutton().value(),
+                );
+
+                adw::prelude::ActionGroupExt::activate_action(&appwindow, "selection-transform", Some(&transform.to_variant()));
+                selector_page.transform_popover().popdown();
+            }),
+        );
+
+        // Skew
+        for skew_spinbutton in [
+            self.skew_horizontal_spinbutton(),
+            self.skew_vertical_spinbutton(),
+        ] {
+            skew_spinbutton.set_increments(1.0, 5.0);
+            skew_spinbutton.set_range(-Self::SKEW_ANGLE_MAX, Self::SKEW_ANGLE_MAX);
+            skew_spinbutton.set_value(0.0);
+        }
+
+        self.skew_apply_button().connect_clicked(
+            clone!(@weak self as selector_page, @weak appwindow => move |_skew_apply_button| {
+                let angles = (
+                    selector_page.skew_horizontal_spinbutton().value(),
+                    selector_page.skew_vertical_spinbutton().value(),
+                );
+
+                adw::prelude::ActionGroupExt::activate_action(&appwindow, "selection-skew", Some(&angles.to_variant()));
+                selector_page.skew_popover().popdown();
+            }),
+        );
+
+        // Restyle
+        self.restyle_popover().connect_show(
+            clone!(@weak self as selector_page, @weak appwindow => move |_restyle_popover| {
+                selector_page.refresh_restyle_values(&appwindow);
+            }),
+        );
+
+        self.restyle_popover().connect_closed(
+            clone!(@weak self as selector_page, @weak appwindow => move |_restyle_popover| {
+                selector_page.apply_pending_restyle(&appwindow);
+            }),
+        );
+
+        // Restyle width
+        self.restyle_width_spinbutton().set_increments(0.1, 2.0);
+        self.restyle_width_spinbutton()
+            .set_range(SmoothOptions::WIDTH_MIN, SmoothOptions::WIDTH_MAX);
+        self.restyle_width_spinbutton()
+            .set_value(SmoothOptions::WIDTH_DEFAULT);
+
+        self.restyle_width_spinbutton().connect_value_changed(
+            clone!(@weak self as selector_page, @weak appwindow => move |restyle_width_spinbutton| {
+                selector_page.restyle_selection_after_timeout(&appwindow, StyleChange::Width(restyle_width_spinbutton.value()));
+            }),
+        );
+
+        // Restyle stroke color
+        self.restyle_stroke_colorpicker().connect_notify_local(
+            Some("current-color"),
+            clone!(@weak self as selector_page, @weak appwindow => move |restyle_stroke_colorpicker, _paramspec| {
+                if selector_page.imp().restyle_values_updating.get() {
+                    return;
+                }
+                let color = Color::from(restyle_stroke_colorpicker.property::<gdk::RGBA>("current-color"));
+                appwindow.canvas().restyle_selection(StyleChange::StrokeColor(color));
+            }),
+        );
+
+        // Restyle fill color
+        self.restyle_fill_colorpicker().connect_notify_local(
+            Some("current-color"),
+            clone!(@weak self as selector_page, @weak appwindow => move |restyle_fill_colorpicker, _paramspec| {
+                if selector_page.imp().restyle_values_updating.get() {
+                    return;
+                }
+                let color = Color::from(restyle_fill_colorpicker.property::<gdk::RGBA>("current-color"));
+                appwindow.canvas().restyle_selection(StyleChange::FillColor(Some(color)));
+            }),
+        );
+
+        // Restyle roughness
+        self.restyle_roughness_spinbutton().set_increments(0.1, 2.0);
+        self.restyle_roughness_spinbutton()
+            .set_range(RoughOptions::ROUGHNESS_MIN, RoughOptions::ROUGHNESS_MAX);
+        self.restyle_roughness_spinbutton()
+            .set_value(RoughOptions::ROUGHNESS_DEFAULT);
+
+        self.restyle_roughness_spinbutton().connect_value_changed(
+            clone!(@weak self as selector_page, @weak appwindow => move |restyle_roughness_spinbutton| {
+                selector_page.restyle_selection_after_timeout(&appwindow, StyleChange::Roughness(restyle_roughness_spinbutton.value()));
+            }),
+        );
+
+        // Restyle bowing
+        self.restyle_bowing_spinbutton().set_increments(0.1, 2.0);
+        self.restyle_bowing_spinbutton()
+            .set_range(RoughOptions::BOWING_MIN, RoughOptions::BOWING_MAX);
+        self.restyle_bowing_spinbutton()
+            .set_value(RoughOptions::BOWING_DEFAULT);
+
+        self.restyle_bowing_spinbutton().connect_value_changed(
+            clone!(@weak self as selector_page, @weak appwindow => move |restyle_bowing_spinbutton| {
+                selector_page.restyle_selection_after_timeout(&appwindow, StyleChange::Bowing(restyle_bowing_spinbutton.value()));
+            }),
+        );
+    }
+
+    /// Loads the style of the last selected stroke into the restyle widgets, without restyling the selection
+    pub fn refresh_restyle_values(&self, appwindow: &RnoteAppWindow) {
+        let stroke = {
+            let sheet = appwindow.canvas().sheet();
+            let sheet = sheet.borrow();
+
+            sheet
+                .strokes_state
+                .last_selection_key()
+                .and_then(|key| sheet.strokes_state.clone_strokes_for_keys(&[key]).pop())
+        };
+        let stroke = match stroke {
+            Some(stroke) => stroke,
+            None => return,
+        };
+
+        self.imp().restyle_values_updating.set(true);
+        if let Some(width) = stroke.width() {
+            self.restyle_width_spinbutton().set_value(width);
+        }
+        if let Some(stroke_color) = stroke.stroke_color() {
+            self.restyle_stroke_colorpicker()
+                .set_current_color(Some(stroke_color));
+        }
+        if let Some(fill_color) = stroke.fill_color() {
+            self.restyle_fill_colorpicker()
+                .set_current_color(Some(fill_color));
+        }
+        if let Some((roughness, bowing)) = stroke.roughness_bowing() {
+            self.restyle_roughness_spinbutton().set_value(roughness);
+            self.restyle_bowing_spinbutton().set_value(bowing);
+        }
+        self.imp().restyle_values_updating.set(false);
+    }
+
+    /// Applies the change after a timeout, so that the selection is not regenerated on every step of the spinbuttons.
+    /// Repeated calls reset the timeout
+    fn restyle_selection_after_timeout(&self, appwindow: &RnoteAppWindow, change: StyleChange) {
+        if self.imp().restyle_values_updating.get() {
+            return;
+        }
+
+        {
+            let mut pending_changes = self.imp().restyle_pending_changes.borrow_mut();
+            // Only the latest change of each kind is applied
+            pending_changes.retain(|pending_change| {
+                std::mem::discriminant(pending_change) != std::mem::discriminant(&change)
+            });
+            pending_changes.push(change);
+        }
+
+        if let Some(restyle_timeout_id) = self.imp().restyle_timeout_id.take() {
+            restyle_timeout_id.remove();
+        }
+
+        self.imp()
+            .restyle_timeout_id
+            .borrow_mut()
+            .replace(glib::source::timeout_add_local_once(
+                Self::RESTYLE_TIMEOUT_TIME,
+                clone!(@weak self as selector_page, @weak appwindow => move || {
+                    // The source is removed after it was dispatched
+                    selector_page.imp().restyle_timeout_id.borrow_mut().take();
+                    selector_page.apply_pending_restyle(&appwindow);
+                }),
+            ));
+    }
+
+    /// Applies the pending changes of the restyle spinbuttons immediately
+    fn apply_pending_restyle(&self, appwindow: &RnoteAppWindow) {
+        if let Some(restyle_timeout_id) = self.imp().restyle_timeout_id.take() {
+            restyle_timeout_id.remove();
+        }
+
+        let pending_changes = self.imp().restyle_pending_changes.take();
+        for change in pending_changes {
+            appwindow.canvas().restyle_selection(change);
+        }
+    }
+
+    /// Updates the transform entries with the position, size and rotation of the current selection
+    pub fn refresh_transform_entries(&self, appwindow: &RnoteAppWindow) {
+        let selection_bounds = appwindow
+            .canvas()
+            .sheet()
+            .borrow()
+            .strokes_state
+            .gen_selection_bounds();
+        let selection_bounds = match selection_bounds {
+            Some(selection_bounds) => selection_bounds,
+            None => return,
+        };
+        let dpi = appwindow.canvas().sheet().borrow().format.dpi;
+        let rotation = appwindow
+            .canvas()
+            .sheet()
+            .borrow()
+            .strokes_state
+            .selection_rotation()
+            .to_degrees();
+
+        self.imp().transform_entries_updating.set(true);
+        for (unitentry, value_in_px) in [
+            (self.transform_x_unitentry(), selection_bounds.mins[0]),
+            (self.transform_y_unitentry(), selection_bounds.mins[1]),
+            (
+                self.transform_width_unitentry(),
+                selection_bounds.extents()[0],
+            ),
+            (
+                self.transform_height_unitentry(),
+                selection_bounds.extents()[1],
+            ),
+        ] {
+            unitentry.set_dpi(dpi);
+            unitentry.set_value_in_px(value_in_px);
+        }
+        // Shown between -180 and 180 degrees
+        self.transform_rotation_spinbutton()
+            .set_value(rotation - (rotation / 360.0).round() * 360.0);
+        self.imp().transform_entries_updating.set(false);
+    }
+
+    /// Adjusts the other dimension to the aspect ratio of the selection, if it is locked
+    fn keep_transform_aspectratio(&self, appwindow: &RnoteAppWindow, width_changed: bool) {
+        if self.imp().transform_entries_updating.get()
+            || !self.transform_lock_aspectratio_switch().is_active()
+        {
+            return;
+        }
+        let selection_bounds = appwindow
+            .canvas()
+            .sheet()
+            .borrow()
+            .strokes_state
+            .gen_selection_bounds();
+        let extents = match selection_bounds {
+            Some(selection_bounds) if selection_bounds.extents().min() > 0.0 => {
+                selection_bounds.extents()
+            }
+            _ => return,
+        };
+
+        self.imp().transform_entries_updating.set(true);
+        if width_changed {
+            let width = self.transform_width_unitentry().value_in_px_f64();
+            self.transform_height_unitentry()
+                .set_value_in_px(width * extents[1] / extents[0]);
+        } else {
+            let height = self.transform_height_unitentry().value_in_px_f64();
+            self.transform_width_unitentry()
+                .set_value_in_px(height * extents[0] / extents[1]);
+        }
+        self.imp().transform_entries_updating.set(false);
     }
 }
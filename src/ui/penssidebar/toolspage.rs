@@ -1,5 +1,9 @@
 mod imp {
-    use gtk4::{glib, prelude::*, subclass::prelude::*, CompositeTemplate, ToggleButton};
+    use crate::ui::colorpicker::ColorPicker;
+    use gtk4::{
+        glib, prelude::*, subclass::prelude::*, Button, CompositeTemplate, DropDown, SpinButton,
+        ToggleButton,
+    };
 
     #[derive(Default, Debug, CompositeTemplate)]
     #[template(resource = "/com/github/flxzt/rnote/ui/penssidebar/toolspage.ui")]
@@ -8,6 +12,24 @@ mod imp {
         pub toolstyle_expandsheet_toggle: TemplateChild<ToggleButton>,
         #[template_child]
         pub toolstyle_dragproximity_toggle: TemplateChild<ToggleButton>,
+        #[template_child]
+        pub toolstyle_ruler_toggle: TemplateChild<ToggleButton>,
+        #[template_child]
+        pub toolstyle_protractor_toggle: TemplateChild<ToggleButton>,
+        #[template_child]
+        pub toolstyle_laser_toggle: TemplateChild<ToggleButton>,
+        #[template_child]
+        pub toolstyle_vanishingink_toggle: TemplateChild<ToggleButton>,
+        #[template_child]
+        pub toolstyle_fill_toggle: TemplateChild<ToggleButton>,
+        #[template_child]
+        pub ruler_unit_dropdown: TemplateChild<DropDown>,
+        #[template_child]
+        pub vanishingink_timeout_spinbutton: TemplateChild<SpinButton>,
+        #[template_child]
+        pub ruler_hide_button: TemplateChild<Button>,
+        #[template_child]
+        pub fill_colorpicker: TemplateChild<ColorPicker>,
     }
 
     #[glib::object_subclass]
@@ -40,8 +62,14 @@ mod imp {
     impl WidgetImpl for ToolsPage {}
 }
 
-use crate::ui::appwindow::RnoteAppWindow;
-use gtk4::{glib, glib::clone, prelude::*, subclass::prelude::*, Orientable, ToggleButton, Widget};
+use crate::compose::color::Color;
+use crate::pens::tools::VanishingInkTool;
+use crate::sheet::format::MeasureUnit;
+use crate::ui::{appwindow::RnoteAppWindow, colorpicker::ColorPicker};
+use gtk4::{
+    gdk, glib, glib::clone, prelude::*, subclass::prelude::*, Button, DropDown, Orientable,
+    SpinButton, ToggleButton, Widget,
+};
 
 glib::wrapper! {
     pub struct ToolsPage(ObjectSubclass<imp::ToolsPage>)
@@ -71,7 +99,82 @@ impl ToolsPage {
             .get()
     }
 
+    pub fn toolstyle_ruler_toggle(&self) -> ToggleButton {
+        imp::ToolsPage::from_instance(self)
+            .toolstyle_ruler_toggle
+            .get()
+    }
+
+    pub fn toolstyle_protractor_toggle(&self) -> ToggleButton {
+        imp::ToolsPage::from_instance(self)
+            .toolstyle_protractor_toggle
+            .get()
+    }
+
+    pub fn toolstyle_laser_toggle(&self) -> ToggleButton {
+        imp::ToolsPage::from_instance(self)
+            .toolstyle_laser_toggle
+            .get()
+    }
+
+    pub fn toolstyle_vanishingink_toggle(&self) -> ToggleButton {
+        imp::ToolsPage::from_instance(self)
+            .toolstyle_vanishingink_toggle
+            .get()
+    }
+
+    pub fn toolstyle_fill_toggle(&self) -> ToggleButton {
+        imp::ToolsPage::from_instance(self)
+            .toolstyle_fill_toggle
+            .get()
+    }
+
+    pub fn vanishingink_timeout_spinbutton(&self) -> SpinButton {
+        imp::ToolsPage::from_instance(self)
+            .vanishingink_timeout_spinbutton
+            .get()
+    }
+
+    pub fn ruler_unit_dropdown(&self) -> DropDown {
+        imp::ToolsPage::from_instance(self)
+            .ruler_unit_dropdown
+            .get()
+    }
+
+    pub fn ruler_hide_button(&self) -> Button {
+        imp::ToolsPage::from_instance(self).ruler_hide_button.get()
+    }
+
+    pub fn fill_colorpicker(&self) -> ColorPicker {
+        imp::ToolsPage::from_instance(self).fill_colorpicker.get()
+    }
+
+    pub fn set_ruler_unit(&self, unit: MeasureUnit) {
+        let ruler_unit_listmodel = self
+            .ruler_unit_dropdown()
+            .model()
+            .unwrap()
+            .downcast::<adw::EnumListModel>()
+            .unwrap();
+
+        self.ruler_unit_dropdown()
+            .set_selected(ruler_unit_listmodel.find_position(unit as i32));
+    }
+
     pub fn init(&self, appwindow: &RnoteAppWindow) {
+        self.vanishingink_timeout_spinbutton()
+            .set_increments(1.0, 5.0);
+        self.vanishingink_timeout_spinbutton()
+            .set_range(VanishingInkTool::TIMEOUT_MIN, VanishingInkTool::TIMEOUT_MAX);
+        self.vanishingink_timeout_spinbutton()
+            .set_value(VanishingInkTool::TIMEOUT_DEFAULT);
+
+        self.vanishingink_timeout_spinbutton().connect_value_changed(
+            clone!(@weak appwindow => move |vanishingink_timeout_spinbutton| {
+                appwindow.canvas().pens().borrow_mut().tools.vanishing_ink_tool.timeout = vanishingink_timeout_spinbutton.value();
+            }),
+        );
+
         self.toolstyle_expandsheet_toggle().connect_toggled(clone!(@weak appwindow => move |toolstyle_expandsheet_toggle| {
             if toolstyle_expandsheet_toggle.is_active() {
                 adw::prelude::ActionGroupExt::activate_action(&appwindow, "tool-style", Some(&"expandsheet".to_variant()));
@@ -83,5 +186,51 @@ impl ToolsPage {
                 adw::prelude::ActionGroupExt::activate_action(&appwindow, "tool-style", Some(&"dragproximity".to_variant()));
             }
         }));
+
+        self.toolstyle_ruler_toggle().connect_toggled(clone!(@weak appwindow => move |toolstyle_ruler_toggle| {
+            if toolstyle_ruler_toggle.is_active() {
+                adw::prelude::ActionGroupExt::activate_action(&appwindow, "tool-style", Some(&"ruler".to_variant()));
+            }
+        }));
+
+        self.toolstyle_protractor_toggle().connect_toggled(clone!(@weak appwindow => move |toolstyle_protractor_toggle| {
+            if toolstyle_protractor_toggle.is_active() {
+                adw::prelude::ActionGroupExt::activate_action(&appwindow, "tool-style", Some(&"protractor".to_variant()));
+            }
+        }));
+
+        self.toolstyle_laser_toggle().connect_toggled(clone!(@weak appwindow => move |toolstyle_laser_toggle| {
+            if toolstyle_laser_toggle.is_active() {
+                adw::prelude::ActionGroupExt::activate_action(&appwindow, "tool-style", Some(&"laser".to_variant()));
+            }
+        }));
+
+        self.toolstyle_vanishingink_toggle().connect_toggled(clone!(@weak appwindow => move |toolstyle_vanishingink_toggle| {
+            if toolstyle_vanishingink_toggle.is_active() {
+                adw::prelude::ActionGroupExt::activate_action(&appwindow, "tool-style", Some(&"vanishingink".to_variant()));
+            }
+        }));
+
+        self.toolstyle_fill_toggle().connect_toggled(clone!(@weak appwindow => move |toolstyle_fill_toggle| {
+            if toolstyle_fill_toggle.is_active() {
+                adw::prelude::ActionGroupExt::activate_action(&appwindow, "tool-style", Some(&"fill".to_variant()));
+            }
+        }));
+
+        self.fill_colorpicker().connect_notify_local(
+            Some("current-color"),
+            clone!(@weak appwindow => move |fill_colorpicker, _paramspec| {
+                let color = Color::from(fill_colorpicker.property::<gdk::RGBA>("current-color"));
+                appwindow.canvas().pens().borrow_mut().tools.fill_tool.color = color;
+            }),
+        );
+
+        self.ruler_unit_dropdown().connect_selected_notify(clone!(@weak appwindow => move |ruler_unit_dropdown| {
+            if let Some(item) = ruler_unit_dropdown.selected_item() {
+                let nick = item.downcast::<adw::EnumListItem>().unwrap().nick();
+
+                adw::prelude::ActionGroupExt::activate_action(&appwindow, "tool-ruler-unit", Some(&nick.to_variant()));
+            }
+        }));
     }
 }
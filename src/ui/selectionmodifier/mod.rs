@@ -1,11 +1,12 @@
 pub mod modifiernode;
 
 pub mod imp {
-    use std::cell::Cell;
+    use std::cell::{Cell, RefCell};
 
     use crate::compose::color::Color;
+    use crate::compose::snapping::SnapGuide;
     use crate::ui::canvas::Canvas;
-    use crate::{compose, render, utils};
+    use crate::{compose, render};
 
     use super::modifiernode::ModifierNode;
 
@@ -39,6 +40,7 @@ pub mod imp {
         pub(super) start_rotation_center: Cell<Option<na::Point2<f64>>>,
         pub(super) start_rotation_angle: Cell<f64>,
         pub(super) current_rotation_angle: Cell<f64>,
+        pub(super) snap_guides: RefCell<Vec<SnapGuide>>,
     }
 
     impl Default for SelectionModifier {
@@ -57,6 +59,7 @@ pub mod imp {
                 start_rotation_center: Cell::new(None),
                 start_rotation_angle: Cell::new(0.0),
                 current_rotation_angle: Cell::new(0.0),
+                snap_guides: RefCell::new(vec![]),
             }
         }
     }
@@ -227,14 +230,8 @@ pub mod imp {
             };
             const SELECTION_BOUNDS_WIDTH: f64 = 1.5;
 
-            if let Some(selection_bounds) = self.selection_bounds.get() {
-                let transformed_selection_bounds = utils::translate_aabb_to_widget(
-                    canvas.transform_sheet_aabb_to_canvas(selection_bounds),
-                    canvas,
-                    widget,
-                )
-                .unwrap();
-
+            if let Some(transformed_selection_bounds) = widget.selection_bounds_on_modifier(canvas)
+            {
                 let draw = || -> Result<(), anyhow::Error> {
                     let rect = element::Rectangle::new()
                         .set("x", f64::from(super::SelectionModifier::RESIZE_NODE_SIZE))
@@ -310,23 +307,16 @@ pub mod imp {
             const ROTATION_LINE_WIDTH: f64 = 2.5;
             const ROTATION_LINE_LEN: f64 = 30.0;
 
-            if let (Some(current_rotation_center), Some(selection_bounds)) = (
+            if let (Some(current_rotation_center), Some(transformed_selection_bounds)) = (
                 self.start_rotation_center.get(),
-                self.selection_bounds.get(),
+                widget.selection_bounds_on_modifier(canvas),
             ) {
-                let transformed_selection_bounds = utils::translate_aabb_to_widget(
-                    canvas.transform_sheet_aabb_to_canvas(selection_bounds),
+                let center = match widget.transform_sheet_coords_to_modifier_coords(
                     canvas,
-                    widget,
-                )
-                .unwrap();
-                let center = {
-                    let center = canvas
-                        .transform_sheet_coords_to_canvas_coords(current_rotation_center.coords);
-                    let center = canvas
-                        .translate_coordinates(widget, center[0], center[1])
-                        .unwrap();
-                    na::point![center.0, center.1]
+                    current_rotation_center.coords,
+                ) {
+                    Some(center) => na::Point2::from(center),
+                    None => return,
                 };
 
                 let draw = || -> Result<(), anyhow::Error> {
@@ -401,14 +391,16 @@ pub mod imp {
     }
 }
 
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
 use gtk4::{gdk, EventSequenceState, GestureDrag, PropagationPhase};
 use gtk4::{glib, glib::clone, prelude::*, subclass::prelude::*};
-use p2d::bounding_volume::AABB;
+use p2d::bounding_volume::{BoundingVolume, AABB};
 
+use crate::compose::color::Color;
 use crate::compose::geometry;
+use crate::compose::snapping::{SnapGuide, SnapLines};
 use crate::{ui::appwindow::RnoteAppWindow, ui::selectionmodifier::modifiernode::ModifierNode};
 
 use super::canvas::Canvas;
@@ -428,6 +420,15 @@ impl SelectionModifier {
     pub const RESIZE_NODE_SIZE: i32 = 18;
     // must not be < 2 * RESIZE_NODE_SIZE + its margins
     pub const SELECTION_BOUNDS_MIN: f64 = 60.0;
+    /// The max. distance (in surface coordinates) in which the selection snaps to alignment guides while translating
+    pub const SNAP_DISTANCE: f64 = 8.0;
+    pub const SNAP_GUIDE_WIDTH: f64 = 1.0;
+    pub const SNAP_GUIDE_COLOR: Color = Color {
+        r: 0.9,
+        g: 0.1,
+        b: 0.5,
+        a: 0.8,
+    };
 
     pub fn new() -> Self {
         let selection_modifier: Self =
@@ -471,6 +472,44 @@ impl SelectionModifier {
             .get()
     }
 
+    /// The guides of the lines the selection is currently snapped to, in sheet coordinates
+    pub fn snap_guides(&self) -> Vec<SnapGuide> {
+        self.imp().snap_guides.borrow().clone()
+    }
+
+    /// The lines in the viewport which the selection can snap to: the bounds of the other strokes, the page borders and the background pattern
+    pub fn gen_snap_lines(&self, canvas: &Canvas) -> SnapLines {
+        let viewport = canvas.viewport_in_sheet_coords();
+        let mut snap_lines = SnapLines::new();
+
+        let sheet = canvas.sheet();
+        let sheet = sheet.borrow();
+
+        sheet
+            .strokes_state
+            .stroke_keys_intersect_bounds(viewport)
+            .into_iter()
+            .filter_map(|key| sheet.strokes_state.gen_bounds(&[key]))
+            .for_each(|bounds| snap_lines.add_bounds(bounds));
+
+        sheet
+            .gen_pages_bounds()
+            .into_iter()
+            .filter(|page_bounds| page_bounds.intersects(&viewport))
+            .for_each(|page_bounds| snap_lines.add_bounds(page_bounds));
+
+        // No grid lines are added if the viewport is outside of the sheet
+        let sheet_bounds = sheet.bounds();
+        let viewport_in_sheet = AABB::new(
+            na::Point2::from(viewport.mins.coords.sup(&sheet_bounds.mins.coords)),
+            na::Point2::from(viewport.maxs.coords.inf(&sheet_bounds.maxs.coords)),
+        );
+        let (column_spacing, row_spacing) = sheet.background.pattern_line_spacings();
+        snap_lines.add_grid(viewport_in_sheet, column_spacing, row_spacing);
+
+        snap_lines
+    }
+
     pub fn resize_lock_aspectratio(&self) -> bool {
         self.property::<bool>("resize-lock-aspectratio")
     }
@@ -489,6 +528,55 @@ impl SelectionModifier {
             .set(bounds.map(|bounds| geometry::aabb_new_positive(bounds.mins, bounds.maxs)));
     }
 
+    /// transforming coordinates in sheet coordinate space into the coordinate space of the modifier, which is rotated together with the view
+    pub fn transform_sheet_coords_to_modifier_coords(
+        &self,
+        canvas: &Canvas,
+        sheet_coords: na::Vector2<f64>,
+    ) -> Option<na::Vector2<f64>> {
+        self.selection_bounds().map(|selection_bounds| {
+            na::Vector2::from_element(f64::from(Self::RESIZE_NODE_SIZE))
+                + (sheet_coords - selection_bounds.mins.coords) * canvas.total_zoom()
+        })
+    }
+
+    /// The selection bounds in the coordinate space of the modifier
+    pub fn selection_bounds_on_modifier(&self, canvas: &Canvas) -> Option<AABB> {
+        let selection_bounds = self.selection_bounds()?;
+
+        Some(AABB::new(
+            na::Point2::from(
+                self.transform_sheet_coords_to_modifier_coords(
+                    canvas,
+                    selection_bounds.mins.coords,
+                )?,
+            ),
+            na::Point2::from(
+                self.transform_sheet_coords_to_modifier_coords(
+                    canvas,
+                    selection_bounds.maxs.coords,
+                )?,
+            ),
+        ))
+    }
+
+    /// transforming a drag offset on the modifier into sheet coordinate space, mapping it through the view rotation
+    pub fn transform_drag_offset_to_sheet(
+        &self,
+        canvas: &Canvas,
+        offset: na::Vector2<f64>,
+    ) -> na::Vector2<f64> {
+        let view_delta = match (
+            self.translate_coordinates(canvas, 0.0, 0.0),
+            self.translate_coordinates(canvas, offset[0], offset[1]),
+        ) {
+            (Some(start), Some(end)) => na::vector![end.0 - start.0, end.1 - start.1],
+            _ => offset,
+        };
+
+        canvas.transform_view_delta_to_canvas_delta(view_delta) / canvas.zoom()
+    }
+
     /// Updates the internal state for measuring the widgets size, allocation, etc.
     pub fn update_state(&self, canvas: &Canvas) {
         self.set_selection_bounds(canvas.sheet().borrow().strokes_state.gen_selection_bounds());
@@ -559,7 +647,8 @@ impl SelectionModifier {
             clone!(@strong start_bounds, @weak self as selection_modifier, @weak appwindow => move |drag_gesture, x, y| {
                 if let (Some(selection_bounds), Some(start_bounds)) = (selection_modifier.selection_bounds(), start_bounds.get()) {
                     let zoom = appwindow.canvas().zoom();
-                    let offset = na::vector![-x.round() / zoom, -y.round() / zoom];
+                    let offset = selection_modifier.transform_drag_offset_to_sheet(&appwindow.canvas(), na::vector![x.round(), y.round()]);
+                    let offset = -offset;
 
                     // Lock aspectratio when property is set or with left click drag + ctrl
                     let new_extents = if selection_modifier.resize_lock_aspectratio()
@@ -626,7 +715,8 @@ impl SelectionModifier {
             clone!(@strong start_bounds, @weak self as selection_modifier, @weak appwindow => move |drag_gesture, x, y| {
                 if let (Some(selection_bounds), Some(start_bounds)) = (selection_modifier.selection_bounds(), start_bounds.get()) {
                     let zoom = appwindow.canvas().zoom();
-                    let offset = na::vector![x.round() / zoom, -y.round() / zoom];
+                    let offset = selection_modifier.transform_drag_offset_to_sheet(&appwindow.canvas(), na::vector![x.round(), y.round()]);
+                    let offset = na::vector![offset[0], -offset[1]];
 
                     // Lock aspectratio when property is set or with left click drag + ctrl
                     let new_extents = if selection_modifier.resize_lock_aspectratio()
@@ -693,7 +783,8 @@ impl SelectionModifier {
             clone!(@strong start_bounds, @weak self as selection_modifier, @weak appwindow => move |drag_gesture, x, y| {
                 if let (Some(selection_bounds), Some(start_bounds)) = (selection_modifier.selection_bounds(), start_bounds.get()) {
                     let zoom = appwindow.canvas().zoom();
-                    let offset = na::vector![-x.round() / zoom, y.round() / zoom];
+                    let offset = selection_modifier.transform_drag_offset_to_sheet(&appwindow.canvas(), na::vector![x.round(), y.round()]);
+                    let offset = na::vector![-offset[0], offset[1]];
 
                     // Lock aspectratio when property is set or with left click drag + ctrl
                     let new_extents = if selection_modifier.resize_lock_aspectratio()
@@ -760,7 +851,7 @@ impl SelectionModifier {
             clone!(@strong start_bounds, @weak self as selection_modifier, @weak appwindow => move |drag_gesture, x, y| {
                 if let (Some(selection_bounds), Some(start_bounds)) = (selection_modifier.selection_bounds(), start_bounds.get()) {
                     let zoom = appwindow.canvas().zoom();
-                    let offset = na::vector![x.round() / zoom, y.round() / zoom];
+                    let offset = selection_modifier.transform_drag_offset_to_sheet(&appwindow.canvas(), na::vector![x.round(), y.round()]);
 
                     // Lock aspectratio when property is set or with left click drag + ctrl
                     let new_extents = if selection_modifier.resize_lock_aspectratio()
@@ -813,28 +904,54 @@ impl SelectionModifier {
             .translate_node
             .add_controller(&translate_node_drag_gesture);
 
+        let start_bounds: Rc<Cell<Option<AABB>>> = Rc::new(Cell::new(None));
+        // The offset the selection is translated by since the drag began, including the snapping
+        let translated: Rc<Cell<na::Vector2<f64>>> = Rc::new(Cell::new(na::Vector2::zeros()));
+        let snap_lines: Rc<RefCell<SnapLines>> = Rc::new(RefCell::new(SnapLines::new()));
+
         translate_node_drag_gesture.connect_drag_begin(
-            clone!(@weak self as selection_modifier, @weak appwindow => move |translate_node_drag_gesture, _x, _y| {
+            clone!(@strong start_bounds, @strong translated, @strong snap_lines, @weak self as selection_modifier, @weak appwindow => move |translate_node_drag_gesture, _x, _y| {
                 translate_node_drag_gesture.set_state(EventSequenceState::Claimed);
 
                 selection_modifier.update_state(&appwindow.canvas());
+
+                start_bounds.set(selection_modifier.selection_bounds());
+                translated.set(na::Vector2::zeros());
+                *snap_lines.borrow_mut() = selection_modifier.gen_snap_lines(&appwindow.canvas());
             }),
         );
         translate_node_drag_gesture.connect_drag_update(
-            clone!(@weak self as selection_modifier, @weak appwindow => move |_translate_node_drag_gesture, x, y| {
+            clone!(@strong start_bounds, @strong translated, @strong snap_lines, @weak self as selection_modifier, @weak appwindow => move |_translate_node_drag_gesture, x, y| {
                 let zoom = appwindow.canvas().zoom();
-                let offset = na::vector![x.round() / zoom, y.round() / zoom];
+                // The node follows the selection, so the drag offset is the distance the pointer is ahead of it
+                let offset = selection_modifier.transform_drag_offset_to_sheet(&appwindow.canvas(), na::vector![x.round(), y.round()]);
+
+                let start_bounds = match start_bounds.get() {
+                    Some(start_bounds) => start_bounds,
+                    None => return,
+                };
+                let unsnapped = translated.get() + offset;
+                let (snap_offset, snap_guides) = snap_lines
+                    .borrow()
+                    .snap(geometry::aabb_translate(start_bounds, unsnapped), Self::SNAP_DISTANCE / zoom);
+                let delta = unsnapped + snap_offset - translated.get();
+                translated.set(unsnapped + snap_offset);
+                *selection_modifier.imp().snap_guides.borrow_mut() = snap_guides;
 
                 let selection_keys = appwindow.canvas().sheet().borrow().strokes_state.selection_keys_in_order_rendered();
-                appwindow.canvas().sheet().borrow_mut().strokes_state.translate_strokes(&selection_keys, offset, zoom);
-                selection_modifier.set_selection_bounds(selection_modifier.imp().selection_bounds.get().map(|selection_bounds| geometry::aabb_translate(selection_bounds, offset)));
+                appwindow.canvas().sheet().borrow_mut().strokes_state.translate_strokes(&selection_keys, delta, zoom);
+                selection_modifier.set_selection_bounds(selection_modifier.imp().selection_bounds.get().map(|selection_bounds| geometry::aabb_translate(selection_bounds, delta)));
 
                 selection_modifier.update_translate_node_size_request(&appwindow.canvas());
                 appwindow.canvas().queue_draw();
             }),
         );
         translate_node_drag_gesture.connect_drag_end(
-            clone!(@weak self as selection_modifier, @weak appwindow => move |_translate_node_drag_gesture, _x, _y| {
+            clone!(@strong start_bounds, @strong snap_lines, @weak self as selection_modifier, @weak appwindow => move |_translate_node_drag_gesture, _x, _y| {
+                start_bounds.set(None);
+                *snap_lines.borrow_mut() = SnapLines::new();
+                selection_modifier.imp().snap_guides.borrow_mut().clear();
+
                 selection_modifier.update_state(&appwindow.canvas());
                 appwindow.canvas().queue_draw();
             }),
@@ -901,8 +1018,7 @@ impl SelectionModifier {
 
                     let angle_delta = angle - selection_modifier.imp().current_rotation_angle.get();
 
-                    let selection_keys = appwindow.canvas().sheet().borrow().strokes_state.selection_keys_in_order_rendered();
-                    appwindow.canvas().sheet().borrow_mut().strokes_state.rotate_strokes(&selection_keys, angle_delta, start_bounds.center(), appwindow.canvas().renderer(), zoom);
+                    appwindow.canvas().sheet().borrow_mut().strokes_state.rotate_selection(angle_delta, start_bounds.center(), appwindow.canvas().renderer(), zoom);
                     selection_modifier.update_state(&appwindow.canvas());
 
                     selection_modifier.imp().current_rotation_angle.set(angle);
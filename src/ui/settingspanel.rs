@@ -4,7 +4,7 @@ mod imp {
 
     use adw::prelude::*;
     use gtk4::{glib, glib::clone, subclass::prelude::*, CompositeTemplate};
-    use gtk4::{Adjustment, Button, ColorButton, ScrolledWindow, ToggleButton};
+    use gtk4::{Adjustment, Button, ColorButton, ScrolledWindow, Switch, ToggleButton};
 
     use crate::sheet::format::{self, Format};
     use crate::ui::unitentry::UnitEntry;
@@ -25,6 +25,18 @@ mod imp {
         #[template_child]
         pub general_pdf_import_as_bitmap_toggle: TemplateChild<ToggleButton>,
         #[template_child]
+        pub stylus_eraser_auto_switch_switch: TemplateChild<Switch>,
+        #[template_child]
+        pub stylus_palm_rejection_switch: TemplateChild<Switch>,
+        #[template_child]
+        pub stylus_palm_rejection_timeout_adj: TemplateChild<Adjustment>,
+        #[template_child]
+        pub stylus_palm_rejection_contact_threshold_adj: TemplateChild<Adjustment>,
+        #[template_child]
+        pub stylus_primary_button_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub stylus_secondary_button_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
         pub format_predefined_formats_row: TemplateChild<adw::ComboRow>,
         #[template_child]
         pub format_orientation_row: TemplateChild<adw::ActionRow>,
@@ -358,10 +370,11 @@ use std::rc::Rc;
 
 use adw::prelude::*;
 use gtk4::{glib, glib::clone, subclass::prelude::*, Widget};
-use gtk4::{Adjustment, ColorButton, ScrolledWindow, ToggleButton};
+use gtk4::{Adjustment, ColorButton, ScrolledWindow, Switch, ToggleButton};
 
 use super::appwindow::RnoteAppWindow;
 use crate::compose::color::Color;
+use crate::pens::stylusconfig::StylusButtonAction;
 use crate::sheet::background::PatternStyle;
 use crate::sheet::format::{self, Format, MeasureUnit};
 use crate::ui::unitentry::UnitEntry;
@@ -416,6 +429,31 @@ impl SettingsPanel {
             .set_selected(background_pattern_listmodel.find_position(pattern as i32));
     }
 
+    fn set_stylus_button_action_variant(
+        combo_row: &adw::ComboRow,
+        button_action: StylusButtonAction,
+    ) {
+        let button_action_listmodel = combo_row
+            .model()
+            .unwrap()
+            .downcast::<adw::EnumListModel>()
+            .unwrap();
+        combo_row.set_selected(button_action_listmodel.find_position(button_action as i32));
+    }
+
+    /// The button action selected in the combo row
+    fn stylus_button_action_selected(combo_row: &adw::ComboRow) -> Option<StylusButtonAction> {
+        let selected_item = combo_row.selected_item()?;
+
+        StylusButtonAction::from_nick(
+            selected_item
+                .downcast::<adw::EnumListItem>()
+                .unwrap()
+                .nick()
+                .as_str(),
+        )
+    }
+
     pub fn set_format_orientation(&self, orientation: format::Orientation) {
         if orientation == format::Orientation::Portrait {
             self.imp()
@@ -458,6 +496,42 @@ impl SettingsPanel {
             .clone()
     }
 
+    pub fn stylus_eraser_auto_switch_switch(&self) -> Switch {
+        imp::SettingsPanel::from_instance(self)
+            .stylus_eraser_auto_switch_switch
+            .clone()
+    }
+
+    pub fn stylus_palm_rejection_switch(&self) -> Switch {
+        imp::SettingsPanel::from_instance(self)
+            .stylus_palm_rejection_switch
+            .clone()
+    }
+
+    pub fn stylus_palm_rejection_timeout_adj(&self) -> Adjustment {
+        imp::SettingsPanel::from_instance(self)
+            .stylus_palm_rejection_timeout_adj
+            .clone()
+    }
+
+    pub fn stylus_palm_rejection_contact_threshold_adj(&self) -> Adjustment {
+        imp::SettingsPanel::from_instance(self)
+            .stylus_palm_rejection_contact_threshold_adj
+            .clone()
+    }
+
+    pub fn stylus_primary_button_row(&self) -> adw::ComboRow {
+        imp::SettingsPanel::from_instance(self)
+            .stylus_primary_button_row
+            .clone()
+    }
+
+    pub fn stylus_secondary_button_row(&self) -> adw::ComboRow {
+        imp::SettingsPanel::from_instance(self)
+            .stylus_secondary_button_row
+            .clone()
+    }
+
     pub fn format_width_unitentry(&self) -> UnitEntry {
         imp::SettingsPanel::from_instance(self)
             .format_width_unitentry
@@ -508,6 +582,7 @@ impl SettingsPanel {
 
     pub fn refresh_for_sheet(&self, appwindow: &RnoteAppWindow) {
         self.load_misc(appwindow);
+        self.load_stylus(appwindow);
         self.load_format(appwindow);
         self.load_background(appwindow);
     }
@@ -525,6 +600,21 @@ impl SettingsPanel {
             .set_value(appwindow.canvas().pdf_import_width());
     }
 
+    pub fn load_stylus(&self, appwindow: &RnoteAppWindow) {
+        let stylus_config = appwindow.canvas().pens().borrow().stylus_config;
+
+        self.stylus_eraser_auto_switch_switch()
+            .set_active(stylus_config.eraser_auto_switch);
+        Self::set_stylus_button_action_variant(
+            &self.stylus_primary_button_row(),
+            stylus_config.primary_button_action,
+        );
+        Self::set_stylus_button_action_variant(
+            &self.stylus_secondary_button_row(),
+            stylus_config.secondary_button_action,
+        );
+    }
+
     pub fn load_format(&self, appwindow: &RnoteAppWindow) {
         let format = appwindow.canvas().sheet().borrow().format.clone();
 
@@ -647,6 +737,53 @@ impl SettingsPanel {
             )
             .build();
 
+        // Palm rejection
+        self.imp()
+            .stylus_palm_rejection_switch
+            .get()
+            .bind_property("active", &appwindow.canvas(), "palm-rejection")
+            .flags(glib::BindingFlags::SYNC_CREATE | glib::BindingFlags::BIDIRECTIONAL)
+            .build();
+        self.imp()
+            .stylus_palm_rejection_timeout_adj
+            .get()
+            .bind_property("value", &appwindow.canvas(), "palm-rejection-timeout")
+            .flags(glib::BindingFlags::SYNC_CREATE | glib::BindingFlags::BIDIRECTIONAL)
+            .build();
+        self.imp()
+            .stylus_palm_rejection_contact_threshold_adj
+            .get()
+            .bind_property(
+                "value",
+                &appwindow.canvas(),
+                "palm-rejection-contact-threshold",
+            )
+            .flags(glib::BindingFlags::SYNC_CREATE | glib::BindingFlags::BIDIRECTIONAL)
+            .build();
+
+        // Stylus
+        self.imp().stylus_eraser_auto_switch_switch.get().connect_active_notify(
+            clone!(@weak appwindow => move |stylus_eraser_auto_switch_switch| {
+                appwindow.canvas().pens().borrow_mut().stylus_config.eraser_auto_switch = stylus_eraser_auto_switch_switch.is_active();
+            }),
+        );
+
+        self.imp().stylus_primary_button_row.get().connect_selected_item_notify(
+            clone!(@weak appwindow => move |stylus_primary_button_row| {
+                if let Some(button_action) = Self::stylus_button_action_selected(stylus_primary_button_row) {
+                    appwindow.canvas().pens().borrow_mut().stylus_config.primary_button_action = button_action;
+                }
+            }),
+        );
+
+        self.imp().stylus_secondary_button_row.get().connect_selected_item_notify(
+            clone!(@weak appwindow => move |stylus_secondary_button_row| {
+                if let Some(button_action) = Self::stylus_button_action_selected(stylus_secondary_button_row) {
+                    appwindow.canvas().pens().borrow_mut().stylus_config.secondary_button_action = button_action;
+                }
+            }),
+        );
+
         // revert format
         self.imp().format_revert_button.get().connect_clicked(
             clone!(@weak self as settings_panel, @weak appwindow => move |_format_revert_button| {
@@ -281,6 +281,28 @@ impl UnitEntry {
         .round() as i32
     }
 
+    /// The value in pixels, without rounding
+    pub fn value_in_px_f64(&self) -> f64 {
+        format::MeasureUnit::convert_measurement(
+            self.value(),
+            self.unit(),
+            self.dpi(),
+            format::MeasureUnit::Px,
+            self.dpi(),
+        )
+    }
+
+    /// Sets the value from pixels, converted into the current unit
+    pub fn set_value_in_px(&self, value_in_px: f64) {
+        self.set_value(format::MeasureUnit::convert_measurement(
+            value_in_px,
+            format::MeasureUnit::Px,
+            self.dpi(),
+            self.unit(),
+            self.dpi(),
+        ));
+    }
+
     pub fn convert_current_value(&self, desired_unit: format::MeasureUnit) {
         let converted_value = format::MeasureUnit::convert_measurement(
             self.value(),
@@ -1,4 +1,7 @@
 mod imp {
+    use std::cell::RefCell;
+    use std::path::PathBuf;
+
     use gtk4::{gdk, DragSource, Image, Label};
     use gtk4::{glib, prelude::*, subclass::prelude::*, CompositeTemplate, Widget};
 
@@ -9,7 +12,11 @@ mod imp {
         #[template_child]
         pub file_image: TemplateChild<Image>,
         #[template_child]
+        pub thumbnail_image: TemplateChild<Image>,
+        #[template_child]
         pub file_label: TemplateChild<Label>,
+        /// the file the thumbnail is currently loaded for, to discard thumbnails which finish after the row was reused
+        pub thumbnail_path: RefCell<Option<PathBuf>>,
     }
 
     impl Default for FileRow {
@@ -22,7 +29,9 @@ mod imp {
             Self {
                 drag_source,
                 file_image: TemplateChild::<Image>::default(),
+                thumbnail_image: TemplateChild::<Image>::default(),
                 file_label: TemplateChild::<Label>::default(),
+                thumbnail_path: RefCell::new(None),
             }
         }
     }
@@ -59,8 +68,20 @@ mod imp {
     impl WidgetImpl for FileRow {}
 }
 
-use gtk4::{glib, subclass::prelude::*, Widget};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::render::{self, tinyskia::TinySkiaRenderer};
+use crate::sheet::Sheet;
+
+use gtk4::{gdk, gio, glib, glib::clone, prelude::*, subclass::prelude::*, Widget};
 use gtk4::{DragSource, Image, Label};
+use once_cell::sync::Lazy;
+
+/// Shared by all rows, as loading the system fonts is expensive
+static THUMBNAIL_RENDERER: Lazy<TinySkiaRenderer> = Lazy::new(TinySkiaRenderer::with_system_fonts);
 
 glib::wrapper! {
     pub struct FileRow(ObjectSubclass<imp::FileRow>)
@@ -74,6 +95,9 @@ impl Default for FileRow {
 }
 
 impl FileRow {
+    /// The size of the thumbnails in logical pixels, same as the pixel size of the thumbnail image
+    pub const THUMBNAIL_SIZE: i32 = 32;
+
     pub fn new() -> Self {
         let filerow: Self = glib::Object::new(&[]).expect("Failed to create `FileRow`");
         filerow
@@ -83,6 +107,10 @@ impl FileRow {
         imp::FileRow::from_instance(self).file_image.clone()
     }
 
+    pub fn thumbnail_image(&self) -> Image {
+        imp::FileRow::from_instance(self).thumbnail_image.clone()
+    }
+
     pub fn file_label(&self) -> Label {
         imp::FileRow::from_instance(self).file_label.clone()
     }
@@ -90,4 +118,150 @@ impl FileRow {
     pub fn drag_source(&self) -> DragSource {
         imp::FileRow::from_instance(self).drag_source.clone()
     }
+
+    /// Shows the thumbnail instead of the file icon. None shows the icon again
+    pub fn set_thumbnail(&self, thumbnail: Option<&gdk::Texture>) {
+        self.thumbnail_image().set_paintable(thumbnail);
+        self.thumbnail_image().set_visible(thumbnail.is_some());
+        self.file_image().set_visible(thumbnail.is_none());
+    }
+
+    /// Shows the thumbnail for rnote files, other files keep their icon.
+    /// The thumbnail generated by the installed thumbnailer is preferred, else it is rendered in the background and cached
+    pub fn load_thumbnail(&self, fileinfo: Option<&gio::FileInfo>) {
+        self.set_thumbnail(None);
+
+        let path = fileinfo
+            .and_then(|fileinfo| fileinfo.attribute_object("standard::file"))
+            .and_then(|file| file.downcast::<gio::File>().ok())
+            .and_then(|file| file.path())
+            .filter(|path| {
+                path.extension()
+                    .map_or(false, |extension| extension == "rnote")
+            });
+        imp::FileRow::from_instance(self)
+            .thumbnail_path
+            .replace(path.clone());
+        let (fileinfo, path) = match (fileinfo, path) {
+            (Some(fileinfo), Some(path)) => (fileinfo, path),
+            _ => return,
+        };
+
+        // The thumbnail from the freedesktop thumbnail cache, filled by the installed thumbnailer
+        if fileinfo.attribute_boolean("thumbnail::is-valid") {
+            if let Some(thumbnail_path) = fileinfo.attribute_byte_string("thumbnail::path") {
+                match gdk::Texture::from_file(&gio::File::for_path(thumbnail_path.as_str())) {
+                    Ok(texture) => {
+                        self.set_thumbnail(Some(&texture));
+                        return;
+                    }
+                    Err(e) => {
+                        log::debug!(
+                            "loading cached thumbnail `{}` failed in load_thumbnail() with Err {}",
+                            thumbnail_path,
+                            e
+                        );
+                    }
+                }
+            }
+        }
+
+        let modified = match std::fs::metadata(&path).and_then(|metadata| metadata.modified()) {
+            Ok(modified) => modified,
+            Err(e) => {
+                log::debug!(
+                    "reading the modification time failed in load_thumbnail() for file `{}` with Err {}",
+                    path.display(),
+                    e
+                );
+                return;
+            }
+        };
+
+        if let Some(texture) = cached_thumbnail(&path, modified) {
+            self.set_thumbnail(Some(&texture));
+            return;
+        }
+
+        let size = (Self::THUMBNAIL_SIZE * self.scale_factor()) as u32;
+        let (thumbnail_tx, thumbnail_rx) =
+            glib::MainContext::channel::<render::Image>(glib::PRIORITY_DEFAULT_IDLE);
+
+        rayon::spawn(clone!(@strong path => move || {
+            match load_rnote_thumbnail(&path, size) {
+                Ok(Some(thumbnail)) => {
+                    if let Err(e) = thumbnail_tx.send(thumbnail) {
+                        log::error!("sending thumbnail failed in load_thumbnail() with Err {}", e);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    log::debug!(
+                        "load_rnote_thumbnail() failed in load_thumbnail() for file `{}` with Err {}",
+                        path.display(),
+                        e
+                    );
+                }
+            }
+        }));
+
+        thumbnail_rx.attach(
+            None,
+            clone!(@weak self as filerow => @default-return glib::Continue(false), move |thumbnail| {
+                match render::image_to_memtexture(&thumbnail) {
+                    Ok(texture) => {
+                        let texture = texture.upcast::<gdk::Texture>();
+                        cache_thumbnail(path.clone(), modified, texture.clone());
+
+                        // The row might already show a different file
+                        if filerow.thumbnail_path().as_ref() == Some(&path) {
+                            filerow.set_thumbnail(Some(&texture));
+                        }
+                    }
+                    Err(e) => {
+                        log::error!("image_to_memtexture() failed in load_thumbnail() with Err {}", e);
+                    }
+                }
+
+                glib::Continue(false)
+            }),
+        );
+    }
+
+    pub fn thumbnail_path(&self) -> Option<PathBuf> {
+        imp::FileRow::from_instance(self)
+            .thumbnail_path
+            .borrow()
+            .clone()
+    }
+}
+
+/// Loads the rnote file and renders its thumbnail, the same way as the thumbnailer does
+fn load_rnote_thumbnail(path: &Path, size: u32) -> Result<Option<render::Image>, anyhow::Error> {
+    let bytes = std::fs::read(path)?;
+
+    Sheet::from_rnote_bytes(&bytes)?.gen_thumbnail(size, &THUMBNAIL_RENDERER)
+}
+
+thread_local! {
+    /// The rendered thumbnails with the modification time of their file, so rows which are bound again don't render again.
+    /// Textures are only used on the main thread
+    static THUMBNAIL_CACHE: RefCell<HashMap<PathBuf, (SystemTime, gdk::Texture)>> =
+        RefCell::new(HashMap::new());
+}
+
+fn cached_thumbnail(path: &Path, modified: SystemTime) -> Option<gdk::Texture> {
+    THUMBNAIL_CACHE.with(|cache| {
+        cache
+            .borrow()
+            .get(path)
+            .filter(|(cached_modified, _)| *cached_modified == modified)
+            .map(|(_, texture)| texture.clone())
+    })
+}
+
+fn cache_thumbnail(path: PathBuf, modified: SystemTime, texture: gdk::Texture) {
+    THUMBNAIL_CACHE.with(|cache| {
+        cache.borrow_mut().insert(path, (modified, texture));
+    });
 }
@@ -26,7 +26,7 @@ mod imp {
     impl Default for WorkspaceBrowser {
         fn default() -> Self {
             let primary_dirlist =
-                DirectoryList::new(Some("standard::*"), None as Option<&gio::File>);
+                DirectoryList::new(Some("standard::*,thumbnail::*"), None as Option<&gio::File>);
             primary_dirlist.set_monitored(true);
 
             Self {
@@ -251,6 +251,28 @@ impl WorkspaceBrowser {
                 Some(&filerow.drag_source()),
             );
         });
+
+        primary_list_factory.connect_bind(move |_, list_item| {
+            if let Some(filerow) = list_item
+                .child()
+                .and_then(|child| child.downcast::<FileRow>().ok())
+            {
+                let fileinfo = list_item
+                    .item()
+                    .and_then(|item| item.downcast::<gio::FileInfo>().ok());
+
+                filerow.load_thumbnail(fileinfo.as_ref());
+            }
+        });
+
+        primary_list_factory.connect_unbind(move |_, list_item| {
+            if let Some(filerow) = list_item
+                .child()
+                .and_then(|child| child.downcast::<FileRow>().ok())
+            {
+                filerow.load_thumbnail(None);
+            }
+        });
         let filefilter = FileFilter::new();
         filefilter.add_pattern("*.rnote");
         filefilter.add_pattern("*.svg");